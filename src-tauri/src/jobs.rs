@@ -0,0 +1,475 @@
+//! Persisted batch queue: stitch jobs survive an app restart and run in the background
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use tauri::{State, Window};
+use uuid::Uuid;
+
+use crate::{partial_output_path, run_stitch, StitchManifest};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A stitch request waiting in, or finished by, the batch queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedJob {
+    pub id: String,
+    pub request: StitchManifest,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub error: Option<String>,
+    /// Label of the window that queued the job. The queue itself is shared by every
+    /// window, so ids are unique across all of them.
+    #[serde(default)]
+    pub window_id: Option<String>,
+}
+
+/// The queue as saved to disk
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueState {
+    jobs: Vec<QueuedJob>,
+    max_parallel: usize,
+    #[serde(skip)]
+    running: usize,
+    #[serde(skip)]
+    cancel_flags: HashMap<String, Arc<AtomicBool>>, // Keyed by id, for running jobs only
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    wake: Condvar,
+    path: PathBuf,
+}
+
+/// Managed state handle to the batch queue
+#[derive(Clone)]
+pub struct JobQueue {
+    shared: Arc<Shared>,
+}
+
+thread_local! {
+    /// Id and cancel flag of the job running on this thread, read by the FFmpeg runner
+    static CURRENT_JOB: RefCell<Option<(String, Arc<AtomicBool>)>> = const { RefCell::new(None) };
+}
+
+/// Cancel flag for the job being rendered on the current thread, if any
+pub(crate) fn current_cancel_flag() -> Option<Arc<AtomicBool>> {
+    CURRENT_JOB.with(|job| job.borrow().as_ref().map(|(_, cancel)| cancel.clone()))
+}
+
+/// Id of the job being rendered on the current thread, if any
+pub(crate) fn current_job_id() -> Option<String> {
+    CURRENT_JOB.with(|job| job.borrow().as_ref().map(|(id, _)| id.clone()))
+}
+
+/// A random (v4) UUID, so ids stay unique across windows and restarts of the app
+pub(crate) fn next_job_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+impl JobQueue {
+    /// Load the queue saved at `path`. Jobs that were running when the app stopped go
+    /// back to pending, and their partial outputs are removed so the resume starts clean.
+    pub fn load(path: PathBuf) -> Self {
+        let mut state: QueueState = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        state.max_parallel = state.max_parallel.max(1);
+
+        for job in &mut state.jobs {
+            if job.status == JobStatus::Running {
+                if !job.request.confirms_overwrite() && !job.request.appends_in_place() {
+                    let _ = fs::remove_file(&job.request.output_path);
+                }
+                let _ = fs::remove_file(partial_output_path(&job.request.output_path));
+                job.status = JobStatus::Pending;
+            }
+        }
+
+        JobQueue {
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                wake: Condvar::new(),
+                path,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Save the queue, going through a temp file so a crash can't leave it half-written
+    fn save(&self, state: &QueueState) {
+        if let Some(parent) = self.shared.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(state) {
+            let temp_path = self.shared.path.with_extension("json.tmp");
+            if fs::write(&temp_path, json).is_ok() {
+                let _ = fs::rename(&temp_path, &self.shared.path);
+            }
+        }
+    }
+
+    /// Save the queue and wake the dispatcher to look for work
+    fn changed(&self, state: &QueueState) {
+        self.save(state);
+        self.shared.wake.notify_all();
+    }
+
+    /// Start the dispatcher thread, which launches pending jobs whenever fewer than
+    /// `max_parallel` are running
+    pub fn start(&self) {
+        let queue = self.clone();
+        thread::spawn(move || loop {
+            let job = {
+                let mut state = queue.lock();
+                loop {
+                    let next = state
+                        .jobs
+                        .iter()
+                        .position(|job| job.status == JobStatus::Pending);
+                    if let (true, Some(index)) = (state.running < state.max_parallel, next) {
+                        let job = &mut state.jobs[index];
+                        job.status = JobStatus::Running;
+                        job.attempts += 1;
+                        job.error = None;
+                        let job = job.clone();
+                        state
+                            .cancel_flags
+                            .insert(job.id.clone(), Arc::new(AtomicBool::new(false)));
+                        state.running += 1;
+                        queue.save(&state);
+                        break job;
+                    }
+                    state = queue
+                        .shared
+                        .wake
+                        .wait(state)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                }
+            };
+
+            let worker = queue.clone();
+            thread::spawn(move || worker.run_job(job));
+        });
+    }
+
+    /// Add a pending job queued from `window_id`, returning its id
+    fn push(&self, window_id: &str, request: StitchManifest) -> String {
+        let id = next_job_id();
+
+        let mut state = self.lock();
+        state.jobs.push(QueuedJob {
+            id: id.clone(),
+            request,
+            status: JobStatus::Pending,
+            attempts: 0,
+            error: None,
+            window_id: Some(window_id.to_string()),
+        });
+        self.changed(&state);
+
+        id
+    }
+
+    /// The jobs in the order they were added, only those from `window_id` if given
+    fn jobs(&self, window_id: Option<&str>) -> Vec<QueuedJob> {
+        self.lock()
+            .jobs
+            .iter()
+            .filter(|job| window_id.is_none() || job.window_id.as_deref() == window_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Cancel a queued or running job by id
+    fn cancel(&self, job_id: &str) -> Result<(), String> {
+        let mut state = self.lock();
+        let Some(job) = state.jobs.iter_mut().find(|job| job.id == job_id) else {
+            return Err(format!("No job with id {}", job_id));
+        };
+
+        match job.status {
+            JobStatus::Pending | JobStatus::Running => job.status = JobStatus::Cancelled,
+            _ => return Err("Job has already finished".to_string()),
+        }
+        // A running render stops at FFmpeg's next poll
+        if let Some(flag) = state.cancel_flags.get(job_id) {
+            flag.store(true, Ordering::Relaxed);
+        }
+        self.changed(&state);
+
+        Ok(())
+    }
+
+    fn run_job(&self, job: QueuedJob) {
+        let cancel = self.lock().cancel_flags.get(&job.id).cloned();
+        let current = cancel.map(|cancel| (job.id.clone(), cancel));
+        CURRENT_JOB.with(|slot| *slot.borrow_mut() = current);
+        let result = tauri::async_runtime::block_on(run_stitch(job.request));
+        CURRENT_JOB.with(|slot| *slot.borrow_mut() = None);
+
+        let mut state = self.lock();
+        state.cancel_flags.remove(&job.id);
+        // A job cancelled mid-render stays cancelled rather than showing up as failed
+        if let Some(entry) = state
+            .jobs
+            .iter_mut()
+            .find(|entry| entry.id == job.id && entry.status != JobStatus::Cancelled)
+        {
+            if result.success {
+                entry.status = JobStatus::Completed;
+                entry.error = None;
+            } else {
+                entry.status = JobStatus::Failed;
+                entry.error = result.error;
+            }
+        }
+        state.running -= 1;
+        self.changed(&state);
+    }
+}
+
+/// Add a stitch to the batch queue, returning its job id
+#[tauri::command]
+pub fn enqueue_stitch(window: Window, queue: State<'_, JobQueue>, job: StitchManifest) -> String {
+    queue.push(window.label(), job)
+}
+
+/// List the jobs in the queue in the order they were added, optionally only those
+/// queued from one window
+#[tauri::command]
+pub fn list_jobs(queue: State<'_, JobQueue>, window_id: Option<String>) -> Vec<QueuedJob> {
+    queue.jobs(window_id.as_deref())
+}
+
+/// Cancel a queued or running job. Works from any window, since jobs are found by id
+/// rather than by the window that queued them.
+#[tauri::command]
+pub fn cancel_stitch(queue: State<'_, JobQueue>, job_id: String) -> Result<(), String> {
+    queue.cancel(&job_id)
+}
+
+/// Set how many queued jobs may render at once
+#[tauri::command]
+pub fn set_max_parallel(queue: State<'_, JobQueue>, max_parallel: usize) -> Result<(), String> {
+    if max_parallel == 0 {
+        return Err("max_parallel must be at least 1".to_string());
+    }
+
+    let mut state = queue.lock();
+    state.max_parallel = max_parallel;
+    queue.changed(&state);
+
+    Ok(())
+}
+
+/// Remove completed and cancelled jobs from the queue, returning how many were removed
+#[tauri::command]
+pub fn clear_completed_jobs(queue: State<'_, JobQueue>) -> usize {
+    let mut state = queue.lock();
+    let before = state.jobs.len();
+    state
+        .jobs
+        .retain(|job| !matches!(job.status, JobStatus::Completed | JobStatus::Cancelled));
+    let removed = before - state.jobs.len();
+    queue.changed(&state);
+
+    removed
+}
+
+/// Put failed jobs back in line, returning how many were requeued
+#[tauri::command]
+pub fn retry_failed_jobs(queue: State<'_, JobQueue>) -> usize {
+    let mut state = queue.lock();
+    let mut retried = 0;
+    for job in &mut state.jobs {
+        if job.status == JobStatus::Failed {
+            // A failed render may have left a partial file behind. With confirm_overwrite
+            // the failure may instead be a refusal to replace, so the retry asks again, and
+            // an in-place append only ever wrote to its .part file.
+            if !job.request.confirms_overwrite() && !job.request.appends_in_place() {
+                let _ = fs::remove_file(&job.request.output_path);
+            }
+            let _ = fs::remove_file(partial_output_path(&job.request.output_path));
+            job.status = JobStatus::Pending;
+            retried += 1;
+        }
+    }
+    queue.changed(&state);
+
+    retried
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{ffmpeg_available, test_dir};
+    use crate::{AudioClip, AudioConfig, OutputFormat};
+    use std::path::Path;
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    fn request(output_path: &str) -> StitchManifest {
+        StitchManifest {
+            format: OutputFormat::Mp3,
+            clips: Vec::new(),
+            output_path: output_path.to_string(),
+            bitrate: "128k".to_string(),
+            audio_config: None,
+            video_config: None,
+        }
+    }
+
+    #[test]
+    fn jobs_stay_with_the_window_that_queued_them() {
+        let dir = std::env::temp_dir().join(format!("rts-jobs-{}", next_job_id()));
+        let queue = JobQueue::load(dir.join("queue.json"));
+
+        let first = queue.push("main", request("first.mp3"));
+        let second = queue.push("second", request("second.mp3"));
+        let third = queue.push("main", request("third.mp3"));
+
+        for id in [&first, &second, &third] {
+            assert_eq!(Uuid::parse_str(id).unwrap().get_version_num(), 4);
+        }
+        let ids = |window_id| -> Vec<String> {
+            queue
+                .jobs(window_id)
+                .into_iter()
+                .map(|job| job.id)
+                .collect()
+        };
+        assert_eq!(ids(None), [first.clone(), second.clone(), third.clone()]);
+        assert_eq!(ids(Some("main")), [first, third]);
+        assert_eq!(ids(Some("second")), [second]);
+        assert!(ids(Some("other")).is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    /// Poll until `done` holds, failing the test after a minute
+    fn wait_for(what: &str, mut done: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        while !done() {
+            assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn cancelling_mid_render_leaves_no_output() {
+        if !ffmpeg_available() {
+            return;
+        }
+        let dir = test_dir("cancel");
+        // Long enough that the encode is still running when it's cancelled
+        let clip_path = dir.join("long.wav");
+        let status = Command::new("ffmpeg")
+            .args(["-v", "error", "-nostdin", "-y", "-f", "lavfi", "-i"])
+            .arg("sine=frequency=440:sample_rate=8000:duration=3600")
+            .arg(&clip_path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let clip = AudioClip {
+            id: "long".to_string(),
+            path: clip_path.to_string_lossy().to_string(),
+            name: "long".to_string(),
+            duration: 3600.0,
+            size: fs::metadata(&clip_path).unwrap().len(),
+            metadata: None,
+            order: None,
+            pan: None,
+        };
+        let queue = JobQueue::load(dir.join("queue.json"));
+        queue.start();
+
+        for atomic_write in [false, true] {
+            let output = dir.join(format!("out-{}.mp3", atomic_write));
+            let output_path = output.to_string_lossy().to_string();
+            let part = partial_output_path(&output_path);
+            let mut job = request(&output_path);
+            job.clips = vec![clip.clone()];
+            job.audio_config = Some(AudioConfig {
+                atomic_write,
+                ..Default::default()
+            });
+            let id = queue.push("main", job);
+
+            // Cancel once FFmpeg has started writing
+            let written = if atomic_write { &part } else { &output_path };
+            wait_for("the render to start writing", || {
+                Path::new(written).metadata().is_ok_and(|m| m.len() > 0)
+            });
+            queue.cancel(&id).unwrap();
+            wait_for("the render to stop", || queue.lock().running == 0);
+
+            assert_eq!(
+                queue.jobs(None).last().unwrap().status,
+                JobStatus::Cancelled
+            );
+            assert!(!output.exists(), "{} was left behind", output.display());
+            assert!(!Path::new(&part).exists(), "{} was left behind", part);
+        }
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn resuming_an_in_place_append_keeps_the_base() {
+        let dir = test_dir("resume-append");
+        let base = dir.join("log.mp3");
+        let base_path = base.to_string_lossy().to_string();
+        let part = partial_output_path(&base_path);
+        fs::write(&base, b"original recording").unwrap();
+        fs::write(&part, b"half an append").unwrap();
+
+        let mut request = request(&base_path);
+        request.audio_config = Some(AudioConfig {
+            append_to: Some(base_path.clone()),
+            ..Default::default()
+        });
+        assert!(request.appends_in_place());
+        let saved = QueueState {
+            jobs: vec![QueuedJob {
+                id: next_job_id(),
+                request,
+                status: JobStatus::Running,
+                attempts: 1,
+                error: None,
+                window_id: Some("main".to_string()),
+            }],
+            max_parallel: 1,
+            ..Default::default()
+        };
+        let queue_path = dir.join("queue.json");
+        fs::write(&queue_path, serde_json::to_string(&saved).unwrap()).unwrap();
+
+        let queue = JobQueue::load(queue_path);
+        assert_eq!(queue.jobs(None)[0].status, JobStatus::Pending);
+        assert_eq!(fs::read(&base).unwrap(), b"original recording");
+        assert!(!Path::new(&part).exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
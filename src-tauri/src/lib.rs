@@ -1,23 +1,81 @@
+mod jobs;
+
 use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::Write;
-use std::path::PathBuf;
-use std::process::Command;
+use std::io::{self, Read, Write};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager, State};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioClip {
     pub id: String,
     pub path: String,
     pub name: String,
     pub duration: f64,
     pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>, // Recorder-supplied details, passed through as-is
+    /// Explicit position in the output, overriding list order; set on every clip or none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<u32>,
+    /// Stereo position from -1.0 (left) to 1.0 (right). When any clip is panned the output
+    /// is stereo and unpanned clips sit in the center.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pan: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct StitchResult {
     pub success: bool,
     pub output_path: Option<String>,
     pub error: Option<String>,
+    pub clip_count: Option<usize>,
+    pub manifest_path: Option<String>,
+    pub resampler: Option<String>,
+    pub chapters_path: Option<String>,
+    pub mtime: Option<u64>, // Modification time applied to the output, in Unix seconds
+    pub clipping_warning: bool, // Normalization/gain pushed the output peak to (near) 0 dBFS
+    pub limiter_engaged: Option<bool>, // Output peak reached the limiter ceiling
+    pub concat_method: Option<String>, // "demuxer" (stream-level join) or "filter" (decoded join)
+    pub concat_reason: Option<String>, // Why that method was used, where the caller had a say
+    pub audio_encoder: Option<String>, // AAC encoder a video's audio went through
+    pub downmix: Option<String>, // pan filter used to fold channels to mono
+    pub audio_output_path: Option<String>, // Standalone audio exported next to a video
+    pub skipped: Vec<String>, // Paths of empty clips left out of the output
+    pub dc_offset_removed: Option<f64>, // Mean DC offset magnitude across the clips, as a sample fraction
+    pub cancelled: bool,                // Stopped by cancel_stitch; nothing was left behind
+    pub duration: Option<f64>,          // Expected output length in seconds, after any speed change
+    pub gapless: Option<bool>,          // MP3 only: output has the encoder delay/padding header
+    pub truncated: Vec<ClipTruncation>, // Clips cut short by cap_clip_length or the length limit
+    pub dropped: Vec<String>,           // Paths of clips left out to fit max_total_duration
+    pub stream_copy: bool, // Audio was copied as-is (like-format clips), not re-encoded
+    pub silence_trimmed: Option<f64>, // Seconds of dead air cut from clip edges in all
+    // Render performance, set on success only
+    pub elapsed_seconds: Option<f64>, // Wall time of the whole stitch
+    pub realtime_factor: Option<f64>, // Seconds of output per second of wall time
+    pub throughput_mb_s: Option<f64>, // Output size (MB, 10^6 bytes) per second of wall time
+    pub output_bytes: Option<u64>,    // Size of the finished output
+    pub slideshow_images: Option<usize>, // Background images the slideshow actually showed
+    pub output_channels: Option<u32>, // Channel count every clip was brought to
+    pub channels_converted: Option<usize>, // Clips that had a different channel count
+    pub warnings: Vec<String>,
+}
+
+/// A clip cut off at the length cap, and how much of it was dropped
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClipTruncation {
+    pub path: String,
+    pub original_seconds: f64,
+    pub removed_seconds: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,413 +84,9891 @@ pub struct AudioInfo {
     pub size: u64,
     pub valid: bool,
     pub error: Option<String>,
+    // From the first audio stream; None when ffprobe doesn't report them
+    pub codec: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u8>,
+    pub bit_rate: Option<u32>, // Falls back to the container's overall bit rate
+    pub sample_format: Option<String>, // e.g. s16, fltp; tells 16-bit from float sources
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl AudioInfo {
+    fn invalid(size: u64, error: String) -> Self {
+        AudioInfo {
+            duration: 0.0,
+            size,
+            valid: false,
+            error: Some(error),
+            codec: None,
+            sample_rate: None,
+            channels: None,
+            bit_rate: None,
+            sample_format: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ImageFitMode {
-    Fit,  // Letterbox/pillarbox with black bars
+    #[default]
+    Fit, // Letterbox/pillarbox with black bars
     Fill, // Scale and crop to fill
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VideoConfig {
-    pub image_path: Option<String>,
-    pub fit_mode: ImageFitMode,
+/// Shape of stitch_video's frame, each with a standard resolution
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Landscape, // 1920x1080
+    Portrait, // 1080x1920, e.g. for Shorts and TikTok
+    Square,   // 1080x1080
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ImageInfo {
-    pub width: u32,
-    pub height: u32,
-    pub valid: bool,
-    pub error: Option<String>,
+impl Orientation {
+    fn size(self) -> (u32, u32) {
+        match self {
+            Orientation::Landscape => (1920, 1080),
+            Orientation::Portrait => (1080, 1920),
+            Orientation::Square => (1080, 1080),
+        }
+    }
 }
 
-/// Get audio file information using ffprobe
-#[tauri::command]
-fn get_audio_info(path: String) -> AudioInfo {
-    let path_buf = PathBuf::from(&path);
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Mp3, // Audio only
+    Mp4,  // Video with background
+    Opus, // Low-bitrate voice audio in an Ogg container (.opus/.ogg)
+}
 
-    // Check if file exists
-    if !path_buf.exists() {
-        return AudioInfo {
-            duration: 0.0,
-            size: 0,
-            valid: false,
-            error: Some("File does not exist".to_string()),
-        };
+impl OutputFormat {
+    /// Default file extension for outputs in this format
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp3 => "mp3",
+            OutputFormat::Mp4 => "mp4",
+            OutputFormat::Opus => "opus",
+        }
     }
 
-    // Get file size
-    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    /// Extensions FFmpeg maps to a muxer that can hold this format
+    fn accepted_extensions(&self) -> &'static [&'static str] {
+        match self {
+            OutputFormat::Mp3 => &["mp3"],
+            OutputFormat::Mp4 => &["mp4", "m4v", "mov"],
+            OutputFormat::Opus => &["opus", "ogg"],
+        }
+    }
+}
 
-    // Try to get duration using ffprobe
-    let output = Command::new("ffprobe")
-        .args([
-            "-v",
-            "error",
-            "-show_entries",
-            "format=duration",
-            "-of",
-            "default=noprint_wrappers=1:nokey=1",
-            &path,
-        ])
-        .output();
+/// Check the output's extension against the format, since FFmpeg picks the muxer from it.
+/// A path with no extension gets the format's default appended; an explicit container
+/// override makes any name acceptable. Returns the path to write.
+fn resolve_output_path(
+    output_path: &str,
+    format: OutputFormat,
+    container: Option<&str>,
+) -> Result<String, String> {
+    if container.is_some() {
+        return Ok(output_path.to_string());
+    }
 
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let duration_str = String::from_utf8_lossy(&output.stdout);
-                let duration: f64 = duration_str.trim().parse().unwrap_or(0.0);
-                AudioInfo {
-                    duration,
-                    size,
-                    valid: true,
-                    error: None,
-                }
-            } else {
-                let error = String::from_utf8_lossy(&output.stderr);
-                AudioInfo {
-                    duration: 0.0,
-                    size,
-                    valid: false,
-                    error: Some(format!("FFprobe error: {}", error)),
-                }
-            }
+    let path = Path::new(output_path);
+    match path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+    {
+        None => Ok(format!("{}.{}", output_path, format.extension())),
+        Some(ext) if format.accepted_extensions().contains(&ext.as_str()) => {
+            Ok(output_path.to_string())
         }
-        Err(e) => AudioInfo {
-            duration: 0.0,
-            size,
-            valid: false,
-            error: Some(format!(
-                "Failed to run ffprobe: {}. Is FFmpeg installed?",
-                e
-            )),
-        },
+        Some(ext) => Err(format!(
+            "Output extension '.{}' doesn't match {} output; use .{}",
+            ext,
+            format.extension().to_uppercase(),
+            format.accepted_extensions().join(" or .")
+        )),
     }
 }
 
-/// Stitch multiple audio clips into a single MP3 file
-#[tauri::command]
-async fn stitch_audio(clips: Vec<AudioClip>, output_path: String, bitrate: String) -> StitchResult {
-    if clips.is_empty() {
-        return StitchResult {
-            success: false,
-            output_path: None,
-            error: Some("No clips provided".to_string()),
+/// URL schemes a render can be pushed to directly
+const STREAM_URL_SCHEMES: [&str; 4] = ["rtmp", "rtmps", "http", "https"];
+
+/// Where FFmpeg writes a render
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputTarget {
+    File,
+    /// Stdout, streamed back to the caller as `stitch-output-chunk` events
+    Pipe,
+    /// A streaming server, which FFmpeg connects to itself
+    Url,
+}
+
+/// Classify an output path. `pipe:` (or `pipe:1`) means stdout. Anything shaped like a URL
+/// must use one of the streaming schemes, so a mistyped URL fails instead of landing on
+/// disk as an oddly named file.
+fn output_target(output_path: &str) -> Result<OutputTarget, String> {
+    if let Some(fd) = output_path.strip_prefix("pipe:") {
+        return match fd {
+            "" | "1" => Ok(OutputTarget::Pipe),
+            _ => Err("Only pipe: or pipe:1 (stdout) can be used as a pipe target".to_string()),
         };
     }
+    let Some((scheme, rest)) = output_path.split_once("://") else {
+        return Ok(OutputTarget::File);
+    };
+    if scheme.is_empty()
+        || !scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    {
+        return Ok(OutputTarget::File);
+    }
 
-    // Create a temporary file for the concat list
-    let temp_dir = std::env::temp_dir();
-    let concat_file_path = temp_dir.join("ffmpeg_concat_list.txt");
+    let scheme = scheme.to_lowercase();
+    if !STREAM_URL_SCHEMES.contains(&scheme.as_str()) {
+        return Err(format!(
+            "Unsupported output URL scheme '{}'; use {}",
+            scheme,
+            STREAM_URL_SCHEMES.join(", ")
+        ));
+    }
+    if rest.split(['/', '?']).next().is_none_or(str::is_empty) {
+        return Err(format!("Output URL has no host: {}", output_path));
+    }
+    Ok(OutputTarget::Url)
+}
 
-    // Create the concat file
-    let mut concat_file = match File::create(&concat_file_path) {
-        Ok(f) => f,
-        Err(e) => {
-            return StitchResult {
-                success: false,
-                output_path: None,
-                error: Some(format!("Failed to create temp file: {}", e)),
-            };
-        }
-    };
+/// Muxer for a pipe or URL target, which has no extension for FFmpeg to pick one from.
+/// RTMP only carries FLV, which has no place for Opus.
+fn stream_container(output_path: &str, format: OutputFormat) -> Result<&'static str, String> {
+    let rtmp = output_path.to_lowercase().starts_with("rtmp");
+    match (format, rtmp) {
+        (OutputFormat::Mp3, true) => Ok("flv"),
+        (OutputFormat::Opus, true) => Err("Opus can't be streamed over RTMP; use MP3".to_string()),
+        (OutputFormat::Mp3, false) => Ok("mp3"),
+        (OutputFormat::Opus, false) => Ok("ogg"),
+        (OutputFormat::Mp4, _) => Err("MP4 output requires stitch_video".to_string()),
+    }
+}
 
-    // Write file paths to concat list
-    for clip in &clips {
-        // Escape single quotes in paths for FFmpeg
-        let escaped_path = clip.path.replace("'", "'\\''");
-        if writeln!(concat_file, "file '{}'", escaped_path).is_err() {
-            return StitchResult {
-                success: false,
-                output_path: None,
-                error: Some("Failed to write concat list".to_string()),
-            };
+/// libopus tuning, passed as `-application`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpusApplication {
+    #[default]
+    Voip, // Optimized for speech intelligibility
+    Audio,    // General audio fidelity
+    Lowdelay, // Lowest latency, slightly lower quality
+}
+
+impl OpusApplication {
+    fn as_str(&self) -> &'static str {
+        match self {
+            OpusApplication::Voip => "voip",
+            OpusApplication::Audio => "audio",
+            OpusApplication::Lowdelay => "lowdelay",
         }
     }
+}
 
-    // Close the file
-    drop(concat_file);
-
-    // Run FFmpeg to concatenate
-    let output = Command::new("ffmpeg")
-        .args([
-            "-y", // Overwrite output
-            "-f",
-            "concat", // Concat demuxer
-            "-safe",
-            "0", // Allow absolute paths
-            "-i",
-            concat_file_path.to_str().unwrap(),
-            "-c:a",
-            "libmp3lame", // MP3 codec
-            "-b:a",
-            &bitrate, // Bitrate
-            &output_path,
-        ])
-        .output();
+/// Where a clip lands in the stitched output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipPlacement {
+    pub index: usize,
+    pub start: f64, // Seconds from the start of the output
+    pub end: f64,
+    pub start_ms: u64, // Same offsets in whole milliseconds, as used for chapters
+    pub end_ms: u64,
+}
 
-    // Clean up temp file
-    let _ = fs::remove_file(&concat_file_path);
+/// Noise gate settings for suppressing hiss between transmissions (FFmpeg `agate`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GateConfig {
+    pub threshold_db: f64, // Level below which audio is attenuated
+    pub ratio: f64,        // Attenuation ratio below the threshold
+    pub attack_ms: f64,    // Time to open once the signal rises above the threshold
+    pub release_ms: f64,   // Time to close once the signal falls below the threshold
+}
 
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                StitchResult {
-                    success: true,
-                    output_path: Some(output_path),
-                    error: None,
-                }
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                StitchResult {
-                    success: false,
-                    output_path: None,
-                    error: Some(format!("FFmpeg error: {}", stderr)),
-                }
-            }
+impl Default for GateConfig {
+    // Tuned for scanner voice: opens quickly on speech, holds through short pauses
+    fn default() -> Self {
+        GateConfig {
+            threshold_db: -45.0,
+            ratio: 10.0,
+            attack_ms: 5.0,
+            release_ms: 250.0,
         }
-        Err(e) => StitchResult {
-            success: false,
-            output_path: None,
-            error: Some(format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e)),
-        },
     }
 }
 
-/// Get image file information using ffprobe
-#[tauri::command]
-fn get_image_info(path: String) -> ImageInfo {
-    let path_buf = PathBuf::from(&path);
+impl GateConfig {
+    fn filter(&self) -> String {
+        // agate takes the threshold as a linear amplitude
+        let threshold = 10f64.powf(self.threshold_db / 20.0);
+        format!(
+            "agate=threshold={:.6}:ratio={}:attack={}:release={}",
+            threshold, self.ratio, self.attack_ms, self.release_ms
+        )
+    }
+}
 
-    // Check if file exists
-    if !path_buf.exists() {
-        return ImageInfo {
-            width: 0,
-            height: 0,
-            valid: false,
-            error: Some("File does not exist".to_string()),
-        };
+/// EBU R128 loudness normalization targets (FFmpeg `loudnorm`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoudnormConfig {
+    pub integrated_lufs: f64, // Target integrated loudness
+    pub true_peak_db: f64,    // Maximum true peak
+    pub lra: f64,             // Target loudness range
+}
+
+impl Default for LoudnormConfig {
+    fn default() -> Self {
+        LoudnormConfig {
+            integrated_lufs: -16.0,
+            true_peak_db: -1.5,
+            lra: 11.0,
+        }
     }
+}
 
-    // Get image dimensions using ffprobe
-    let output = Command::new("ffprobe")
-        .args([
-            "-v",
-            "error",
-            "-select_streams",
-            "v:0",
-            "-show_entries",
-            "stream=width,height",
-            "-of",
-            "csv=s=x:p=0",
-            &path,
-        ])
-        .output();
+impl LoudnormConfig {
+    fn filter(&self) -> String {
+        format!(
+            "loudnorm=I={}:TP={}:LRA={}",
+            self.integrated_lufs, self.true_peak_db, self.lra
+        )
+    }
+}
 
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                let dimensions = String::from_utf8_lossy(&output.stdout);
-                let parts: Vec<&str> = dimensions.trim().split('x').collect();
-                if parts.len() == 2 {
-                    let width: u32 = parts[0].parse().unwrap_or(0);
-                    let height: u32 = parts[1].parse().unwrap_or(0);
-                    if width > 0 && height > 0 {
-                        return ImageInfo {
-                            width,
-                            height,
-                            valid: true,
-                            error: None,
-                        };
-                    }
-                }
-                ImageInfo {
-                    width: 0,
-                    height: 0,
-                    valid: false,
-                    error: Some("Could not parse image dimensions".to_string()),
-                }
-            } else {
-                let error = String::from_utf8_lossy(&output.stderr);
-                ImageInfo {
-                    width: 0,
-                    height: 0,
-                    valid: false,
-                    error: Some(format!("FFprobe error: {}", error)),
-                }
-            }
+/// Peak limiter settings (FFmpeg `alimiter`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimiterConfig {
+    pub limit_db: f64,   // Ceiling in dBFS
+    pub level: bool,     // Let alimiter auto-level the output up to the ceiling
+    pub attack_ms: f64,  // How fast gain reduction kicks in
+    pub release_ms: f64, // How fast gain recovers afterwards
+}
+
+impl Default for LimiterConfig {
+    // -1 dBFS leaves headroom for inter-sample peaks on lossy decode
+    fn default() -> Self {
+        LimiterConfig {
+            limit_db: -1.0,
+            level: false,
+            attack_ms: 5.0,
+            release_ms: 50.0,
         }
-        Err(e) => ImageInfo {
-            width: 0,
-            height: 0,
-            valid: false,
-            error: Some(format!(
-                "Failed to run ffprobe: {}. Is FFmpeg installed?",
-                e
-            )),
-        },
     }
 }
 
-/// Stitch multiple audio clips into a single MP4 video file with optional background image
-#[tauri::command]
-async fn stitch_video(
-    clips: Vec<AudioClip>,
-    output_path: String,
-    bitrate: String,
-    video_config: VideoConfig,
-) -> StitchResult {
-    if clips.is_empty() {
-        return StitchResult {
-            success: false,
-            output_path: None,
-            error: Some("No clips provided".to_string()),
-        };
+impl LimiterConfig {
+    fn filter(&self) -> String {
+        // alimiter takes the ceiling as a linear amplitude
+        let limit = 10f64.powf(self.limit_db / 20.0);
+        format!(
+            "alimiter=limit={:.6}:level={}:attack={}:release={}",
+            limit, self.level as u8, self.attack_ms, self.release_ms
+        )
     }
+}
 
-    // Create a temporary file for the concat list
-    let temp_dir = std::env::temp_dir();
-    let concat_file_path = temp_dir.join("ffmpeg_video_concat_list.txt");
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResampleQuality {
+    #[default]
+    Fast, // FFmpeg's built-in swr resampler
+    High, // soxr at 28-bit precision, when the FFmpeg build includes it
+}
 
-    // Create the concat file
-    let mut concat_file = match File::create(&concat_file_path) {
-        Ok(f) => f,
-        Err(e) => {
-            return StitchResult {
-                success: false,
-                output_path: None,
-                error: Some(format!("Failed to create temp file: {}", e)),
-            };
+/// Channel count to bring every clip to before joining
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelTarget {
+    Mono,
+    Stereo,
+    Majority, // Whatever count most clips already have
+}
+
+/// AAC profile for a video's audio track, passed as `-profile:a`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AacProfile {
+    Low,  // AAC-LC, the most widely decodable
+    He,   // HE-AAC, for low bitrates; needs libfdk_aac
+    HeV2, // HE-AAC v2 (parametric stereo); needs libfdk_aac
+}
+
+/// AAC encoder preference for a video's audio track
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AacEncoder {
+    Native, // FFmpeg's own aac, in every build
+    Fdk,    // libfdk_aac, better at low bitrates; falls back to native with a warning
+    Auto,   // libfdk_aac when the build has it, else native
+}
+
+/// Gain curve of one side of a crossfade, as named by acrossfade
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrossfadeCurve {
+    #[default]
+    Tri, // Linear
+    Qsin, // Quarter sine; equal power, so no dip in the middle
+    Esin, // Exponential sine
+    Log,  // Logarithmic
+    Exp,  // Exponential
+}
+
+impl CrossfadeCurve {
+    fn as_str(self) -> &'static str {
+        match self {
+            CrossfadeCurve::Tri => "tri",
+            CrossfadeCurve::Qsin => "qsin",
+            CrossfadeCurve::Esin => "esin",
+            CrossfadeCurve::Log => "log",
+            CrossfadeCurve::Exp => "exp",
         }
-    };
+    }
+}
 
-    // Write file paths to concat list
-    for clip in &clips {
-        // Escape single quotes in paths for FFmpeg
-        let escaped_path = clip.path.replace("'", "'\\''");
-        if writeln!(concat_file, "file '{}'", escaped_path).is_err() {
-            return StitchResult {
-                success: false,
-                output_path: None,
-                error: Some("Failed to write concat list".to_string()),
-            };
+/// Overlap consecutive clips instead of butting them together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrossfadeConfig {
+    pub duration_seconds: f64,
+    pub fade_out_curve: CrossfadeCurve, // Outgoing clip (acrossfade's c1)
+    pub fade_in_curve: CrossfadeCurve,  // Incoming clip (acrossfade's c2)
+}
+
+impl Default for CrossfadeConfig {
+    fn default() -> Self {
+        CrossfadeConfig {
+            duration_seconds: 1.0,
+            fade_out_curve: CrossfadeCurve::default(),
+            fade_in_curve: CrossfadeCurve::default(),
         }
     }
+}
 
-    // Close the file
-    drop(concat_file);
+impl CrossfadeConfig {
+    fn filter(&self) -> String {
+        format!(
+            "acrossfade=d={}:c1={}:c2={}",
+            self.duration_seconds,
+            self.fade_out_curve.as_str(),
+            self.fade_in_curve.as_str()
+        )
+    }
+}
 
-    // Build the FFmpeg command based on whether we have an image or not
-    let mut args: Vec<String> = vec!["-y".to_string()]; // Overwrite output
+/// How a stored image must be flipped or rotated to appear upright
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageOrientation {
+    Upright,
+    FlipHorizontal,
+    Rotate180,
+    FlipVertical,
+    Transpose,  // Mirrored along the top-left to bottom-right diagonal
+    Rotate90,   // Clockwise
+    Transverse, // Mirrored along the top-right to bottom-left diagonal
+    Rotate270,  // Clockwise
+}
 
-    // Video filter based on fit mode - using yuv444p for better color preservation with graphics
-    let vf = match video_config.fit_mode {
-        ImageFitMode::Fit => {
-            // Keep original size (no upscaling), only scale down if larger than 1920x1080
-            // Then center on 1920x1080 black canvas
-            "scale=iw*min(1\\,min(1920/iw\\,1080/ih)):ih*min(1\\,min(1920/iw\\,1080/ih)),pad=1920:1080:(ow-iw)/2:(oh-ih)/2:black,format=yuv444p".to_string()
+impl ImageOrientation {
+    /// Map an EXIF Orientation value (1-8)
+    fn from_exif(value: i32) -> Self {
+        match value {
+            2 => ImageOrientation::FlipHorizontal,
+            3 => ImageOrientation::Rotate180,
+            4 => ImageOrientation::FlipVertical,
+            5 => ImageOrientation::Transpose,
+            6 => ImageOrientation::Rotate90,
+            7 => ImageOrientation::Transverse,
+            8 => ImageOrientation::Rotate270,
+            _ => ImageOrientation::Upright,
         }
-        ImageFitMode::Fill => {
-            // Scale and crop to fill 1920x1080
-            "scale=1920:1080:force_original_aspect_ratio=increase,crop=1920:1080,format=yuv444p"
-                .to_string()
+    }
+
+    /// Map a clockwise rotation, snapped to the nearest quarter turn
+    fn from_clockwise_degrees(degrees: i32) -> Self {
+        match ((degrees as f64 / 90.0).round() as i32).rem_euclid(4) {
+            1 => ImageOrientation::Rotate90,
+            2 => ImageOrientation::Rotate180,
+            3 => ImageOrientation::Rotate270,
+            _ => ImageOrientation::Upright,
         }
-    };
+    }
 
-    if let Some(image_path) = &video_config.image_path {
-        // With image: loop the image for video stream
-        args.extend_from_slice(&[
-            "-loop".to_string(),
-            "1".to_string(),
+    fn filter(self) -> Option<&'static str> {
+        match self {
+            ImageOrientation::Upright => None,
+            ImageOrientation::FlipHorizontal => Some("hflip"),
+            ImageOrientation::Rotate180 => Some("hflip,vflip"),
+            ImageOrientation::FlipVertical => Some("vflip"),
+            ImageOrientation::Transpose => Some("transpose=cclock_flip"),
+            ImageOrientation::Rotate90 => Some("transpose=clock"),
+            ImageOrientation::Transverse => Some("transpose=clock_flip"),
+            ImageOrientation::Rotate270 => Some("transpose=cclock"),
+        }
+    }
+}
+
+/// A short beep played between clips to mark where each transmission starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkerTone {
+    pub frequency_hz: f64,
+    pub duration_seconds: f64,
+    pub volume_db: f64, // Kept well below full scale by default so it isn't jarring
+}
+
+impl Default for MarkerTone {
+    fn default() -> Self {
+        MarkerTone {
+            frequency_hz: 1000.0,
+            duration_seconds: 0.2,
+            volume_db: -18.0,
+        }
+    }
+}
+
+impl MarkerTone {
+    fn validate(&self) -> Result<(), String> {
+        if !(20.0..=20000.0).contains(&self.frequency_hz) {
+            return Err("Marker tone frequency must be between 20 and 20000 Hz".to_string());
+        }
+        if !(self.duration_seconds > 0.0 && self.duration_seconds <= 5.0) {
+            return Err("Marker tone duration must be between 0 and 5 seconds".to_string());
+        }
+        if !(-60.0..=0.0).contains(&self.volume_db) {
+            return Err("Marker tone volume must be between -60 and 0 dB".to_string());
+        }
+        Ok(())
+    }
+
+    /// Source filter generating one tone
+    fn filter(&self) -> String {
+        format!(
+            "sine=frequency={}:duration={},volume={}dB",
+            self.frequency_hz, self.duration_seconds, self.volume_db
+        )
+    }
+}
+
+/// How the filter path joins consecutive clips
+#[derive(Clone, Copy)]
+enum ClipJoin<'a> {
+    Concat,
+    Crossfade(&'a CrossfadeConfig),
+    MarkerTone(&'a MarkerTone),
+}
+
+/// What to do with clips that turn out to have no audio
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptyClipMode {
+    #[default]
+    Skip, // Leave them out and list them in `skipped`
+    Abort, // Fail the stitch
+}
+
+/// What to do when the concat demuxer would join VBR MP3 clips. Without a Xing header
+/// their lengths are estimated from the first frame's bitrate, so the joined output can
+/// report the wrong duration to players and seek bars.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VbrHandling {
+    #[default]
+    Warn, // Keep the fast path and warn that the duration may be off
+    FilterPath, // Decode the clips through the concat filter instead
+    /// Keep the fast path, then remux an MP3 output so its header counts every frame.
+    /// Opus outputs have nothing to rewrite, so they take the filter path.
+    FixDuration,
+}
+
+/// High/low-pass filtering to strip out-of-band rumble and hiss
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandFilter {
+    pub highpass_hz: Option<f64>, // Cut rumble below this frequency
+    pub lowpass_hz: Option<f64>,  // Cut hiss above this frequency
+    pub voice_band: bool,         // Default unset cutoffs to the 300-3400 Hz telephone band
+}
+
+impl BandFilter {
+    fn filters(&self) -> Result<Vec<String>, String> {
+        let (default_high, default_low) = if self.voice_band {
+            (Some(300.0), Some(3400.0))
+        } else {
+            (None, None)
+        };
+        let highpass = self.highpass_hz.or(default_high);
+        let lowpass = self.lowpass_hz.or(default_low);
+
+        if highpass.into_iter().chain(lowpass).any(|hz| hz <= 0.0) {
+            return Err("Band filter cutoffs must be greater than zero".to_string());
+        }
+        if let (Some(high), Some(low)) = (highpass, lowpass) {
+            if high >= low {
+                return Err(format!(
+                    "Highpass cutoff ({} Hz) must be below the lowpass cutoff ({} Hz)",
+                    high, low
+                ));
+            }
+        }
+
+        let mut filters = Vec::new();
+        if let Some(high) = highpass {
+            filters.push(format!("highpass=f={}", high));
+        }
+        if let Some(low) = lowpass {
+            filters.push(format!("lowpass=f={}", low));
+        }
+        Ok(filters)
+    }
+}
+
+/// A narrow cut at one frequency, for a squeal or whine found on the spectrogram
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotchFilter {
+    pub frequency_hz: f64,
+    pub q: f64, // Higher is narrower; 5-10 takes out a tone without dulling speech
+}
+
+/// Cut applied at each notch, deep enough to bury a tone
+const NOTCH_GAIN_DB: f64 = -30.0;
+
+/// Notches used by `auto_deharsh`: the squeal and whine bands that show up most on trunked
+/// and analog FM scanner feeds, as (frequency Hz, Q)
+const DEHARSH_NOTCHES: [(f64, f64); 3] = [(2600.0, 8.0), (3200.0, 8.0), (4000.0, 6.0)];
+
+impl NotchFilter {
+    fn filter(&self) -> Result<String, String> {
+        if self.frequency_hz <= 0.0 || self.q <= 0.0 {
+            return Err("Notch filter frequency and Q must be greater than zero".to_string());
+        }
+        Ok(format!(
+            "equalizer=f={}:width_type=q:width={}:g={}",
+            self.frequency_hz, self.q, NOTCH_GAIN_DB
+        ))
+    }
+}
+
+/// Optional audio processing applied when stitching
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    /// Block DC bias in each clip. Runs before the gate, whose threshold a bias would skew.
+    pub remove_dc_offset: bool,
+    /// Gate each clip before concatenation. The gate comes first in the per-clip chain
+    /// after DC removal, so any level normalization runs on the gated signal.
+    pub noise_gate: Option<GateConfig>,
+    pub band_filter: Option<BandFilter>, // Per-clip high/low-pass, applied after the gate
+    pub notch_filters: Vec<NotchFilter>, // Per-clip notches, in order, after the band filter
+    pub auto_deharsh: bool,              // Add the DEHARSH_NOTCHES preset after notch_filters
+    pub crossfade: Option<CrossfadeConfig>, // Blend each clip into the next
+    /// Fade each clip in and out over this many milliseconds, so a clip that starts or
+    /// ends mid-waveform doesn't click at the join. Needs the filter path; 0 is off.
+    pub boundary_fade_ms: Option<u32>,
+    pub marker_tone: Option<MarkerTone>, // Beep between clips (not after the last)
+    pub format: OutputFormat,            // Audio container/codec: mp3 (default) or opus
+    pub opus_application: OpusApplication,
+    pub container: Option<String>, // Explicit muxer (`-f`), e.g. "adts"; else from extension
+    /// Output level processing, applied to the joined output: loudnorm, then gain
+    pub loudnorm: Option<LoudnormConfig>,
+    pub gain_db: Option<f64>,
+    pub speed: Option<f64>, // Tempo change with pitch kept (atempo), e.g. 1.5 for faster review
+    /// Pad the output with trailing silence to exactly this many seconds, e.g. for a fixed
+    /// broadcast slot. Clips running longer are an error unless `truncate_to_pad` is set.
+    pub pad_to_duration: Option<f64>,
+    pub truncate_to_pad: bool, // Cut an over-long output at pad_to_duration, with a warning
+    /// Longest output allowed, in seconds, e.g. a host's upload limit. A longer output is
+    /// an error before anything renders, unless `truncate_to_max` is set: then the clips
+    /// starting past the limit are dropped and the last one kept is cut short.
+    pub max_total_duration: Option<f64>,
+    pub truncate_to_max: bool,
+    /// Peak limiter, always the last output filter so nothing after it can push
+    /// peaks back over the ceiling (it runs after loudnorm, gain, and resampling)
+    pub limiter: Option<LimiterConfig>,
+    pub sample_rate: Option<u32>, // Resample the output, e.g. 8kHz trunked audio up to 44.1kHz
+    /// Convert clips whose channel count differs from this target, leaving the rest as-is
+    pub channel_target: Option<ChannelTarget>,
+    pub resample_quality: ResampleQuality,
+    /// Most clips fed to one filter_complex run before falling back to hierarchical
+    /// concat (defaults to DEFAULT_MAX_FILTER_INPUTS)
+    pub max_filter_inputs: Option<usize>,
+    pub preserve_mtime: Option<u64>, // Set the output's mtime to this Unix time
+    pub inherit_mtime: bool,         // Set the output's mtime to the earliest clip's
+    pub creation_time: Option<String>, // ISO 8601 `creation_time` tag for the output
+    pub creation_time_from_clips: bool, // Unless set above, tag with the earliest clip's time
+    pub location: Option<GeoTag>,    // Where the recordings were made, as a location tag
+    pub location_from_clips: bool,   // Unless set above, tag with the first clip's location
+    pub write_manifest: bool,        // Write a .manifest.json sidecar next to the output
+    pub check_disk_space: bool,      // Refuse to start when the estimated output won't fit
+    pub warn_bitrate_upscale: bool,  // Warn when the output bitrate exceeds every source's
+    pub atomic_write: bool,          // Render to a .part file and rename it into place
+    pub confirm_overwrite: Option<OverwritePrompt>, // Ask before replacing an existing output
+    pub empty_clips: EmptyClipMode,  // Handling for clips whose duration is zero
+    pub vbr_handling: VbrHandling,   // What to do when the demuxer would join VBR MP3 clips
+    pub transcript_srt: Option<String>, // SRT whose cues are embedded as chapters
+    pub clip_chapters: bool,         // Embed a chapter per clip instead, titled by clip name
+    /// Existing output to extend: it goes ahead of the clips, and if it has chapters they
+    /// are kept and followed by one chapter per new clip. Per-clip and output processing
+    /// apply to it too. May be the output path itself, which is then replaced atomically.
+    pub append_to: Option<String>,
+    pub threads: Option<u32>, // Encoder threads (`-threads`); unset lets FFmpeg decide
+    pub low_priority: bool,   // Run FFmpeg below normal process priority
+    pub skip_corrupt_clips: bool, // Fully decode every clip first and leave out any that fail
+    pub cap_clip_length: Option<f64>, // Cut any clip longer than this many seconds off there
+    /// Skip the dead air at the start and end of each clip, leaving pauses inside a
+    /// transmission alone. Clips are seeked past it rather than filtered, but each needs
+    /// its own input, so this takes the filter path.
+    pub trim_inter_clip_silence: bool,
+    pub encrypt: Option<EncryptConfig>, // Replace the output and manifest with passphrase-encrypted .enc files
+}
+
+/// Passphrase encryption of a finished output, in age's passphrase format (scrypt key
+/// derivation, ChaCha20-Poly1305 payload), so the file also opens with any age tool.
+/// Sidecars are encrypted alongside it, so a manifest has to be decrypted before it can
+/// be rerun. An existing `.enc` is only replaced once confirm_overwrite allows it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptConfig {
+    /// Never written to manifests or the saved queue. A job resumed after a restart
+    /// therefore fails for want of it, rather than leaving an unencrypted file.
+    #[serde(default, skip_serializing)]
+    pub passphrase: String,
+}
+
+impl EncryptConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.passphrase.is_empty() {
+            return Err(
+                "Encryption needs a passphrase (passphrases aren't saved, so re-enter it for resumed jobs)"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// A standalone audio file rendered alongside a video from the same clips
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioExport {
+    pub format: OutputFormat,
+    pub bitrate: Option<String>, // Defaults to the video's audio bitrate
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VideoConfig {
+    pub image_path: Option<String>,
+    pub fit_mode: ImageFitMode,
+    #[serde(default)]
+    pub orientation: Orientation, // Sets the frame size; width/height override either side
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub write_manifest: bool, // Write a .manifest.json sidecar next to the output
+    #[serde(default)]
+    pub video_chapters: bool, // Write a WebVTT chapter file with one cue per clip
+    #[serde(default)]
+    pub mux_chapters: bool, // Also embed the chapter cues as a mov_text track
+    #[serde(default)]
+    pub scrolling_log: bool, // Scroll every clip name up the screen over the whole video
+    #[serde(default)]
+    pub scroll_speed: Option<f64>, // Pixels per second; defaults to one pass over the video
+    #[serde(default)]
+    pub keyframe_interval: Option<f64>, // Seconds between keyframes; defaults to 2
+    #[serde(default)]
+    pub also_export_audio: Option<AudioExport>,
+    #[serde(default)]
+    pub atomic_write: bool, // Render to a .part file and rename it into place
+    /// Ask before replacing an existing output, and likewise for the chapter file and
+    /// audio export rendered alongside it
+    #[serde(default)]
+    pub confirm_overwrite: Option<OverwritePrompt>,
+    #[serde(default)]
+    pub aac_profile: Option<AacProfile>, // Unset keeps the encoder's default (AAC-LC)
+    /// Unset uses native aac, or libfdk_aac for the HE profiles
+    #[serde(default)]
+    pub aac_encoder: Option<AacEncoder>,
+    #[serde(default)]
+    pub aac_vbr: Option<u32>, // libfdk_aac VBR mode, 1 (smallest) to 5, in place of the bitrate
+    /// Unset keeps CRF 12 for the video, with `bitrate` for the audio
+    #[serde(default)]
+    pub rate_control: Option<VideoRateControl>,
+    #[serde(default)]
+    pub threads: Option<u32>, // Encoder threads (`-threads`); unset lets FFmpeg decide
+    #[serde(default)]
+    pub low_priority: bool, // Run FFmpeg below normal process priority
+    #[serde(default)]
+    pub pad_color: Option<String>, // Bars and blank background color, e.g. "#1a2b3c"; black if unset
+    #[serde(default)]
+    pub clip_counter: bool, // Show "3 / 120" for the clip currently playing
+    #[serde(default)]
+    pub counter_position: Corner,
+    #[serde(default)]
+    pub counter_font_size: Option<u32>, // Defaults to DEFAULT_COUNTER_FONT_SIZE
+    #[serde(default)]
+    pub watermark: Option<Watermark>, // Small logo composited over the background
+    /// Images shown in turn instead of a single background, each scaled per fit_mode
+    #[serde(default)]
+    pub background_slideshow: Option<Vec<String>>,
+    /// Seconds each slideshow image stays up; unset shows one image per clip
+    #[serde(default)]
+    pub slideshow_interval: Option<f64>,
+    /// Smallest possible file for a static background: 1fps with a single keyframe at the
+    /// start, so later frames cost next to nothing. Players that only seek to keyframes
+    /// (some browsers and hardware players) jump back to the start instead of the seek
+    /// point, and decoders must read from the start to reach any later frame.
+    #[serde(default)]
+    pub minimal_motion: bool,
+    #[serde(default)]
+    pub creation_time: Option<String>, // ISO 8601 `creation_time` tag for the output
+    #[serde(default)]
+    pub creation_time_from_clips: bool, // Unless set above, tag with the earliest clip's time
+    #[serde(default)]
+    pub location: Option<GeoTag>, // Also tags the audio export
+    #[serde(default)]
+    pub location_from_clips: bool, // Unless set above, tag with the first clip's location
+    #[serde(default)]
+    pub encrypt: Option<EncryptConfig>, // Also covers the audio export, chapters and manifest
+}
+
+/// How the video encoder spends bits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum VideoRateControl {
+    /// Constant quality, 0 (lossless) to 51; lower is better and larger
+    Crf { value: u32 },
+    /// Target bitrates such as "2500k", for platforms that cap them. The video never
+    /// exceeds its target over a two-second window. Audio defaults to `bitrate`.
+    Bitrate {
+        video: String,
+        audio: Option<String>,
+    },
+}
+
+/// libx264's CRF scale tops out here (for 8-bit output)
+const MAX_X264_CRF: u32 = 51;
+
+/// Below this libx264 can't hold a frame together, even a static one
+const MIN_VIDEO_BITRATE: u64 = 100_000;
+
+/// Range the AAC encoders (native and libfdk_aac) take for a stereo stream
+const AAC_BITRATE_RANGE: (u64, u64) = (8_000, 512_000);
+
+impl VideoRateControl {
+    /// Check the values against libx264's and the AAC encoders' ranges. aac_vbr replaces
+    /// the audio bitrate, so the two can't both be given.
+    fn validate(&self, aac_vbr: Option<u32>) -> Result<(), String> {
+        match self {
+            VideoRateControl::Crf { value } if *value > MAX_X264_CRF => Err(format!(
+                "CRF {} is out of range for libx264 (0-{})",
+                value, MAX_X264_CRF
+            )),
+            VideoRateControl::Crf { .. } => Ok(()),
+            VideoRateControl::Bitrate { video, audio } => {
+                match parse_bitrate(video) {
+                    Some(bits) if bits >= MIN_VIDEO_BITRATE => {}
+                    Some(_) => {
+                        return Err(format!(
+                            "Video bitrate {} is below libx264's workable minimum of {}k",
+                            video,
+                            MIN_VIDEO_BITRATE / 1000
+                        ));
+                    }
+                    None => return Err(format!("Invalid video bitrate: {}", video)),
+                }
+                let Some(audio) = audio else {
+                    return Ok(());
+                };
+                if aac_vbr.is_some() {
+                    return Err(
+                        "aac_vbr sets the audio quality itself; leave the audio bitrate unset"
+                            .to_string(),
+                    );
+                }
+                let (min, max) = AAC_BITRATE_RANGE;
+                match parse_bitrate(audio) {
+                    Some(bits) if (min..=max).contains(&bits) => Ok(()),
+                    Some(_) => Err(format!(
+                        "AAC bitrate {} is out of range ({}k-{}k)",
+                        audio,
+                        min / 1000,
+                        max / 1000
+                    )),
+                    None => Err(format!("Invalid audio bitrate: {}", audio)),
+                }
+            }
+        }
+    }
+
+    /// Bitrate for the AAC audio, falling back to the one given with the request
+    fn audio_bitrate<'a>(&'a self, fallback: &'a str) -> &'a str {
+        match self {
+            VideoRateControl::Bitrate {
+                audio: Some(audio), ..
+            } => audio,
+            _ => fallback,
+        }
+    }
+
+    /// libx264 arguments setting the rate. A bitrate target is capped by maxrate over a
+    /// buffer of twice the target, i.e. two seconds.
+    fn x264_args(&self) -> Vec<String> {
+        match self {
+            VideoRateControl::Crf { value } => vec!["-crf".to_string(), value.to_string()],
+            VideoRateControl::Bitrate { video, .. } => {
+                let bufsize = parse_bitrate(video).unwrap_or_default() * 2;
+                vec![
+                    "-b:v".to_string(),
+                    video.clone(),
+                    "-maxrate".to_string(),
+                    video.clone(),
+                    "-bufsize".to_string(),
+                    bufsize.to_string(),
+                ]
+            }
+        }
+    }
+}
+
+/// Corner of the frame an overlay (clip counter, watermark) is placed in
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl Corner {
+    /// x and y expressions placing an item of size `item` inset 20px from this corner of
+    /// `frame`, in whatever variables the filter uses (drawtext: w/h and tw/th)
+    fn xy(self, frame: (&str, &str), item: (&str, &str)) -> (String, String) {
+        let (right, bottom) = match self {
+            Corner::TopLeft => (false, false),
+            Corner::TopRight => (true, false),
+            Corner::BottomLeft => (false, true),
+            Corner::BottomRight => (true, true),
+        };
+        let offset = |far: bool, frame: &str, item: &str| {
+            if far {
+                format!("{}-{}-20", frame, item)
+            } else {
+                "20".to_string()
+            }
+        };
+        (
+            offset(right, frame.0, item.0),
+            offset(bottom, frame.1, item.1),
+        )
+    }
+}
+
+/// A logo image overlaid in a corner of the video. PNG transparency is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Watermark {
+    pub image_path: String,
+    pub position: Corner,
+    pub opacity: f64, // 0 (invisible) to 1 (as drawn)
+    pub scale: f64,   // Logo width as a fraction of the frame width
+}
+
+impl Default for Watermark {
+    fn default() -> Self {
+        Watermark {
+            image_path: String::new(),
+            position: Corner::default(),
+            opacity: 0.8,
+            scale: 0.15,
+        }
+    }
+}
+
+impl Watermark {
+    fn validate(&self) -> Result<(), String> {
+        if self.image_path.is_empty() || !Path::new(&self.image_path).exists() {
+            return Err(format!("Watermark image not found: {}", self.image_path));
+        }
+        if !(0.0..=1.0).contains(&self.opacity) {
+            return Err("Watermark opacity must be between 0 and 1".to_string());
+        }
+        if !(self.scale > 0.0 && self.scale <= 1.0) {
+            return Err("Watermark scale must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Filter chain for the logo input: alpha kept, sized, and faded to the opacity
+    fn logo_filter(&self, frame_width: u32) -> String {
+        // Even widths keep chroma subsampling happy once composited
+        let width = ((frame_width as f64 * self.scale / 2.0).round() as u32 * 2).max(2);
+        format!(
+            "format=rgba,scale={}:-1,colorchannelmixer=aa={}",
+            width, self.opacity
+        )
+    }
+}
+
+/// Title- and action-safe outlines drawn on framing previews. Never part of a render.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafeGuides {
+    pub color: String,
+    pub opacity: f64, // 0 (invisible) to 1 (solid)
+}
+
+impl Default for SafeGuides {
+    fn default() -> Self {
+        SafeGuides {
+            color: "white".to_string(),
+            opacity: 0.6,
+        }
+    }
+}
+
+impl SafeGuides {
+    fn validate(&self) -> Result<(), String> {
+        validate_color(&self.color)?;
+        if !(0.0..=1.0).contains(&self.opacity) {
+            return Err("Guide opacity must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Outlines at 90% (action safe) and 80% (title safe) of the frame, centered
+    fn filters(&self) -> Vec<String> {
+        let color = escape_filter_value(&format!("{}@{}", self.color, self.opacity));
+        // (inset from each edge, size) as fractions of the frame
+        [(0.05, 0.9), (0.1, 0.8)]
+            .iter()
+            .map(|(inset, fraction)| {
+                format!(
+                    "drawbox=x=iw*{inset}:y=ih*{inset}:w=iw*{fraction}:h=ih*{fraction}:color={color}:t=2",
+                    inset = inset,
+                    fraction = fraction,
+                    color = color
+                )
+            })
+            .collect()
+    }
+}
+
+/// Everything needed to reproduce a stitch, saved as a sidecar next to the output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StitchManifest {
+    pub format: OutputFormat,
+    pub clips: Vec<AudioClip>,
+    pub output_path: String,
+    pub bitrate: String,
+    pub audio_config: Option<AudioConfig>,
+    pub video_config: Option<VideoConfig>,
+}
+
+/// Format of a clip's first audio stream, as far as concat compatibility is concerned
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamFormat {
+    pub codec: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u32>,
+    pub profile: Option<String>,
+}
+
+impl StitchManifest {
+    /// Whether the render asks before replacing its output. Such an output may be a file
+    /// the user chose to keep, so the queue mustn't clean it up as a leftover.
+    pub(crate) fn confirms_overwrite(&self) -> bool {
+        match self.format {
+            OutputFormat::Mp4 => self
+                .video_config
+                .as_ref()
+                .is_some_and(|config| config.confirm_overwrite.is_some()),
+            OutputFormat::Mp3 | OutputFormat::Opus => self
+                .audio_config
+                .as_ref()
+                .is_some_and(|config| config.confirm_overwrite.is_some()),
+        }
+    }
+
+    /// Whether the render extends its own output in place (append_to names output_path).
+    /// That output is the append base, and only the `.part` file beside it is a leftover.
+    pub(crate) fn appends_in_place(&self) -> bool {
+        self.audio_config
+            .as_ref()
+            .and_then(|config| config.append_to.as_deref())
+            .is_some_and(|existing| same_file(existing, &self.output_path))
+    }
+}
+
+/// Whether the clips can be joined by the concat demuxer without re-encoding issues
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConcatPlan {
+    pub demuxer_compatible: bool,
+    pub mismatch_index: Option<usize>, // First clip that differs from the first clip
+    pub mismatch_property: Option<String>, // codec, sample_rate, channels, or profile
+    pub error: Option<String>,
+}
+
+/// How multichannel audio is folded down to mono
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DownmixMode {
+    Sum,                       // Average every channel
+    LeftOnly,                  // Keep only the first channel
+    RightOnly,                 // Keep only the second channel
+    Pan { weights: Vec<f64> }, // One gain per input channel
+}
+
+/// How showwaves draws each sample of the animated waveform
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WaveformMode {
+    #[default]
+    Line, // Vertical line per sample
+    Point, // Single dot per sample
+    Cline, // Vertical line centred on the midline
+}
+
+impl WaveformMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            WaveformMode::Line => "line",
+            WaveformMode::Point => "point",
+            WaveformMode::Cline => "cline",
+        }
+    }
+}
+
+/// Look of the waveform-over-spectrogram video
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitScreenStyle {
+    pub split_ratio: f64, // Share of the height given to the waveform, on top
+    pub waveform_color: String,
+    pub waveform_mode: WaveformMode,
+    pub spectrum_color: String, // One of SPECTRUM_COLOR_MODES
+}
+
+impl Default for SplitScreenStyle {
+    fn default() -> Self {
+        SplitScreenStyle {
+            split_ratio: 0.5,
+            waveform_color: "white".to_string(),
+            waveform_mode: WaveformMode::default(),
+            spectrum_color: "intensity".to_string(),
+        }
+    }
+}
+
+/// Color schemes showspectrum offers
+const SPECTRUM_COLOR_MODES: [&str; 15] = [
+    "channel",
+    "intensity",
+    "rainbow",
+    "moreland",
+    "nebulae",
+    "fire",
+    "fiery",
+    "fruit",
+    "cool",
+    "magma",
+    "green",
+    "viridis",
+    "plasma",
+    "cividis",
+    "terrain",
+];
+
+/// Encoding speed and size of one x264 preset on this machine
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PresetBenchmark {
+    pub preset: String,
+    pub seconds: f64,          // Wall-clock encode time for the sample slice
+    pub fps: f64,              // Frames encoded per second of wall-clock time
+    pub bytes_per_second: f64, // Output size per second of encoded media
+}
+
+/// Silence detection settings for split_on_silence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SilenceSplitConfig {
+    pub threshold_db: f64, // Anything quieter counts as silence; raise for noisy recorders
+    pub min_silence_seconds: f64, // Shortest silence that is detected at all
+    /// Detected silences shorter than this don't split, so a pause mid-sentence stays
+    /// inside one transmission
+    pub merge_short_gaps: Option<f64>,
+    pub detect_only: bool, // Report the regions without writing any files
+}
+
+impl Default for SilenceSplitConfig {
+    fn default() -> Self {
+        SilenceSplitConfig {
+            threshold_db: -50.0,
+            min_silence_seconds: 0.5,
+            merge_short_gaps: None,
+            detect_only: false,
+        }
+    }
+}
+
+/// A span of the input, in seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// A non-silent stretch of the input, and the file it was written to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitSegment {
+    pub start: f64,
+    pub end: f64,
+    pub path: Option<String>, // None when only detecting
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SilenceSplit {
+    pub silences: Vec<TimeRange>, // Every silence found, including ones merged over
+    pub segments: Vec<SplitSegment>,
+}
+
+/// Result of fully decoding a clip
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub path: String,
+    pub ok: bool,            // Decoded to the end with no errors
+    pub errors: Vec<String>, // Decoder errors, capped at MAX_INTEGRITY_ERRORS
+}
+
+/// Chromaprint fingerprint of one clip from a batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClipFingerprint {
+    pub path: String,
+    pub fingerprint: Option<String>, // Base64, as printed by the chromaprint muxer
+    pub error: Option<String>,
+}
+
+/// Free space on the volume an output will be written to
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiskStatus {
+    pub mount_point: String,
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+    pub required_bytes: u64,
+    pub sufficient: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    pub valid: bool,
+    pub error: Option<String>,
+}
+
+/// Get audio file information using ffprobe
+#[tauri::command]
+fn get_audio_info(path: String) -> AudioInfo {
+    let path_buf = PathBuf::from(&path);
+
+    // Check if file exists
+    if !path_buf.exists() {
+        return AudioInfo::invalid(0, "File does not exist".to_string());
+    }
+
+    // Get file size
+    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+    // Get duration and the audio stream's details in one ffprobe call
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "a:0",
+            "-show_entries",
+            "format=duration,bit_rate:stream=codec_name,sample_rate,channels,bit_rate,sample_fmt,profile",
+            "-of",
+            "json",
+            &path,
+        ])
+        .output();
+
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            return AudioInfo::invalid(
+                size,
+                format!("Failed to run ffprobe: {}. Is FFmpeg installed?", e),
+            )
+        }
+    };
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return AudioInfo::invalid(size, format!("FFprobe error: {}", error));
+    }
+
+    let probe: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_default();
+    let stream = probe["streams"]
+        .as_array()
+        .and_then(|streams| streams.first())
+        .cloned()
+        .unwrap_or_default();
+    let format = &probe["format"];
+    // ffprobe prints numbers other than channels as JSON strings
+    let number = |value: &serde_json::Value| value.as_str().and_then(|v| v.parse::<u32>().ok());
+    // Saves stitching a second probe of the same clip for its concat check
+    remember_stream_format(
+        &path,
+        &StreamFormat {
+            codec: stream["codec_name"]
+                .as_str()
+                .and_then(reported_value)
+                .map(str::to_string),
+            sample_rate: number(&stream["sample_rate"]),
+            channels: stream["channels"]
+                .as_u64()
+                .and_then(|c| u32::try_from(c).ok()),
+            profile: stream["profile"]
+                .as_str()
+                .and_then(reported_value)
+                .map(str::to_string),
+        },
+    );
+
+    AudioInfo {
+        duration: format["duration"]
+            .as_str()
+            .and_then(|d| d.parse().ok())
+            .unwrap_or(0.0),
+        size,
+        valid: true,
+        error: None,
+        codec: stream["codec_name"].as_str().map(str::to_string),
+        sample_rate: number(&stream["sample_rate"]),
+        channels: stream["channels"]
+            .as_u64()
+            .and_then(|c| u8::try_from(c).ok()),
+        bit_rate: number(&stream["bit_rate"]).or_else(|| number(&format["bit_rate"])),
+        sample_format: stream["sample_fmt"].as_str().map(str::to_string),
+    }
+}
+
+/// Every format property, stream, tag and chapter ffprobe reports for a file, as its raw
+/// JSON tree. Fails outright rather than returning whatever parsed before an error.
+#[tauri::command]
+fn dump_media_info(path: String) -> Result<serde_json::Value, String> {
+    if !Path::new(&path).exists() {
+        return Err("File does not exist".to_string());
+    }
+
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-show_format",
+            "-show_streams",
+            "-show_chapters",
+            "-print_format",
+            "json",
+            &path,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFprobe error: {}", error));
+    }
+
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse ffprobe output: {}", e))
+}
+
+/// One recording in a session file, as written by a companion recorder
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SessionEntry {
+    path: String, // Relative paths are resolved against the session file's folder
+    duration: f64,
+    #[serde(default)]
+    name: Option<String>, // Defaults to the file name
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    metadata: Option<serde_json::Value>,
+}
+
+/// Most malformed entries listed in a load_clips_from_json error
+const MAX_SESSION_ERRORS: usize = 20;
+
+/// Load clips from a recorder's JSON session file without probing each one, since the
+/// recorder's durations are trusted. The file looks like:
+///
+/// ```json
+/// { "clips": [ { "path": "rec/0001.wav", "duration": 12.48, "name": "Dispatch",
+///                "id": "0001", "metadata": { "talkgroup": 1201 } } ] }
+/// ```
+///
+/// Only `path` and `duration` are required. With `verify`, each clip is probed anyway and
+/// the probed duration replaces the recorded one. Any malformed entry fails the load.
+#[tauri::command]
+async fn load_clips_from_json(
+    path: String,
+    verify: Option<bool>,
+) -> Result<Vec<AudioClip>, String> {
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read session file: {}", e))?;
+    let session: serde_json::Value =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid session file: {}", e))?;
+    let entries = session["clips"]
+        .as_array()
+        .ok_or("Session file has no \"clips\" array")?;
+    let base_dir = Path::new(&path).parent().unwrap_or(Path::new(""));
+    let id_prefix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+
+    let mut clips = Vec::with_capacity(entries.len());
+    let mut errors = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let entry: SessionEntry = match serde_json::from_value(entry.clone()) {
+            Ok(entry) => entry,
+            Err(e) => {
+                errors.push(format!("clip {}: {}", index + 1, e));
+                continue;
+            }
+        };
+        if entry.path.trim().is_empty() {
+            errors.push(format!("clip {}: path is empty", index + 1));
+            continue;
+        }
+        if !(entry.duration.is_finite() && entry.duration > 0.0) {
+            errors.push(format!(
+                "clip {}: duration must be greater than zero",
+                index + 1
+            ));
+            continue;
+        }
+
+        let clip_path = base_dir.join(&entry.path);
+        let Ok(file) = fs::metadata(&clip_path) else {
+            errors.push(format!("clip {}: {} does not exist", index + 1, entry.path));
+            continue;
+        };
+        let clip_path = clip_path.to_string_lossy().to_string();
+
+        let mut duration = entry.duration;
+        if verify.unwrap_or(false) {
+            let info = get_audio_info(clip_path.clone());
+            if !info.valid || info.duration <= 0.0 {
+                let reason = info.error.unwrap_or_else(|| "no audio".to_string());
+                errors.push(format!("clip {}: {}", index + 1, reason.trim()));
+                continue;
+            }
+            duration = info.duration;
+        }
+
+        clips.push(AudioClip {
+            id: entry
+                .id
+                .unwrap_or_else(|| format!("{:x}-{}", id_prefix, index)),
+            name: entry.name.unwrap_or_else(|| {
+                Path::new(&clip_path)
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            }),
+            path: clip_path,
+            duration,
+            size: file.len(),
+            metadata: entry.metadata,
+            order: None, // The session's own list order is the order
+            pan: None,
+        });
+    }
+
+    if !errors.is_empty() {
+        let more = errors.len().saturating_sub(MAX_SESSION_ERRORS);
+        errors.truncate(MAX_SESSION_ERRORS);
+        if more > 0 {
+            errors.push(format!("...and {} more", more));
+        }
+        return Err(format!("Malformed session entries: {}", errors.join("; ")));
+    }
+    Ok(clips)
+}
+
+/// Split out clips with no audio. A clip listed with a zero duration is re-probed first,
+/// since the frontend's figure may be stale or missing; the rest are returned unchanged.
+fn partition_empty_clips(clips: Vec<AudioClip>) -> (Vec<AudioClip>, Vec<AudioClip>) {
+    let mut kept = Vec::with_capacity(clips.len());
+    let mut empty = Vec::new();
+
+    for mut clip in clips {
+        if clip.duration <= 0.0 {
+            let info = get_audio_info(clip.path.clone());
+            if info.valid && info.duration > 0.0 {
+                clip.duration = info.duration;
+            } else {
+                empty.push(clip);
+                continue;
+            }
+        }
+        kept.push(clip);
+    }
+
+    (kept, empty)
+}
+
+/// Most decoder errors kept per integrity report; a badly damaged file can print thousands
+const MAX_INTEGRITY_ERRORS: usize = 20;
+
+/// Decode a whole clip and collect any errors. This catches corrupt or truncated tails
+/// that ffprobe's duration check misses, at the cost of a full decode pass.
+fn clip_integrity(path: &str) -> IntegrityReport {
+    let output = ffmpeg_command()
+        .args(["-v", "error", "-i", path, "-f", "null", "-"])
+        .output();
+
+    let (ok, errors) = match output {
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let errors: Vec<String> = stderr
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .take(MAX_INTEGRITY_ERRORS)
+                .map(str::to_string)
+                .collect();
+            (output.status.success() && errors.is_empty(), errors)
+        }
+        Err(e) => (
+            false,
+            vec![format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e)],
+        ),
+    };
+
+    IntegrityReport {
+        path: path.to_string(),
+        ok,
+        errors,
+    }
+}
+
+/// Fully decode a clip to check it isn't truncated or corrupt
+#[tauri::command]
+async fn check_clip_integrity(path: String) -> IntegrityReport {
+    clip_integrity(&path)
+}
+
+/// Most ffprobe/FFmpeg analysis processes a batch command runs at once
+const MAX_PROBE_WORKERS: usize = 4;
+
+/// Payload of the `probe-progress` event, sent as each clip of a batch finishes
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeProgress {
+    pub command: String, // The batch command reporting, e.g. "check_clips_integrity"
+    pub completed: usize,
+    pub total: usize,
+    pub path: String, // The clip that just finished
+}
+
+/// Run `probe` over every clip on a small worker pool, returning results in input order.
+/// Each finished clip emits a `probe-progress` event to `window`; `probe` reports its own
+/// failures in its result, so one bad clip doesn't stop the batch.
+fn probe_batch<T, F>(window: &tauri::Window, command: &str, clips: &[AudioClip], probe: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&str) -> T + Sync,
+{
+    let paths: Vec<String> = clips.iter().map(|clip| clip.path.clone()).collect();
+    run_batch(window, command, &paths, MAX_PROBE_WORKERS, probe)
+}
+
+/// probe_batch over plain paths, on up to `max_workers` threads
+fn run_batch<T, F>(
+    window: &tauri::Window,
+    command: &str,
+    paths: &[String],
+    max_workers: usize,
+    probe: F,
+) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&str) -> T + Sync,
+{
+    let total = paths.len();
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, max_workers.max(1))
+        .min(total.max(1));
+    let next = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<T>>> = Mutex::new((0..total).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(index) else {
+                    break;
+                };
+                let result = probe(path);
+                results
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())[index] = Some(result);
+
+                let _ = window.emit(
+                    "probe-progress",
+                    ProbeProgress {
+                        command: command.to_string(),
+                        completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                        total,
+                        path: path.clone(),
+                    },
+                );
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// check_clip_integrity over a clip list, one report per clip in order. Clips are decoded
+/// in parallel, with `probe-progress` events as each finishes.
+#[tauri::command]
+async fn check_clips_integrity(
+    window: tauri::Window,
+    clips: Vec<AudioClip>,
+) -> Vec<IntegrityReport> {
+    probe_batch(&window, "check_clips_integrity", &clips, clip_integrity)
+}
+
+/// Sort clips by their explicit `order` when any clip has one, keeping list order otherwise.
+/// Orders given for only some clips, or repeated, are an error rather than a guess.
+fn order_clips(mut clips: Vec<AudioClip>) -> Result<Vec<AudioClip>, String> {
+    let ordered = clips.iter().filter(|clip| clip.order.is_some()).count();
+    if ordered == 0 {
+        return Ok(clips);
+    }
+    if ordered < clips.len() {
+        return Err(format!(
+            "Only {} of {} clips have an order; set it on every clip or none",
+            ordered,
+            clips.len()
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    if let Some(order) = clips
+        .iter()
+        .filter_map(|clip| clip.order)
+        .find(|&order| !seen.insert(order))
+    {
+        return Err(format!("More than one clip has order {}", order));
+    }
+
+    clips.sort_by_key(|clip| clip.order);
+    Ok(clips)
+}
+
+/// Build a failed StitchResult with the given error message
+fn stitch_error(error: impl Into<String>) -> StitchResult {
+    StitchResult {
+        success: false,
+        error: Some(error.into()),
+        ..Default::default()
+    }
+}
+
+/// Fill in the render performance fields of a successful result, timed from `started`.
+/// `duration` is the output's length in seconds.
+fn record_render_stats(result: &mut StitchResult, started: Instant, duration: f64) {
+    if !result.success {
+        return;
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+    result.elapsed_seconds = Some(elapsed);
+    if elapsed > 0.0 {
+        result.realtime_factor = Some(duration / elapsed);
+    }
+    result.output_bytes = result
+        .output_path
+        .as_ref()
+        .and_then(|path| fs::metadata(path).ok())
+        .map(|meta| meta.len());
+    if let (Some(size), true) = (result.output_bytes, elapsed > 0.0) {
+        result.throughput_mb_s = Some(size as f64 / 1_000_000.0 / elapsed);
+    }
+}
+
+/// Escape a path for use inside a single-quoted concat list entry
+fn escape_concat_path(path: &str) -> String {
+    path.replace("'", "'\\''")
+}
+
+/// Token that keeps one run's temp files apart from every other run's, whether a
+/// parallel queue job in this process or another instance of the app
+fn temp_run_id() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    format!(
+        "{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Temp file path named after `name` but unique to this run. The extension stays last, so
+/// FFmpeg still picks the format from it (`ffmpeg_chapters.txt` -> `ffmpeg_chapters_<id>.txt`).
+fn unique_temp_path(name: &str) -> PathBuf {
+    let name = Path::new(name);
+    let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+    let file = match name.extension() {
+        Some(extension) => format!("{}_{}.{}", stem, temp_run_id(), extension.to_string_lossy()),
+        None => format!("{}_{}", stem, temp_run_id()),
+    };
+    std::env::temp_dir().join(file)
+}
+
+/// Write an FFmpeg concat demuxer list referencing each path in order
+fn write_concat_list<P: AsRef<str>>(
+    paths: impl IntoIterator<Item = P>,
+    concat_file_path: &Path,
+) -> Result<(), String> {
+    let mut concat_file =
+        File::create(concat_file_path).map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+    for path in paths {
+        if writeln!(concat_file, "file '{}'", escape_concat_path(path.as_ref())).is_err() {
+            return Err("Failed to write concat list".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a temporary concat list for the clips, run FFmpeg with the arguments built
+/// around it, then clean the list up again
+fn run_with_concat_list(
+    clips: &[AudioClip],
+    list_name: &str,
+    output_path: String,
+    build_args: impl FnOnce(&Path) -> Vec<String>,
+) -> StitchResult {
+    // Create a temporary file for the concat list
+    let concat_file_path = unique_temp_path(list_name);
+
+    if let Err(e) = write_concat_list(clips.iter().map(|clip| &clip.path), &concat_file_path) {
+        return stitch_error(e);
+    }
+
+    let args = build_args(&concat_file_path);
+    let result = run_ffmpeg(&args, output_path);
+
+    // Clean up temp file
+    let _ = fs::remove_file(&concat_file_path);
+
+    result
+}
+
+/// Temp file a render is written to before being moved into place. It sits in the same
+/// directory so the final rename doesn't cross devices, and keeps the real extension last
+/// so FFmpeg still picks the muxer from it (`out.mp3` -> `out.part.mp3`).
+fn partial_output_path(output_path: &str) -> String {
+    let path = Path::new(output_path);
+    let partial = match path.extension() {
+        Some(extension) => path.with_extension(format!("part.{}", extension.to_string_lossy())),
+        None => path.with_extension("part"),
+    };
+    partial.to_string_lossy().to_string()
+}
+
+/// Move a finished partial render to the output path, so the output path only ever holds
+/// a complete file. A failed render's partial is removed instead.
+fn commit_partial_output(result: &mut StitchResult, partial_path: &str, output_path: &str) {
+    if !result.success {
+        let _ = fs::remove_file(partial_path);
+        return;
+    }
+
+    // rename can't move across filesystems, e.g. onto a network share mounted elsewhere
+    let moved = fs::rename(partial_path, output_path).or_else(|rename_error| {
+        match fs::copy(partial_path, output_path) {
+            Ok(_) => {
+                let _ = fs::remove_file(partial_path);
+                Ok(())
+            }
+            Err(copy_error) => {
+                // A failed copy can leave a truncated file behind at the output path
+                let _ = fs::remove_file(output_path);
+                Err(format!(
+                    "Failed to move {} into place (rename: {}; copy: {}); the complete render was kept there",
+                    partial_path, rename_error, copy_error
+                ))
+            }
+        }
+    });
+
+    match moved {
+        Ok(()) => result.output_path = Some(output_path.to_string()),
+        Err(e) => {
+            result.success = false;
+            result.output_path = None;
+            result.error = Some(e);
+        }
+    }
+}
+
+/// Path of the manifest sidecar for an output file (`out.mp3` -> `out.manifest.json`)
+fn manifest_path_for(output_path: &str) -> PathBuf {
+    Path::new(output_path).with_extension("manifest.json")
+}
+
+/// Save the manifest next to a successful output, recording its path on the result
+fn attach_manifest(result: &mut StitchResult, manifest: &StitchManifest) {
+    if !result.success {
+        return;
+    }
+
+    let manifest_path = manifest_path_for(&manifest.output_path);
+    let written = serde_json::to_string_pretty(manifest)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&manifest_path, json).map_err(|e| e.to_string()));
+
+    match written {
+        Ok(()) => result.manifest_path = Some(manifest_path.to_string_lossy().to_string()),
+        Err(e) => result
+            .warnings
+            .push(format!("Failed to write manifest: {}", e)),
+    }
+}
+
+/// Check whether the installed FFmpeg was configured with `--enable-<library>`
+fn ffmpeg_has_library(library: &str) -> bool {
+    cached_build_info().is_ok_and(|info| info.enabled.iter().any(|flag| flag == library))
+}
+
+/// Optional FFmpeg libraries features here depend on, by configure name, with what
+/// goes missing without each
+const TRACKED_LIBRARIES: [(&str, &str); 7] = [
+    ("libmp3lame", "MP3 output"),
+    ("libx264", "MP4 video"),
+    ("libopus", "Opus output"),
+    ("libfdk-aac", "HE-AAC audio"),
+    ("libvpx", "VP8/VP9 video"),
+    ("libsoxr", "high-quality resampling"),
+    ("chromaprint", "audio fingerprints"),
+];
+
+/// Whether one of TRACKED_LIBRARIES is in the build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibrarySupport {
+    pub name: String,
+    pub present: bool,
+    pub needed_for: String,
+}
+
+/// What the installed FFmpeg was built with, from `ffmpeg -version`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub version: String,      // First line of `ffmpeg -version`
+    pub enabled: Vec<String>, // Every --enable-* configure flag, without the prefix
+    pub libraries: Vec<LibrarySupport>,
+}
+
+/// Managed state: the build info, probed on first use. FFmpeg doesn't change under a
+/// running app, so there's no need to probe it again for every feature check.
+#[derive(Default)]
+pub struct BuildInfoCache(Mutex<Option<BuildInfo>>);
+
+impl BuildInfoCache {
+    fn get(&self) -> Result<BuildInfo, String> {
+        let mut cached = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(info) = cached.as_ref() {
+            return Ok(info.clone());
+        }
+        // A failed probe isn't cached, so installing FFmpeg takes effect without a restart
+        let info = probe_build_info()?;
+        *cached = Some(info.clone());
+        Ok(info)
+    }
+}
+
+/// Read the build configuration from `ffmpeg -version`
+fn probe_build_info() -> Result<BuildInfo, String> {
+    let output = ffmpeg_command()
+        .args(["-version"])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+    if !output.status.success() {
+        return Err("FFmpeg found but returned an error".to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = stdout.lines().next().unwrap_or_default().to_string();
+    let enabled: Vec<String> = stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("configuration:"))
+        .flat_map(str::split_whitespace)
+        .filter_map(|flag| flag.strip_prefix("--enable-"))
+        .map(str::to_string)
+        .collect();
+    let libraries = TRACKED_LIBRARIES
+        .iter()
+        .map(|&(name, needed_for)| LibrarySupport {
+            name: name.to_string(),
+            present: enabled.iter().any(|flag| flag == name),
+            needed_for: needed_for.to_string(),
+        })
+        .collect();
+
+    Ok(BuildInfo {
+        version,
+        enabled,
+        libraries,
+    })
+}
+
+/// Build info from the managed cache, or probed directly before the app is set up
+fn cached_build_info() -> Result<BuildInfo, String> {
+    match APP_HANDLE
+        .get()
+        .and_then(|app| app.try_state::<BuildInfoCache>())
+    {
+        Some(cache) => cache.get(),
+        None => probe_build_info(),
+    }
+}
+
+/// Error for a feature whose library this FFmpeg build lacks, naming the configure flag
+/// so the fix is clear
+fn missing_library_error(feature: &str, library: &str) -> String {
+    format!(
+        "{} needs an FFmpeg build with {} (--enable-{}); install a full build or switch options",
+        feature, library, library
+    )
+}
+
+/// Report the installed FFmpeg's version, configure flags, and which of the libraries
+/// this app relies on it has
+#[tauri::command]
+fn ffmpeg_build_info(cache: State<'_, BuildInfoCache>) -> Result<BuildInfo, String> {
+    cache.get()
+}
+
+/// Slowest and fastest output speeds accepted; beyond these the atempo chain gets long
+/// and speech stops being intelligible anyway
+const MIN_SPEED: f64 = 0.01;
+const MAX_SPEED: f64 = 100.0;
+
+/// atempo chain for a speed factor. A single atempo stage only covers 0.5-2.0, so other
+/// factors are split into full-range stages plus a remainder.
+fn atempo_filter(speed: f64) -> Result<String, String> {
+    if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        return Err(format!(
+            "Speed must be between {} and {}",
+            MIN_SPEED, MAX_SPEED
+        ));
+    }
+
+    let mut stages = Vec::new();
+    let mut remaining = speed;
+    while remaining > 2.0 {
+        stages.push("atempo=2".to_string());
+        remaining /= 2.0;
+    }
+    while remaining < 0.5 {
+        stages.push("atempo=0.5".to_string());
+        remaining /= 0.5;
+    }
+    stages.push(format!("atempo={}", remaining));
+    Ok(stages.join(","))
+}
+
+/// Build the aresample filter for the requested quality, returning it with the resampler used.
+/// High quality falls back to swr with a warning when FFmpeg lacks soxr.
+fn resample_filter(
+    sample_rate: u32,
+    quality: ResampleQuality,
+    warnings: &mut Vec<String>,
+) -> (String, &'static str) {
+    if let ResampleQuality::High = quality {
+        if ffmpeg_has_library("libsoxr") {
+            return (
+                format!("aresample={}:resampler=soxr:precision=28", sample_rate),
+                "soxr",
+            );
+        }
+        warnings.push(
+            "FFmpeg was built without libsoxr (--enable-libsoxr); using the default resampler instead"
+                .to_string(),
+        );
+    }
+
+    (format!("aresample={}", sample_rate), "swr")
+}
+
+/// Encoder arguments for MP3 output at the given bitrate
+fn mp3_encoder_args(bitrate: &str) -> Vec<String> {
+    vec![
+        "-c:a".to_string(),
+        "libmp3lame".to_string(), // MP3 codec
+        "-b:a".to_string(),
+        bitrate.to_string(), // Bitrate
+    ]
+}
+
+/// Whether an MP3 carries gapless playback info: a Xing/Info header in its first frame
+/// followed by the LAME extension recording a nonzero encoder delay. Read from the file
+/// itself, since ffprobe applies these values without reporting them. The muxer can only
+/// fill the header in on a seekable output, and a stream-copy remux may leave the delay out.
+fn mp3_has_gapless_info(path: &str) -> Result<bool, String> {
+    let mut head = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(64 * 1024).read_to_end(&mut head))
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    // Skip an ID3v2 tag; its size is syncsafe (7 bits per byte), plus an optional footer
+    let mut offset = 0;
+    if head.len() >= 10 && head.starts_with(b"ID3") {
+        let size = head[6..10]
+            .iter()
+            .fold(0usize, |size, &b| (size << 7) | (b & 0x7f) as usize);
+        offset = 10 + size + if head[5] & 0x10 != 0 { 10 } else { 0 };
+    }
+    let Some(frame) = (offset..head.len().saturating_sub(4))
+        .find(|&i| head[i] == 0xff && head[i + 1] & 0xe0 == 0xe0)
+    else {
+        return Ok(false);
+    };
+
+    // The tag sits after the side info, whose size depends on MPEG version and channel mode
+    let mpeg1 = head[frame + 1] & 0x18 == 0x18;
+    let mono = head[frame + 3] & 0xc0 == 0xc0;
+    let side_info = match (mpeg1, mono) {
+        (true, false) => 32,
+        (true, true) | (false, false) => 17,
+        (false, true) => 9,
+    };
+    let tag = frame + 4 + side_info;
+    if !matches!(head.get(tag..tag + 4), Some(b"Xing") | Some(b"Info")) {
+        return Ok(false);
+    }
+    let Some(&flags) = head.get(tag + 7) else {
+        return Ok(false);
+    };
+    // Frame count, byte count, seek table and quality are each present only if flagged
+    let extension = tag
+        + 8
+        + [(0x01, 4), (0x02, 4), (0x04, 100), (0x08, 4)]
+            .iter()
+            .filter(|&&(bit, _)| flags & bit != 0)
+            .map(|&(_, len)| len)
+            .sum::<usize>();
+
+    // FFmpeg writes its own name where LAME would, in the same layout
+    if !matches!(
+        head.get(extension..extension + 4),
+        Some(b"LAME") | Some(b"Lavf") | Some(b"Lavc")
+    ) {
+        return Ok(false);
+    }
+    // Delay and padding are 12 bits each, 21 bytes into the extension
+    let Some(delay_padding) = head.get(extension + 21..extension + 24) else {
+        return Ok(false);
+    };
+    let delay = ((delay_padding[0] as u32) << 4) | (delay_padding[1] as u32 >> 4);
+    Ok(delay > 0)
+}
+
+/// How far a source bitrate may be from the requested one and still count as matching it,
+/// as a fraction; CBR files report a little over or under their nominal rate
+const STREAM_COPY_BITRATE_TOLERANCE: f64 = 0.05;
+
+/// Whether MP3 clips already match the requested output, so the concat demuxer can copy
+/// their frames instead of decoding and re-encoding them. The caller has already checked
+/// the clips share one codec, sample rate and channel layout.
+fn can_stream_copy_mp3(clips: &[AudioClip], bitrate: &str) -> bool {
+    let Some(target) = parse_bitrate(bitrate) else {
+        return false;
+    };
+    let is_mp3 = clips
+        .first()
+        .and_then(|clip| stream_format(&clip.path).ok())
+        .and_then(|format| format.codec)
+        .is_some_and(|codec| codec == "mp3");
+    is_mp3
+        && clips.iter().all(|clip| {
+            probe_bitrate(&clip.path).is_ok_and(|rate| {
+                (rate as f64 - target as f64).abs() <= target as f64 * STREAM_COPY_BITRATE_TOLERANCE
+            })
+        })
+}
+
+/// Encoder arguments with the codec and bitrate replaced by a stream copy; muxer and
+/// other options are kept
+fn stream_copy_args(encoder_args: &[String]) -> Vec<String> {
+    let mut args = vec!["-c:a".to_string(), "copy".to_string()];
+    let mut rest = encoder_args.iter();
+    while let Some(arg) = rest.next() {
+        if arg == "-c:a" || arg == "-b:a" {
+            rest.next();
+        } else {
+            args.push(arg.clone());
+        }
+    }
+    args
+}
+
+/// Check whether the installed FFmpeg has a muxer (output container) with this name
+fn ffmpeg_has_muxer(name: &str) -> Result<bool, String> {
+    let output = ffmpeg_command()
+        .args(["-hide_banner", "-muxers"])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+
+    // Entries follow a "--" separator as " E  name  Description"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .skip_while(|line| line.trim() != "--")
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .any(|names| names.split(',').any(|muxer| muxer == name)))
+}
+
+/// Encoder arguments for an audio-only output format, plus `-f <container>` when the
+/// muxer is given explicitly instead of inferred from the output extension
+fn audio_encoder_args(
+    format: OutputFormat,
+    bitrate: &str,
+    opus_application: OpusApplication,
+    container: Option<&str>,
+    output_path: &str,
+) -> Result<Vec<String>, String> {
+    let mut args = match format {
+        OutputFormat::Mp3 => mp3_encoder_args(bitrate),
+        OutputFormat::Opus => {
+            let extension = Path::new(output_path)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase());
+            if container.is_none() && !matches!(extension.as_deref(), Some("opus") | Some("ogg")) {
+                return Err("Opus output must use a .opus or .ogg extension".to_string());
+            }
+            if !ffmpeg_has_library("libopus") {
+                return Err(missing_library_error("Opus output", "libopus"));
+            }
+            vec![
+                "-c:a".to_string(),
+                "libopus".to_string(),
+                "-b:a".to_string(),
+                bitrate.to_string(),
+                "-application".to_string(),
+                opus_application.as_str().to_string(),
+            ]
+        }
+        OutputFormat::Mp4 => return Err("MP4 output requires stitch_video".to_string()),
+    };
+
+    if let Some(container) = container {
+        if !ffmpeg_has_muxer(container)? {
+            return Err(format!("FFmpeg has no muxer named '{}'", container));
+        }
+        args.extend_from_slice(&["-f".to_string(), container.to_string()]);
+    }
+
+    // The Xing/Info header carries the encoder delay and padding that gapless players trim.
+    // It's on by default but made explicit; it's an mp3 muxer option, so other muxers would
+    // reject it.
+    let muxer = container.map(str::to_lowercase).or_else(|| {
+        Path::new(output_path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+    });
+    if format == OutputFormat::Mp3 && muxer.as_deref() == Some("mp3") {
+        args.extend_from_slice(&["-write_xing".to_string(), "1".to_string()]);
+    }
+
+    Ok(args)
+}
+
+/// AAC options for a video's audio track
+#[derive(Debug, Clone, Copy, Default)]
+struct AacSettings {
+    encoder: Option<AacEncoder>,
+    profile: Option<AacProfile>,
+    vbr: Option<u32>,
+}
+
+impl AacSettings {
+    fn from_config(video_config: &VideoConfig) -> Self {
+        AacSettings {
+            encoder: video_config.aac_encoder,
+            profile: video_config.aac_profile,
+            vbr: video_config.aac_vbr,
+        }
+    }
+}
+
+/// AAC encoder arguments, with the encoder they picked and any fallbacks taken
+struct AacEncoding {
+    args: Vec<String>,
+    encoder: &'static str,
+    warnings: Vec<String>,
+}
+
+/// Encoder arguments for a video's AAC audio. The HE profiles only exist in libfdk_aac, so
+/// they fail rather than fall back. VBR maps to libfdk_aac's `-vbr` mode; native aac's VBR
+/// is experimental, so it encodes at the bitrate instead.
+fn aac_encoder_args(settings: AacSettings, bitrate: &str) -> Result<AacEncoding, String> {
+    let he = matches!(settings.profile, Some(AacProfile::He | AacProfile::HeV2));
+    let fdk_built = ffmpeg_has_library("libfdk-aac");
+    let mut warnings = Vec::new();
+    let fdk = match settings.encoder {
+        Some(AacEncoder::Native) if he => {
+            return Err(
+                "HE-AAC profiles need libfdk_aac; set aac_encoder to fdk or auto".to_string(),
+            );
+        }
+        Some(AacEncoder::Native) => false,
+        None | Some(AacEncoder::Auto) if !he => fdk_built && settings.encoder.is_some(),
+        Some(AacEncoder::Fdk) if !fdk_built && !he => {
+            warnings.push(format!(
+                "{}; used the native aac encoder",
+                missing_library_error("libfdk_aac", "libfdk-aac")
+            ));
+            false
+        }
+        _ => true,
+    };
+    if fdk && !fdk_built {
+        return Err(format!(
+            "{}, or use the low profile",
+            missing_library_error("HE-AAC", "libfdk-aac")
+        ));
+    }
+    let encoder = if fdk { "libfdk_aac" } else { "aac" };
+
+    let mut args = vec!["-c:a".to_string(), encoder.to_string()];
+    let profile = match settings.profile {
+        None => None,
+        Some(AacProfile::Low) => Some("aac_low"),
+        Some(AacProfile::He) => Some("aac_he"),
+        Some(AacProfile::HeV2) => Some("aac_he_v2"),
+    };
+    if let Some(profile) = profile {
+        args.extend_from_slice(&["-profile:a".to_string(), profile.to_string()]);
+    }
+    match settings.vbr {
+        Some(mode) if !(1..=5).contains(&mode) => {
+            return Err(format!("Invalid AAC VBR mode {} (expected 1-5)", mode));
+        }
+        Some(mode) if fdk => args.extend_from_slice(&["-vbr".to_string(), mode.to_string()]),
+        Some(_) => {
+            warnings.push(format!(
+                "The native aac encoder has no reliable VBR mode; encoded at {} instead",
+                bitrate
+            ));
+            args.extend_from_slice(&["-b:a".to_string(), bitrate.to_string()]);
+        }
+        None => args.extend_from_slice(&["-b:a".to_string(), bitrate.to_string()]),
+    }
+
+    Ok(AacEncoding {
+        args,
+        encoder,
+        warnings,
+    })
+}
+
+/// Build the FFmpeg arguments that encode a concat list with the given encoder
+fn audio_args(
+    concat_file_path: &Path,
+    output_filters: &[String],
+    encoder_args: &[String],
+    output_path: &str,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-y".to_string(), // Overwrite output
+        "-f".to_string(),
+        "concat".to_string(), // Concat demuxer
+        "-safe".to_string(),
+        "0".to_string(), // Allow absolute paths
+        "-i".to_string(),
+        concat_file_path.to_string_lossy().to_string(),
+    ];
+
+    // Whole-output filters work on the single demuxed stream
+    if !output_filters.is_empty() {
+        args.extend_from_slice(&["-af".to_string(), output_filters.join(",")]);
+    }
+
+    args.extend_from_slice(encoder_args);
+    args.push(output_path.to_string());
+
+    args
+}
+
+/// Parse an FFmpeg bitrate such as "192k" or "1.5M" into bits per second
+fn parse_bitrate(bitrate: &str) -> Option<u64> {
+    let bitrate = bitrate.trim();
+    let (number, multiplier) = match bitrate.char_indices().last()? {
+        (i, 'k') | (i, 'K') => (&bitrate[..i], 1_000.0),
+        (i, 'm') | (i, 'M') => (&bitrate[..i], 1_000_000.0),
+        _ => (bitrate, 1.0),
+    };
+    let bits_per_second = number.parse::<f64>().ok()? * multiplier;
+    (bits_per_second.is_finite() && bits_per_second > 0.0).then_some(bits_per_second as u64)
+}
+
+/// Headroom added to size estimates for container overhead and encoder overshoot
+const OUTPUT_SIZE_MARGIN: f64 = 1.05;
+
+/// Rough size of an encoded output: the clips' total duration at the target bitrate
+fn estimated_output_bytes(clips: &[AudioClip], bitrate: &str) -> Option<u64> {
+    let bits_per_second = parse_bitrate(bitrate)?;
+    let total_duration: f64 = clips.iter().map(|clip| clip.duration).sum();
+    Some((total_duration * bits_per_second as f64 / 8.0 * OUTPUT_SIZE_MARGIN) as u64)
+}
+
+/// Free space on the volume holding `output_path`. The volume is the mounted disk with
+/// the longest mount point containing the output's directory, which must already exist.
+fn disk_status(output_path: &str, required_bytes: u64) -> Result<DiskStatus, String> {
+    let parent = Path::new(output_path)
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let directory = parent.canonicalize().map_err(|e| {
+        format!(
+            "Cannot resolve output directory {}: {}",
+            parent.display(),
+            e
+        )
+    })?;
+
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|disk| directory.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .ok_or_else(|| format!("No mounted volume found for {}", directory.display()))?;
+
+    Ok(DiskStatus {
+        mount_point: disk.mount_point().to_string_lossy().to_string(),
+        available_bytes: disk.available_space(),
+        total_bytes: disk.total_space(),
+        required_bytes,
+        sufficient: disk.available_space() >= required_bytes,
+    })
+}
+
+/// Report free space on the output's volume and whether `required_bytes` will fit
+#[tauri::command]
+fn check_disk_space(output_path: String, required_bytes: u64) -> Result<DiskStatus, String> {
+    disk_status(&output_path, required_bytes)
+}
+
+/// Earliest modification time among the clips' source files
+fn earliest_clip_mtime(clips: &[AudioClip]) -> Option<SystemTime> {
+    clips
+        .iter()
+        .filter_map(|clip| fs::metadata(&clip.path).and_then(|m| m.modified()).ok())
+        .min()
+}
+
+/// Date (year, month, day) of a day count since 1970-01-01, in the proleptic Gregorian
+/// calendar (Howard Hinnant's civil_from_days)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Day count since 1970-01-01 of a Gregorian date; the inverse of civil_from_days
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse an ISO 8601 timestamp as ffprobe prints `creation_time` tags
+/// (`2024-05-01T12:03:11.000000Z`, or with a `+02:00` offset) into Unix seconds. Only real
+/// dates and times are accepted, with a leap second allowed and offsets up to ±14:00.
+fn parse_iso8601(timestamp: &str) -> Option<i64> {
+    let timestamp = timestamp.trim();
+    let (date, time) = timestamp.split_once(['T', ' '])?;
+    let digits = |part: &str| {
+        (!part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| part.parse::<i64>().ok())
+            .flatten()
+    };
+    let date_parts: Vec<i64> = date.split('-').map(digits).collect::<Option<_>>()?;
+    let [year, month, day] = date_parts[..] else {
+        return None;
+    };
+
+    // Split off the zone: Z, +HH:MM, -HH:MM, +HHMM, +HH, or none (taken as UTC)
+    let (clock, offset_secs) = if let Some(clock) = time.strip_suffix('Z') {
+        (clock, 0)
+    } else if let Some(index) = time.rfind(['+', '-']) {
+        let (clock, zone) = time.split_at(index);
+        let sign = if zone.starts_with('-') { -1 } else { 1 };
+        let zone = &zone[1..];
+        let (hours, minutes) = match zone.split_once(':') {
+            Some(parts) => parts,
+            None if zone.len() == 4 => zone.split_at(2),
+            None => (zone, "0"),
+        };
+        let (hours, minutes) = (digits(hours)?, digits(minutes)?);
+        if minutes > 59 || hours * 60 + minutes > 14 * 60 {
+            return None;
+        }
+        (clock, sign * (hours * 3600 + minutes * 60))
+    } else {
+        (time, 0)
+    };
+    let clock = match clock.split_once('.') {
+        Some((clock, fraction)) => {
+            digits(fraction)?;
+            clock
+        }
+        None => clock,
+    };
+    let clock_parts: Vec<i64> = clock.split(':').map(digits).collect::<Option<_>>()?;
+    let [hours, minutes, seconds] = clock_parts[..] else {
+        return None;
+    };
+
+    if !(1..=12).contains(&month) || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    // A day past the end of its month comes back from the round trip as another date
+    let days = days_from_civil(year, month as u32, day as u32);
+    if day < 1 || civil_from_days(days) != (year, month as u32, day as u32) {
+        return None;
+    }
+    Some(days * 86_400 + hours * 3600 + minutes * 60 + seconds - offset_secs)
+}
+
+/// Unix seconds as a filename-safe UTC timestamp, e.g. `2024-05-01_12-03-11`
+fn format_filename_timestamp(secs: i64) -> String {
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let time = secs.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        (time / 60) % 60,
+        time % 60
+    )
+}
+
+/// Unix seconds as an ISO 8601 UTC timestamp, in the form FFmpeg writes `creation_time`
+fn format_iso8601(secs: i64) -> String {
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let time = secs.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.000000Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        (time / 60) % 60,
+        time % 60
+    )
+}
+
+/// `-metadata creation_time=...` for an output: the given timestamp, else the earliest
+/// clip's when `from_clips` is set. Written back in UTC, so every muxer gets the one form
+/// FFmpeg itself produces.
+fn creation_time_args(
+    creation_time: Option<&str>,
+    from_clips: bool,
+    clips: &[AudioClip],
+) -> Result<Vec<String>, String> {
+    let secs = match creation_time {
+        Some(timestamp) => Some(parse_iso8601(timestamp).ok_or_else(|| {
+            format!(
+                "Invalid creation_time '{}'; expected ISO 8601, e.g. 2024-05-01T12:03:11Z",
+                timestamp
+            )
+        })?),
+        None if from_clips => clips.iter().filter_map(clip_timestamp).min(),
+        None => None,
+    };
+    Ok(secs
+        .map(|secs| {
+            vec![
+                "-metadata".to_string(),
+                format!("creation_time={}", format_iso8601(secs)),
+            ]
+        })
+        .unwrap_or_default())
+}
+
+/// Where a recording was made, for scanners that tag their recordings on the move
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoTag {
+    pub latitude: f64,  // Degrees, -90 (south) to 90 (north)
+    pub longitude: f64, // Degrees, -180 (west) to 180 (east)
+    #[serde(default)]
+    pub altitude: Option<f64>, // Meters above sea level
+}
+
+/// Metadata key MP4 outputs carry their location under, as Apple's apps and most phone
+/// galleries read it. FFmpeg only writes keys like it with the `use_metadata_tags` movflag.
+const MP4_LOCATION_KEY: &str = "com.apple.quicktime.location.ISO6709";
+
+impl GeoTag {
+    fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err(format!(
+                "Latitude {} is out of range (expected -90 to 90)",
+                self.latitude
+            ));
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            return Err(format!(
+                "Longitude {} is out of range (expected -180 to 180)",
+                self.longitude
+            ));
+        }
+        if self.altitude.is_some_and(|altitude| !altitude.is_finite()) {
+            return Err("Altitude must be a finite number of meters".to_string());
+        }
+        Ok(())
+    }
+
+    /// ISO 6709 form, e.g. "+37.7749-122.4194/" or "+37.7749-122.4194+012.500/", with
+    /// the zero-padded degrees QuickTime expects
+    fn iso6709(&self) -> String {
+        let mut tag = format!("{:+08.4}{:+09.4}", self.latitude, self.longitude);
+        if let Some(altitude) = self.altitude {
+            tag.push_str(&format!("{:+08.3}", altitude));
+        }
+        tag.push('/');
+        tag
+    }
+}
+
+/// Parse an ISO 6709 location in decimal degrees, as phones and FFmpeg write it
+fn parse_iso6709(value: &str) -> Option<GeoTag> {
+    let value = value.trim().trim_end_matches('/');
+    // Each coordinate starts at its sign
+    let mut parts: Vec<String> = Vec::new();
+    for c in value.chars() {
+        match c {
+            '+' | '-' => parts.push(c.to_string()),
+            _ => parts.last_mut()?.push(c),
+        }
+    }
+    let numbers: Vec<f64> = parts
+        .iter()
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let (latitude, longitude, altitude) = match numbers[..] {
+        [latitude, longitude] => (latitude, longitude, None),
+        [latitude, longitude, altitude] => (latitude, longitude, Some(altitude)),
+        _ => return None,
+    };
+    let tag = GeoTag {
+        latitude,
+        longitude,
+        altitude,
+    };
+    tag.validate().ok().map(|_| tag)
+}
+
+/// A clip's location tag, whether it was written by a phone, a QuickTime muxer or as
+/// a plain `location` tag (container, then stream)
+fn probe_location(path: &str) -> Option<GeoTag> {
+    let entries = format!(
+        "format_tags=location,{}:stream_tags=location",
+        MP4_LOCATION_KEY
+    );
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            &entries,
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+            path,
+        ])
+        .output()
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(parse_iso6709)
+}
+
+/// `-metadata` for an output's location: the given tag, else the first clip's that has
+/// one when `from_clips` is set. Every container gets the ISO 6709 string: MP4 under
+/// MP4_LOCATION_KEY, MP3 as ID3's TXXX:location and Ogg as a LOCATION comment.
+fn location_args(
+    location: Option<&GeoTag>,
+    from_clips: bool,
+    clips: &[AudioClip],
+    format: OutputFormat,
+) -> Result<Vec<String>, String> {
+    let tag = match location {
+        Some(tag) => {
+            tag.validate()?;
+            Some(tag.clone())
+        }
+        None if from_clips => clips.iter().find_map(|clip| probe_location(&clip.path)),
+        None => None,
+    };
+    let key = match format {
+        OutputFormat::Mp4 => MP4_LOCATION_KEY,
+        OutputFormat::Mp3 | OutputFormat::Opus => "location",
+    };
+    Ok(tag
+        .map(|tag| {
+            vec![
+                "-metadata".to_string(),
+                format!("{}={}", key, tag.iso6709()),
+            ]
+        })
+        .unwrap_or_default())
+}
+
+/// A clip's `creation_time` tag (container, then stream) in Unix seconds
+fn probe_creation_time(path: &str) -> Option<i64> {
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format_tags=creation_time:stream_tags=creation_time",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+            path,
+        ])
+        .output()
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(parse_iso8601)
+}
+
+/// When a clip was recorded: its creation_time tag, else its file's modification time
+fn clip_timestamp(clip: &AudioClip) -> Option<i64> {
+    probe_creation_time(&clip.path).or_else(|| {
+        let modified = fs::metadata(&clip.path).and_then(|m| m.modified()).ok()?;
+        let secs = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        i64::try_from(secs).ok()
+    })
+}
+
+/// Replace characters that aren't allowed in file names on some platform
+fn sanitize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_matches([' ', '.'])
+        .to_string()
+}
+
+/// Most numbered names tried before giving up on finding a free output name
+const MAX_NAME_ATTEMPTS: u32 = 10_000;
+
+/// Name an output after the time range its clips cover, e.g.
+/// `2024-05-01_12-03-11-to-2024-05-01_14-22-09.mp3` in `output_dir`, from the first and
+/// last clips' creation times (UTC), falling back to their file modification times. When
+/// neither is available, the name is sequential (`stitched-001.mp3`). A name already taken
+/// gets a numeric suffix.
+#[tauri::command]
+fn time_range_output_path(
+    clips: Vec<AudioClip>,
+    output_dir: String,
+    format: OutputFormat,
+) -> Result<String, String> {
+    let clips = order_clips(clips)?;
+    let (Some(first), Some(last)) = (clips.first(), clips.last()) else {
+        return Err("No clips provided".to_string());
+    };
+    let dir = Path::new(&output_dir);
+    if !dir.is_dir() {
+        return Err(format!("Output folder not found: {}", output_dir));
+    }
+
+    let range = match (clip_timestamp(first), clip_timestamp(last)) {
+        (Some(start), Some(end)) => Some(sanitize_filename(&format!(
+            "{}-to-{}",
+            format_filename_timestamp(start.min(end)),
+            format_filename_timestamp(start.max(end))
+        ))),
+        _ => None,
+    };
+
+    let extension = format.extension();
+    let candidates = (1..=MAX_NAME_ATTEMPTS).map(|n| match (&range, n) {
+        (Some(range), 1) => format!("{}.{}", range, extension),
+        (Some(range), n) => format!("{}-{}.{}", range, n, extension),
+        (None, n) => format!("stitched-{:03}.{}", n, extension),
+    });
+    candidates
+        .map(|name| dir.join(name))
+        .find(|path| !path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| "No free output name in the folder".to_string())
+}
+
+/// Details a scanner recorder wrote into a recording's filename
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordingMeta {
+    pub pattern: String,        // The template that matched
+    pub timestamp: Option<i64>, // Unix seconds; SDRTrunk's local wall-clock time is taken as-is
+    pub talkgroup: Option<u32>,
+    pub radio_id: Option<u32>,
+    pub frequency_hz: Option<f64>,
+    pub label: Option<String>, // System/alias text, e.g. "Metro Police"
+}
+
+/// Filename templates tried after any caller-supplied ones, most specific first: SDRTrunk
+/// (`20240115_143022Metro_Police__TO_1234_FROM_5678`) then Trunk Recorder
+/// (`1234-1705329022_851012500.0-call_17`)
+const RECORDING_PATTERNS: [&str; 5] = [
+    "{date}_{time}{label}__TO_{talkgroup}_FROM_{radio}",
+    "{date}_{time}{label}__TO_{talkgroup}",
+    "{date}_{time}{label}_{talkgroup}",
+    "{talkgroup}-{unix}_{frequency}-call_{*}",
+    "{talkgroup}-{unix}_{frequency}",
+];
+
+/// A field a filename template can capture
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NameField {
+    Date,      // YYYYMMDD
+    Time,      // HHMMSS
+    Unix,      // Unix seconds
+    Talkgroup, // Digits
+    Radio,     // Digits
+    Frequency, // Hz, or MHz when it has a decimal point and is under 10,000
+    Label,     // Any text; underscores read as spaces
+    Skip,      // Any text, discarded (`{*}`)
+}
+
+enum NameToken {
+    Literal(String),
+    Field(NameField),
+}
+
+/// Split a template such as `{talkgroup}-{unix}_{frequency}` into literals and fields
+fn parse_name_template(template: &str) -> Result<Vec<NameToken>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        if open > 0 {
+            tokens.push(NameToken::Literal(rest[..open].to_string()));
+        }
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed '{{' in pattern '{}'", template))?;
+        let field = match &rest[open + 1..open + close] {
+            "date" => NameField::Date,
+            "time" => NameField::Time,
+            "unix" => NameField::Unix,
+            "talkgroup" => NameField::Talkgroup,
+            "radio" => NameField::Radio,
+            "frequency" => NameField::Frequency,
+            "label" => NameField::Label,
+            "*" => NameField::Skip,
+            other => {
+                return Err(format!(
+                    "Unknown field '{{{}}}' in pattern '{}'",
+                    other, template
+                ))
+            }
+        };
+        tokens.push(NameToken::Field(field));
+        rest = &rest[open + close + 1..];
+    }
+    if !rest.is_empty() {
+        tokens.push(NameToken::Literal(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+/// Whether `text` is a valid value for a field, before any range checks
+fn name_field_accepts(field: NameField, text: &str) -> bool {
+    let digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    match field {
+        NameField::Date => text.len() == 8 && digits(text),
+        NameField::Time => text.len() == 6 && digits(text),
+        NameField::Unix | NameField::Talkgroup | NameField::Radio => digits(text),
+        NameField::Frequency => match text.split_once('.') {
+            Some((whole, fraction)) => digits(whole) && digits(fraction),
+            None => digits(text),
+        },
+        NameField::Label | NameField::Skip => !text.is_empty(),
+    }
+}
+
+/// Match the whole of `name` against the tokens, trying the shortest value for each
+/// field first and backtracking, collecting what each field captured
+fn match_name_tokens<'a>(
+    tokens: &[NameToken],
+    name: &'a str,
+    captures: &mut Vec<(NameField, &'a str)>,
+) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return name.is_empty();
+    };
+    match token {
+        NameToken::Literal(literal) => name
+            .strip_prefix(literal.as_str())
+            .is_some_and(|name| match_name_tokens(rest, name, captures)),
+        NameToken::Field(field) => {
+            for end in name
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .filter(|&end| name_field_accepts(*field, &name[..end]))
+            {
+                captures.push((*field, &name[..end]));
+                if match_name_tokens(rest, &name[end..], captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+    }
+}
+
+/// Build the metadata from a template's captures, or None when a value is out of range
+fn recording_meta(pattern: &str, captures: &[(NameField, &str)]) -> Option<RecordingMeta> {
+    let mut meta = RecordingMeta {
+        pattern: pattern.to_string(),
+        ..Default::default()
+    };
+    let (mut date, mut time) = (None, None);
+    for &(field, text) in captures {
+        match field {
+            NameField::Date => {
+                let (year, month, day) = (
+                    text[..4].parse::<i64>().ok()?,
+                    text[4..6].parse::<u32>().ok()?,
+                    text[6..].parse::<u32>().ok()?,
+                );
+                if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+                    return None;
+                }
+                date = Some(days_from_civil(year, month, day));
+            }
+            NameField::Time => {
+                let (hour, minute, second) = (
+                    text[..2].parse::<i64>().ok()?,
+                    text[2..4].parse::<i64>().ok()?,
+                    text[4..].parse::<i64>().ok()?,
+                );
+                if hour > 23 || minute > 59 || second > 59 {
+                    return None;
+                }
+                time = Some(hour * 3600 + minute * 60 + second);
+            }
+            NameField::Unix => meta.timestamp = Some(text.parse().ok()?),
+            NameField::Talkgroup => meta.talkgroup = Some(text.parse().ok()?),
+            NameField::Radio => meta.radio_id = Some(text.parse().ok()?),
+            NameField::Frequency => {
+                let value: f64 = text.parse().ok()?;
+                meta.frequency_hz = Some(if text.contains('.') && value < 10_000.0 {
+                    value * 1_000_000.0
+                } else {
+                    value
+                });
+            }
+            NameField::Label => {
+                let label = text.replace('_', " ").trim().to_string();
+                meta.label = (!label.is_empty()).then_some(label);
+            }
+            NameField::Skip => {}
+        }
+    }
+    if let Some(days) = date {
+        meta.timestamp = Some(days * 86_400 + time.unwrap_or(0));
+    }
+    Some(meta)
+}
+
+/// Parse a filename (with or without its directory and audio extension) against
+/// `patterns`, then the built-in RECORDING_PATTERNS, returning the first match
+fn match_recording_name(name: &str, patterns: &[String]) -> Result<Option<RecordingMeta>, String> {
+    let file_name = Path::new(name)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // Only a known audio extension comes off: Trunk Recorder frequencies contain a dot
+    let stem = match file_name.rsplit_once('.') {
+        Some((stem, ext)) if AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()) => stem,
+        _ => file_name.as_str(),
+    };
+
+    let patterns = patterns
+        .iter()
+        .map(String::as_str)
+        .chain(RECORDING_PATTERNS);
+    for pattern in patterns {
+        let tokens = parse_name_template(pattern)?;
+        let mut captures = Vec::new();
+        if match_name_tokens(&tokens, stem, &mut captures) {
+            if let Some(meta) = recording_meta(pattern, &captures) {
+                return Ok(Some(meta));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Read the date/time, talkgroup, radio and frequency a scanner recorder (SDRTrunk,
+/// Trunk Recorder) encoded in a filename. `patterns` are extra templates tried first,
+/// built from `{date}`, `{time}`, `{unix}`, `{talkgroup}`, `{radio}`, `{frequency}`,
+/// `{label}` and `{*}` (ignored text). None when nothing matches.
+#[tauri::command]
+fn parse_recording_filename(
+    name: String,
+    patterns: Option<Vec<String>>,
+) -> Result<Option<RecordingMeta>, String> {
+    match_recording_name(&name, &patterns.unwrap_or_default())
+}
+
+/// Clip name built from a recording's metadata, e.g. "Metro Police TG 1234 2024-01-15 14:30:22"
+fn recording_clip_name(meta: &RecordingMeta) -> Option<String> {
+    let mut parts: Vec<String> = meta.label.iter().cloned().collect();
+    if let Some(talkgroup) = meta.talkgroup {
+        parts.push(format!("TG {}", talkgroup));
+    }
+    if let Some(secs) = meta.timestamp {
+        let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+        let time = secs.rem_euclid(86_400);
+        parts.push(format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            time / 3600,
+            (time / 60) % 60,
+            time % 60
+        ));
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+/// Name clips from their recorder filenames and put them in chronological order. Since
+/// chapters are titled by clip name, this also seeds the chapter titles. Clips whose
+/// names don't parse keep their name and go after the dated ones, in their list order.
+/// Any explicit `order` is renumbered to match, so order_clips keeps the new sequence.
+#[tauri::command]
+fn label_recordings(
+    clips: Vec<AudioClip>,
+    patterns: Option<Vec<String>>,
+) -> Result<Vec<AudioClip>, String> {
+    let patterns = patterns.unwrap_or_default();
+    let mut labeled = Vec::with_capacity(clips.len());
+    for mut clip in clips {
+        let meta = match_recording_name(&clip.path, &patterns)?;
+        if let Some(name) = meta.as_ref().and_then(recording_clip_name) {
+            clip.name = name;
+        }
+        labeled.push((meta.and_then(|meta| meta.timestamp), clip));
+    }
+
+    // A stable sort keeps same-second recordings in list order
+    labeled.sort_by_key(|(timestamp, _)| (timestamp.is_none(), *timestamp));
+    let ordered = labeled.iter().any(|(_, clip)| clip.order.is_some());
+    Ok(labeled
+        .into_iter()
+        .enumerate()
+        .map(|(position, (_, mut clip))| {
+            clip.order = ordered.then_some(position as u32);
+            clip
+        })
+        .collect())
+}
+
+/// Where the encrypted copy of an output is written
+fn encrypted_path(path: &str) -> String {
+    format!("{}.enc", path)
+}
+
+/// Encrypt `input` to `output` with a passphrase
+fn encrypt_file(input: &str, output: &str, passphrase: &str) -> Result<(), String> {
+    let mut reader =
+        File::open(input).map_err(|e| format!("Failed to open {} for encryption: {}", input, e))?;
+    let file = File::create(output).map_err(|e| format!("Failed to create {}: {}", output, e))?;
+
+    let encryptor =
+        age::Encryptor::with_user_passphrase(age::secrecy::Secret::new(passphrase.to_string()));
+    let mut writer = encryptor
+        .wrap_output(file)
+        .map_err(|e| format!("Failed to start encryption: {}", e))?;
+    io::copy(&mut reader, &mut writer)
+        .and_then(|_| writer.finish())
+        .and_then(|file| file.sync_all())
+        .map_err(|e| format!("Failed to encrypt {}: {}", input, e))
+}
+
+/// Refuse to replace an existing encrypted output unless the user agrees to it, since the
+/// old file may have been written with another passphrase
+fn confirm_encrypted_overwrite(
+    output_path: &str,
+    prompt: Option<&OverwritePrompt>,
+) -> Option<StitchResult> {
+    let encrypted = encrypted_path(output_path);
+    match prompt {
+        Some(prompt) => confirm_overwrite(&encrypted, prompt),
+        None if Path::new(&encrypted).exists() => Some(stitch_error(format!(
+            "{} already exists; set confirm_overwrite to replace it",
+            encrypted
+        ))),
+        None => None,
+    }
+}
+
+/// Encrypt a sidecar of an encrypted output, returning its new path. One that can't be
+/// encrypted is dropped with a warning rather than left in plain text.
+fn encrypt_sidecar(
+    result: &mut StitchResult,
+    path: &str,
+    config: &EncryptConfig,
+) -> Option<String> {
+    let encrypted = encrypted_path(path);
+    let outcome = encrypt_file(path, &encrypted, &config.passphrase);
+    let _ = fs::remove_file(path);
+    match outcome {
+        Ok(()) => Some(encrypted),
+        Err(e) => {
+            let _ = fs::remove_file(&encrypted);
+            result
+                .warnings
+                .push(format!("{} was not kept: {}", path, e));
+            None
+        }
+    }
+}
+
+/// Replace a successful output with its encrypted copy, returning the path now holding the
+/// output. The plaintext is removed even when encryption fails, so it's never left behind.
+fn encrypt_output(result: &mut StitchResult, output_path: &str, config: &EncryptConfig) -> String {
+    let encrypted = encrypted_path(output_path);
+    if !result.success {
+        return output_path.to_string();
+    }
+
+    match encrypt_file(output_path, &encrypted, &config.passphrase) {
+        Ok(()) => {
+            if let Err(e) = fs::remove_file(output_path) {
+                result.warnings.push(format!(
+                    "Encrypted, but the unencrypted output couldn't be removed: {}",
+                    e
+                ));
+            }
+            result.output_path = Some(encrypted.clone());
+            encrypted
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&encrypted);
+            let _ = fs::remove_file(output_path);
+            result.success = false;
+            result.output_path = None;
+            result.error = Some(e);
+            output_path.to_string()
+        }
+    }
+}
+
+/// Decrypt a file written with the `encrypt` option (or any passphrase-encrypted age file)
+#[tauri::command]
+async fn decrypt_file(path: String, passphrase: String, output: String) -> Result<(), String> {
+    let file = File::open(&path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let decryptor = match age::Decryptor::new(file) {
+        Ok(age::Decryptor::Passphrase(decryptor)) => decryptor,
+        Ok(_) => return Err("File is encrypted to a key, not a passphrase".to_string()),
+        Err(e) => return Err(format!("Not an encrypted file: {}", e)),
+    };
+    let mut reader = decryptor
+        .decrypt(&age::secrecy::Secret::new(passphrase), None)
+        .map_err(|e| match e {
+            age::DecryptError::DecryptionFailed | age::DecryptError::NoMatchingKeys => {
+                "Wrong passphrase".to_string()
+            }
+            e => format!("Failed to decrypt: {}", e),
+        })?;
+
+    // Decrypt beside the destination first: a truncated or tampered file only fails its
+    // authentication partway through, and that mustn't leave a partial output behind
+    let partial = partial_output_path(&output);
+    let decrypted = File::create(&partial)
+        .and_then(|mut file| io::copy(&mut reader, &mut file).and_then(|_| file.sync_all()))
+        .map_err(|e| format!("Encrypted file is damaged or incomplete: {}", e))
+        .and_then(|_| {
+            fs::rename(&partial, &output)
+                .map_err(|e| format!("Failed to move the decrypted file into place: {}", e))
+        });
+    if decrypted.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    decrypted
+}
+
+/// Set a file's modification time, returning the applied time in Unix seconds
+fn apply_mtime(path: &str, mtime: SystemTime) -> Result<u64, String> {
+    File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(mtime))
+        .map_err(|e| format!("Failed to set modification time: {}", e))?;
+
+    Ok(mtime
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0))
+}
+
+/// Peaks within this many dB of full scale are reported as clipping
+const CLIPPING_MARGIN_DB: f64 = 0.1;
+
+/// Peaks within this many dB of the limiter ceiling count as the limiter having engaged
+const LIMITER_ENGAGED_MARGIN_DB: f64 = 0.5;
+
+/// Measure a file's overall sample peak in dBFS using astats
+fn peak_level_db(path: &str) -> Result<f64, String> {
+    overall_astat(path, "Peak level dB:")
+        .map_err(|_| "Could not measure output peak level".to_string())
+}
+
+/// Measure a file's overall DC offset as a fraction of full scale using astats
+fn dc_offset(path: &str) -> Result<f64, String> {
+    overall_astat(path, "DC offset:")
+        .map_err(|_| format!("Could not measure DC offset of {}", path))
+}
+
+/// DC blocker for the per-clip chain: a gentle high-pass well below any voice content
+const DC_BLOCK_FILTER: &str = "highpass=f=20";
+
+/// Ahead of the blocker, measures the offset being removed as the render decodes each
+/// clip. Its summary goes to the render's log, where dc_offsets_in_log picks it up.
+const DC_MEASURE_FILTER: &str =
+    "astats=metadata=0:measure_perchannel=none:measure_overall=DC_offset";
+
+/// Every clip's DC offset from a render log holding DC_MEASURE_FILTER summaries, one per
+/// clip, as magnitudes, since a negative bias is as much an offset as a positive one
+fn dc_offsets_in_log(log: &str) -> Vec<f64> {
+    log.lines()
+        .filter_map(|line| line.split("DC offset:").nth(1))
+        .filter_map(|value| value.trim().parse::<f64>().ok())
+        .map(f64::abs)
+        .collect()
+}
+
+/// Run astats over a file and read one statistic from its Overall section
+fn overall_astat(path: &str, label: &str) -> Result<f64, String> {
+    let output = ffmpeg_command()
+        .args([
+            "-hide_banner",
+            "-i",
+            path,
+            "-af",
+            "astats=metadata=0",
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+
+    // astats prints one section per channel followed by "Overall"; the last value is the overall one
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr
+        .lines()
+        .filter_map(|line| line.split(label).nth(1))
+        .filter_map(|value| value.trim().parse::<f64>().ok())
+        .next_back()
+        .ok_or_else(|| format!("astats reported no '{}'", label))
+}
+
+/// Filters applied once to the joined output, in order (loudnorm, gain, tempo, padding,
+/// resampling, then the limiter), and the resampler used
+fn output_filter_chain(
+    audio_config: &AudioConfig,
+    warnings: &mut Vec<String>,
+) -> Result<(Vec<String>, Option<String>), String> {
+    let mut output_filters = Vec::new();
+    if let Some(loudnorm) = &audio_config.loudnorm {
+        output_filters.push(loudnorm.filter());
+    }
+    if let Some(gain_db) = audio_config.gain_db {
+        output_filters.push(format!("volume={}dB", gain_db));
+    }
+    if let Some(speed) = audio_config.speed {
+        output_filters.push(atempo_filter(speed)?);
+    }
+    // The silence goes after the tempo change so the target is the output's real length
+    if let Some(target) = audio_config.pad_to_duration {
+        if !(target > 0.0 && target.is_finite()) {
+            return Err("pad_to_duration must be greater than zero".to_string());
+        }
+        output_filters.push(format!("apad=whole_dur={}", target));
+    }
+
+    let mut resampler = None;
+    if let Some(sample_rate) = audio_config.sample_rate {
+        if !(8000..=192000).contains(&sample_rate) {
+            return Err(format!(
+                "Invalid sample rate {} (expected 8000-192000 Hz)",
+                sample_rate
+            ));
+        }
+        let (filter, name) = resample_filter(sample_rate, audio_config.resample_quality, warnings);
+        output_filters.push(filter);
+        resampler = Some(name.to_string());
+    }
+
+    if let Some(limiter) = &audio_config.limiter {
+        if !(-24.0..=0.0).contains(&limiter.limit_db) {
+            return Err("Limiter ceiling must be between -24 and 0 dBFS".to_string());
+        }
+        output_filters.push(limiter.filter());
+    }
+
+    Ok((output_filters, resampler))
+}
+
+/// How the filter path would join the clips, checking a crossfade against every clip's
+/// length (as capped at `cap`)
+fn clip_join<'a>(
+    audio_config: &'a AudioConfig,
+    clips: &[AudioClip],
+    cap: Option<f64>,
+) -> Result<ClipJoin<'a>, String> {
+    if let Some(crossfade) = &audio_config.crossfade {
+        if crossfade.duration_seconds <= 0.0 {
+            return Err("Crossfade duration must be greater than zero".to_string());
+        }
+        if let Some(clip) = clips.iter().find(|clip| {
+            cap.map_or(clip.duration, |cap| clip.duration.min(cap)) < crossfade.duration_seconds
+        }) {
+            return Err(format!(
+                "Clip '{}' is shorter than the {}s crossfade",
+                clip.name, crossfade.duration_seconds
+            ));
+        }
+    }
+    if let Some(tone) = &audio_config.marker_tone {
+        tone.validate()?;
+    }
+    Ok(match (&audio_config.crossfade, &audio_config.marker_tone) {
+        (Some(_), Some(_)) => {
+            return Err("Marker tones can't be combined with crossfades".to_string());
+        }
+        (Some(crossfade), None) => ClipJoin::Crossfade(crossfade),
+        (None, Some(tone)) if clips.len() > 1 => ClipJoin::MarkerTone(tone),
+        _ => ClipJoin::Concat,
+    })
+}
+
+/// Fit the clips under max_total_duration, returning the paths of those dropped and the
+/// limit the output gets cut at, if the clips run over it. Measured in joined time like
+/// the joins themselves, so a speed change stretches the limit.
+fn apply_length_limit(
+    clips: &mut Vec<AudioClip>,
+    audio_config: &AudioConfig,
+    cap: Option<f64>,
+    join: ClipJoin,
+    truncated: &mut Vec<ClipTruncation>,
+    warnings: &mut Vec<String>,
+) -> Result<(Vec<String>, Option<f64>), String> {
+    let mut dropped = Vec::new();
+    let Some(max) = audio_config.max_total_duration else {
+        return Ok((dropped, None));
+    };
+    if !(max > 0.0 && max.is_finite()) {
+        return Err("max_total_duration must be greater than zero".to_string());
+    }
+    if audio_config
+        .pad_to_duration
+        .is_some_and(|target| target > max)
+    {
+        return Err("pad_to_duration can't be longer than max_total_duration".to_string());
+    }
+    let speed = audio_config.speed.unwrap_or(1.0);
+    let durations: Vec<f64> = clips
+        .iter()
+        .map(|clip| cap.map_or(clip.duration, |cap| clip.duration.min(cap)))
+        .collect();
+    let starts = clip_start_times(&durations, join);
+    let joined_end = starts
+        .last()
+        .zip(durations.last())
+        .map_or(0.0, |(start, length)| start + length);
+    let limit = max * speed;
+    if joined_end <= limit {
+        return Ok((dropped, None));
+    }
+    if !audio_config.truncate_to_max {
+        return Err(format!(
+            "Clips run {:.1}s, {:.1}s over the {:.1}s max_total_duration",
+            joined_end / speed,
+            (joined_end - limit) / speed,
+            max
+        ));
+    }
+
+    let keep = starts
+        .iter()
+        .take_while(|&&start| start < limit)
+        .count()
+        .max(1);
+    for clip in clips.drain(keep..) {
+        warnings.push(format!("Dropped '{}' to fit max_total_duration", clip.name));
+        dropped.push(clip.path);
+    }
+    truncated.retain(|entry| !dropped.contains(&entry.path));
+    let last = &clips[keep - 1];
+    let kept_seconds = limit - starts[keep - 1];
+    if kept_seconds < durations[keep - 1] {
+        truncated.retain(|entry| entry.path != last.path);
+        truncated.push(ClipTruncation {
+            path: last.path.clone(),
+            original_seconds: last.duration,
+            removed_seconds: last.duration - kept_seconds,
+        });
+    }
+    Ok((dropped, Some(max)))
+}
+
+/// What in a request rules out the concat demuxer, in plain words; empty when nothing
+/// does. The demuxer reads the clips as one stream, so anything done to a clip on its
+/// own or at the joins needs every clip decoded as a separate filter input.
+fn filter_path_reasons(
+    clip_filters: &[String],
+    cap_inputs: bool,
+    panned: bool,
+    boundary_fade: Option<f64>,
+    remixed: usize,
+    edge_trimmed: usize,
+    join: ClipJoin,
+) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if !clip_filters.is_empty() {
+        let names: Vec<&str> = clip_filters
+            .iter()
+            .map(|filter| filter.split('=').next().unwrap_or(filter))
+            .collect();
+        reasons.push(format!(
+            "Per-clip filters ({}) run on each clip separately",
+            names.join(", ")
+        ));
+    }
+    if cap_inputs {
+        reasons.push("cap_clip_length cuts clips one by one".to_string());
+    }
+    if panned {
+        reasons.push("Panned clips are placed one by one".to_string());
+    }
+    if boundary_fade.is_some() {
+        reasons.push("Boundary fades are applied to each clip".to_string());
+    }
+    if remixed > 0 {
+        reasons.push(format!(
+            "{} clip(s) are converted to the channel target",
+            remixed
+        ));
+    }
+    if edge_trimmed > 0 {
+        reasons.push(format!(
+            "{} clip(s) have dead air skipped at their edges",
+            edge_trimmed
+        ));
+    }
+    match join {
+        ClipJoin::Concat => {}
+        ClipJoin::Crossfade(_) => {
+            reasons.push("Crossfades blend each clip into the next".to_string())
+        }
+        ClipJoin::MarkerTone(_) => {
+            reasons.push("Marker tones are inserted between clips".to_string())
+        }
+    }
+    reasons
+}
+
+/// Whether the demuxer can join the clips' streams as-is. Either way round a fallback to
+/// the concat filter carries a warning saying why.
+enum DemuxerFormats {
+    Match,
+    Differ(String),
+    Unreadable(String), // A clip's format couldn't be probed, so a match can't be assumed
+}
+
+impl DemuxerFormats {
+    fn check(clips: &[AudioClip]) -> Self {
+        let plan = concat_plan(clips);
+        match (plan.mismatch_index, plan.mismatch_property, plan.error) {
+            _ if plan.demuxer_compatible => DemuxerFormats::Match,
+            (Some(index), Some(property), _) => DemuxerFormats::Differ(format!(
+                "Clip {} has a different {} than the first clip; re-encoding through the concat filter",
+                index + 1,
+                property
+            )),
+            (index, _, error) => DemuxerFormats::Unreadable(format!(
+                "Couldn't read the format of clip {} ({}); re-encoding through the concat filter to be safe",
+                index.map_or(0, |index| index + 1),
+                error.unwrap_or_default().trim()
+            )),
+        }
+    }
+}
+
+/// MP3 packets sampled from the start of a clip to tell VBR from CBR
+const VBR_SAMPLE_PACKETS: u32 = 200;
+
+/// Whether a clip is VBR MP3. CBR frames differ in size by at most the one padding byte,
+/// so a wider spread among the first packets means the bitrate moves.
+fn is_vbr_mp3(path: &str) -> Result<bool, String> {
+    let interval = format!("%+#{}", VBR_SAMPLE_PACKETS);
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "a:0",
+            "-read_intervals",
+            &interval,
+            "-show_entries",
+            "stream=codec_name:packet=size",
+            "-of",
+            "json",
+            path,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFprobe error: {}", error));
+    }
+
+    let probe: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse ffprobe output: {}", e))?;
+    if probe["streams"][0]["codec_name"].as_str() != Some("mp3") {
+        return Ok(false);
+    }
+    let sizes: Vec<u64> = probe["packets"]
+        .as_array()
+        .map(|packets| {
+            packets
+                .iter()
+                .filter_map(|packet| packet["size"].as_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    match (sizes.iter().min(), sizes.iter().max()) {
+        (Some(min), Some(max)) => Ok(max - min > 1),
+        _ => Ok(false),
+    }
+}
+
+/// What vbr_handling makes of a join: whether it still uses the demuxer, whether an MP3
+/// output's header is rewritten afterwards, and why, when VBR clips changed anything
+struct VbrPlan {
+    use_demuxer: bool,
+    fix_header: bool,
+    reason: Option<String>,
+    warning: Option<String>,
+}
+
+/// Check for VBR MP3 clips where it matters, on a demuxer join, and apply vbr_handling
+fn vbr_plan(
+    clips: &[AudioClip],
+    use_demuxer: bool,
+    handling: VbrHandling,
+    format: OutputFormat,
+) -> VbrPlan {
+    let mut plan = VbrPlan {
+        use_demuxer,
+        fix_header: false,
+        reason: None,
+        warning: None,
+    };
+    if !use_demuxer {
+        return plan;
+    }
+    let vbr = clips
+        .iter()
+        .filter(|clip| is_vbr_mp3(&clip.path).unwrap_or(false))
+        .count();
+    if vbr == 0 {
+        return plan;
+    }
+
+    match (handling, format) {
+        (VbrHandling::Warn, _) => {
+            plan.warning = Some(format!(
+                "{} clip(s) are VBR MP3, so the output's duration may be misreported; set vbr_handling to filter_path or fix_duration",
+                vbr
+            ));
+        }
+        (VbrHandling::FixDuration, OutputFormat::Mp3) => {
+            plan.fix_header = true;
+            plan.reason = Some(format!(
+                "{} clip(s) are VBR MP3: joined by the demuxer, then the output's header is rewritten to count every frame",
+                vbr
+            ));
+        }
+        (VbrHandling::FilterPath | VbrHandling::FixDuration, _) => {
+            plan.use_demuxer = false;
+            plan.reason = Some(format!(
+                "{} clip(s) are VBR MP3, so they're decoded through the concat filter for an accurate duration",
+                vbr
+            ));
+        }
+    }
+    plan
+}
+
+/// Remux a finished MP3 in place so its Xing header's frame count and seek table match
+/// the frames actually in it
+fn rewrite_mp3_header(output_path: &str) -> Result<(), String> {
+    let temp_path = Path::new(output_path)
+        .with_extension("xing.mp3")
+        .to_string_lossy()
+        .to_string();
+    run_ffmpeg_task(&[
+        "-y".to_string(),
+        "-i".to_string(),
+        output_path.to_string(),
+        "-map".to_string(),
+        "0".to_string(),
+        "-c".to_string(),
+        "copy".to_string(),
+        "-write_xing".to_string(),
+        "1".to_string(),
+        temp_path.clone(),
+    ])
+    .and_then(|_| {
+        fs::rename(&temp_path, output_path)
+            .map_err(|e| format!("Failed to replace {}: {}", output_path, e))
+    })
+    .inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+/// Whether a demuxer join can copy the audio rather than re-encode it: re-encoding MP3
+/// to MP3 at the same bitrate only loses quality and time
+fn can_stream_copy(
+    clips: &[AudioClip],
+    output_filters: &[String],
+    format: OutputFormat,
+    bitrate: &str,
+) -> bool {
+    output_filters.is_empty() && format == OutputFormat::Mp3 && can_stream_copy_mp3(clips, bitrate)
+}
+
+/// Per-clip filters requested by the audio config, in the order they are applied
+fn clip_filter_chain(audio_config: &AudioConfig) -> Result<Vec<String>, String> {
+    let mut filters = Vec::new();
+
+    if audio_config.remove_dc_offset {
+        filters.push(DC_MEASURE_FILTER.to_string());
+        filters.push(DC_BLOCK_FILTER.to_string());
+    }
+    if let Some(gate) = &audio_config.noise_gate {
+        filters.push(gate.filter());
+    }
+    if let Some(band_filter) = &audio_config.band_filter {
+        filters.extend(band_filter.filters()?);
+    }
+    for notch in &audio_config.notch_filters {
+        filters.push(notch.filter()?);
+    }
+    if audio_config.auto_deharsh {
+        for (frequency_hz, q) in DEHARSH_NOTCHES {
+            filters.push(NotchFilter { frequency_hz, q }.filter()?);
+        }
+    }
+
+    Ok(filters)
+}
+
+/// Build the FFmpeg arguments that filter each clip and join them, with the concat filter
+/// (optionally with marker tones between clips) or a chain of crossfades
+fn filter_audio_args(
+    clips: &[AudioClip],
+    chain: &ClipChain,
+    join: ClipJoin,
+    output_filters: &[String],
+    encoder_args: &[String],
+    output_path: &str,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec!["-y".to_string()]; // Overwrite output
+
+    // Each clip is its own input so it can be filtered independently, and every clip stops
+    // being read at the cap, whatever its reported duration. Trimmed clips are read from
+    // past their leading dead air to the end of the audio (or the cap), measured in the
+    // source's time.
+    for clip in clips {
+        let max_length = chain.max_length;
+        if let Some(trim) = chain.edge_trims.and_then(|trims| trims.get(&clip.path)) {
+            let end = trim.lead + max_length.map_or(clip.duration, |max| clip.duration.min(max));
+            args.extend_from_slice(&[
+                "-ss".to_string(),
+                trim.lead.to_string(),
+                "-to".to_string(),
+                end.to_string(),
+            ]);
+        } else if let Some(max_length) = max_length {
+            args.extend_from_slice(&["-t".to_string(), max_length.to_string()]);
+        }
+        args.extend_from_slice(&["-i".to_string(), clip.path.clone()]);
+    }
+
+    // [0:a]filters[a0];[1:a]filters[a1];...;[a0][a1]...concat=n=N:v=0:a=1,output_filters[out]
+    // anull passes audio through untouched when only the concat filter itself is needed
+    let mut graph = String::new();
+    for (i, clip) in clips.iter().enumerate() {
+        let filters = chain.clip_filters(clip);
+        let filters = if filters.is_empty() {
+            "anull".to_string()
+        } else {
+            filters.join(",")
+        };
+        graph.push_str(&format!("[{}:a]{}[a{}];", i, filters, i));
+    }
+    match join {
+        // [a0][a1]acrossfade[x1];[x1][a2]acrossfade[x2];...;[xN-2][aN-1]acrossfade,output_filters[out]
+        ClipJoin::Crossfade(crossfade) if clips.len() > 1 => {
+            for i in 1..clips.len() {
+                if i == 1 {
+                    graph.push_str("[a0]");
+                } else {
+                    graph.push_str(&format!("[x{}]", i - 1));
+                }
+                graph.push_str(&format!("[a{}]{}", i, crossfade.filter()));
+                if i < clips.len() - 1 {
+                    graph.push_str(&format!("[x{}];", i));
+                }
+            }
+        }
+        // sine[t0];sine[t1];...;[a0][t0][a1][t1]...[aN-1]concat=n=2N-1:v=0:a=1
+        // The concat filter converts the mono tone to the clips' format as it negotiates
+        ClipJoin::MarkerTone(tone) => {
+            for i in 0..clips.len() - 1 {
+                graph.push_str(&format!("{}[t{}];", tone.filter(), i));
+            }
+            for i in 0..clips.len() {
+                graph.push_str(&format!("[a{}]", i));
+                if i < clips.len() - 1 {
+                    graph.push_str(&format!("[t{}]", i));
+                }
+            }
+            graph.push_str(&format!("concat=n={}:v=0:a=1", clips.len() * 2 - 1));
+        }
+        _ => {
+            for i in 0..clips.len() {
+                graph.push_str(&format!("[a{}]", i));
+            }
+            graph.push_str(&format!("concat=n={}:v=0:a=1", clips.len()));
+        }
+    }
+    for filter in output_filters {
+        graph.push(',');
+        graph.push_str(filter);
+    }
+    graph.push_str("[out]");
+
+    args.extend_from_slice(&[
+        "-filter_complex".to_string(),
+        graph,
+        "-map".to_string(),
+        "[out]".to_string(),
+    ]);
+    args.extend_from_slice(encoder_args);
+    args.push(output_path.to_string());
+
+    args
+}
+
+/// Default cap on inputs to a single filter_complex invocation. Every clip adds an
+/// `-i <path>` pair plus a graph segment to the command line, and Windows limits a
+/// command line to 32,767 characters; 200 typical archive paths stay well below that.
+const DEFAULT_MAX_FILTER_INPUTS: usize = 200;
+
+/// Per-clip processing on the filter path
+struct ClipChain<'a> {
+    filters: &'a [String],
+    max_length: Option<f64>, // Clips longer than this many seconds are cut off there
+    pan: bool,               // Place every clip at its stereo position, after the filters
+    remix: Option<ChannelRemix<'a>>, // Applied first, ahead of the filters
+    boundary_fade: Option<f64>, // Seconds faded in and out at each clip's edges, last of all
+    edge_trims: Option<&'a HashMap<String, EdgeTrim>>, // Dead air skipped at clip edges, by path
+}
+
+impl ClipChain<'_> {
+    /// The filters one clip goes through, in order: remix, the configured chain, pan, fades
+    fn clip_filters(&self, clip: &AudioClip) -> Vec<String> {
+        let mut filters = Vec::new();
+        if let Some(remix) = self
+            .remix
+            .as_ref()
+            .filter(|remix| remix.paths.contains(&clip.path))
+        {
+            filters.push(format!(
+                "aformat=channel_layouts={}",
+                channel_layout_name(remix.channels)
+            ));
+        }
+        filters.extend_from_slice(self.filters);
+        if self.pan {
+            filters.push(pan_filter(clip.pan.unwrap_or(0.0)));
+        }
+        if let Some(fade) = self.boundary_fade {
+            let length = self
+                .max_length
+                .map_or(clip.duration, |max| clip.duration.min(max));
+            filters.extend(boundary_fade_filters(fade, length));
+        }
+        filters
+    }
+}
+
+/// Longest boundary fade accepted; anything audible is a job for crossfade instead
+const MAX_BOUNDARY_FADE_MS: u32 = 100;
+
+/// boundary_fade_ms in seconds, None when off
+fn boundary_fade_seconds(audio_config: &AudioConfig) -> Result<Option<f64>, String> {
+    match audio_config.boundary_fade_ms {
+        Some(ms) if ms > MAX_BOUNDARY_FADE_MS => Err(format!(
+            "boundary_fade_ms can be at most {}; use a crossfade for longer blends",
+            MAX_BOUNDARY_FADE_MS
+        )),
+        Some(ms) if ms > 0 => Ok(Some(ms as f64 / 1000.0)),
+        _ => Ok(None),
+    }
+}
+
+/// Fade a clip's first and last `fade` seconds. The fade-out is placed from the clip's
+/// probed length, and a clip too short for both fades gets half its length each.
+fn boundary_fade_filters(fade: f64, length: f64) -> [String; 2] {
+    let fade = fade.min(length / 2.0);
+    [
+        format!("afade=t=in:d={}", fade),
+        format!("afade=t=out:st={}:d={}", (length - fade).max(0.0), fade),
+    ]
+}
+
+/// Clips to convert to the output's channel count, keyed by path so a chunk of the
+/// clip list finds its own
+struct ChannelRemix<'a> {
+    channels: u32,
+    paths: &'a HashSet<String>,
+}
+
+/// FFmpeg channel layout name for a channel count
+fn channel_layout_name(channels: u32) -> String {
+    match channels {
+        1 => "mono".to_string(),
+        2 => "stereo".to_string(),
+        n => format!("{}c", n),
+    }
+}
+
+/// Resolve the channel target against the clips' probed counts, returning it and the
+/// paths of the clips that need converting to it. Majority ties go to the larger count,
+/// so stereo clips aren't folded down to mono on a split list.
+fn plan_channel_remix(
+    clips: &[AudioClip],
+    target: ChannelTarget,
+) -> Result<(u32, HashSet<String>), String> {
+    let mut counts = Vec::with_capacity(clips.len());
+    for clip in clips {
+        let channels = stream_format(&clip.path)?
+            .channels
+            .ok_or_else(|| format!("Couldn't read the channel count of '{}'", clip.name))?;
+        counts.push(channels);
+    }
+
+    let channels = match target {
+        ChannelTarget::Mono => 1,
+        ChannelTarget::Stereo => 2,
+        ChannelTarget::Majority => {
+            let mut tally: HashMap<u32, usize> = HashMap::new();
+            for &count in &counts {
+                *tally.entry(count).or_default() += 1;
+            }
+            tally
+                .into_iter()
+                .max_by_key(|&(count, clips)| (clips, count))
+                .map_or(2, |(count, _)| count)
+        }
+    };
+    let paths = clips
+        .iter()
+        .zip(&counts)
+        .filter(|(_, &count)| count != channels)
+        .map(|(clip, _)| clip.path.clone())
+        .collect();
+
+    Ok((channels, paths))
+}
+
+/// Fold a clip to mono and place it in the stereo field with a constant-power pan law,
+/// so a clip keeps the same loudness wherever it sits (each side is -3 dB at center)
+fn pan_filter(pan: f64) -> String {
+    let angle = (pan + 1.0) * std::f64::consts::FRAC_PI_4;
+    format!(
+        "aformat=channel_layouts=mono,pan=stereo|c0={:.4}*c0|c1={:.4}*c0",
+        angle.cos(),
+        angle.sin()
+    )
+}
+
+/// Check that every clip's pan is within -1.0 to 1.0
+fn validate_pans(clips: &[AudioClip]) -> Result<(), String> {
+    match clips
+        .iter()
+        .find(|clip| clip.pan.is_some_and(|pan| !(-1.0..=1.0).contains(&pan)))
+    {
+        Some(clip) => Err(format!(
+            "Clip '{}' has pan {}; expected -1.0 (left) to 1.0 (right)",
+            clip.name,
+            clip.pan.unwrap_or_default()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Fails for joins that can't survive a list being split into chunks: the demuxer join
+/// between chunks would butt them together with no fade or tone
+fn check_chunked_join(join: ClipJoin, clip_count: usize) -> Result<(), String> {
+    let joined_by = match join {
+        ClipJoin::Concat => return Ok(()),
+        ClipJoin::Crossfade(_) => "Crossfades",
+        ClipJoin::MarkerTone(_) => "Marker tones",
+    };
+    Err(format!(
+        "{} need all {} clips in one filter graph; raise max_filter_inputs",
+        joined_by, clip_count
+    ))
+}
+
+/// Run the per-clip filter path. Lists longer than `max_inputs` are filtered in chunks
+/// to lossless intermediates, which are then joined with the concat demuxer.
+fn run_filter_path(
+    clips: &[AudioClip],
+    chain: &ClipChain,
+    join: ClipJoin,
+    output_filters: &[String],
+    encoder_args: &[String],
+    output_path: String,
+    max_inputs: usize,
+) -> StitchResult {
+    if clips.len() <= max_inputs {
+        let args = filter_audio_args(
+            clips,
+            chain,
+            join,
+            output_filters,
+            encoder_args,
+            &output_path,
+        );
+        return run_ffmpeg(&args, output_path);
+    }
+    if let Err(e) = check_chunked_join(join, clips.len()) {
+        return stitch_error(e);
+    }
+
+    let temp_dir = std::env::temp_dir();
+    let run_id = temp_run_id();
+    let mut chunk_paths: Vec<String> = Vec::new();
+    let cleanup = |chunk_paths: &[String]| {
+        for path in chunk_paths {
+            let _ = fs::remove_file(path);
+        }
+    };
+
+    // Float PCM keeps the intermediates lossless and unclipped ahead of output filters
+    let intermediate_args = ["-c:a".to_string(), "pcm_f32le".to_string()];
+    for (i, chunk) in clips.chunks(max_inputs).enumerate() {
+        let chunk_path = temp_dir
+            .join(format!("ffmpeg_filter_chunk_{}_{}.wav", run_id, i))
+            .to_string_lossy()
+            .to_string();
+        let args = filter_audio_args(
+            chunk,
+            chain,
+            ClipJoin::Concat,
+            &[],
+            &intermediate_args,
+            &chunk_path,
+        );
+        chunk_paths.push(chunk_path.clone());
+
+        let chunk_result = run_ffmpeg(&args, chunk_path);
+        if !chunk_result.success {
+            cleanup(&chunk_paths);
+            return chunk_result;
+        }
+    }
+
+    let concat_file_path = unique_temp_path("ffmpeg_chunk_concat_list.txt");
+    let result = match write_concat_list(&chunk_paths, &concat_file_path) {
+        Ok(()) => {
+            let args = audio_args(
+                &concat_file_path,
+                output_filters,
+                encoder_args,
+                &output_path,
+            );
+            run_ffmpeg(&args, output_path)
+        }
+        Err(e) => stitch_error(e),
+    };
+
+    // Clean up temp files
+    let _ = fs::remove_file(&concat_file_path);
+    cleanup(&chunk_paths);
+
+    result
+}
+
+/// Scale a background image onto a `width`x`height` canvas for the fit mode. `pad_color`
+/// must already be escaped for the filtergraph.
+fn background_scaling_filter(
+    fit_mode: &ImageFitMode,
+    width: u32,
+    height: u32,
+    pad_color: &str,
+) -> String {
+    match fit_mode {
+        ImageFitMode::Fit => {
+            // Keep original size (no upscaling), only scale down if larger than the canvas
+            // Then center on the canvas in the pad color
+            format!(
+                "scale=iw*min(1\\,min({w}/iw\\,{h}/ih)):ih*min(1\\,min({w}/iw\\,{h}/ih)),pad={w}:{h}:(ow-iw)/2:(oh-ih)/2:{color}",
+                w = width,
+                h = height,
+                color = pad_color
+            )
+        }
+        ImageFitMode::Fill => {
+            // Scale and crop to fill the canvas
+            format!(
+                "scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h}",
+                w = width,
+                h = height
+            )
+        }
+    }
+}
+
+/// One image of a background slideshow and the times it is shown
+struct Slide {
+    image_path: String,
+    enable: String, // Timeline expression, true while the slide is up
+}
+
+/// Assign the images to time windows: `interval` seconds each in turn, or one per clip
+/// (cycling when there are more clips than images) when no interval is given. Images
+/// that never come up are left out.
+fn slideshow_slides(images: &[String], clips: &[AudioClip], interval: Option<f64>) -> Vec<Slide> {
+    let count = images.len();
+    if let Some(interval) = interval {
+        return images
+            .iter()
+            .enumerate()
+            .map(|(i, image_path)| Slide {
+                image_path: image_path.clone(),
+                enable: format!("eq(mod(floor(t/{}),{}),{})", interval, count, i),
+            })
+            .collect();
+    }
+
+    let placements = clip_placements(clips, 0.0);
+    let last = placements.len().saturating_sub(1);
+    let mut windows: Vec<Vec<String>> = vec![Vec::new(); count];
+    for placement in &placements {
+        // The last window stays open so the final slide covers any rounding at the end
+        windows[placement.index % count].push(if placement.index == last {
+            format!("gte(t,{})", placement.start)
+        } else {
+            format!("gte(t,{})*lt(t,{})", placement.start, placement.end)
+        });
+    }
+    images
+        .iter()
+        .zip(windows)
+        .filter(|(_, windows)| !windows.is_empty())
+        .map(|(image_path, windows)| Slide {
+            image_path: image_path.clone(),
+            enable: windows.join("+"),
+        })
+        .collect()
+}
+
+/// Check a slideshow's images exist and its settings fit with the rest of the video.
+/// Images needn't match in size, as each is scaled onto the frame by the fit mode.
+fn validate_slideshow(images: &[String], video_config: &VideoConfig) -> Result<(), String> {
+    if images.is_empty() {
+        return Err("background_slideshow needs at least one image".to_string());
+    }
+    if video_config.image_path.is_some() {
+        return Err("Use either image_path or background_slideshow, not both".to_string());
+    }
+    if let Some(image) = images.iter().find(|image| !Path::new(image).exists()) {
+        return Err(format!("Slideshow image not found: {}", image));
+    }
+    if let Some(interval) = video_config.slideshow_interval {
+        if !(interval > 0.0 && interval.is_finite()) {
+            return Err("slideshow_interval must be greater than zero".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// What a video draws over its background: slideshow images, then text overlays
+struct VideoLayers<'a> {
+    slides: &'a [Slide],
+    overlays: &'a [String],
+}
+
+/// Metadata the MP4 muxer is asked to carry beyond the audio encoder's tags
+#[derive(Default)]
+struct Mp4Metadata<'a> {
+    chapters_path: Option<&'a Path>, // WebVTT cues muxed as a mov_text chapter track
+    /// Write tags as free-form keys (`use_metadata_tags`), which MP4_LOCATION_KEY needs
+    metadata_keys: bool,
+}
+
+/// Build the FFmpeg arguments that render a concat list to MP4 over a background
+fn video_args(
+    concat_file_path: &Path,
+    metadata: &Mp4Metadata,
+    layers: &VideoLayers,
+    output_path: &str,
+    audio_encoder_args: &[String],
+    keyframe_interval: u32,
+    video_config: &VideoConfig,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec!["-y".to_string()]; // Overwrite output
+    let pad_color = escape_filter_value(video_config.pad_color.as_deref().unwrap_or("black"));
+    let (width, height) = video_frame_size(video_config);
+    let scaling = background_scaling_filter(&video_config.fit_mode, width, height, &pad_color);
+
+    // Rotation is applied explicitly below, so FFmpeg's own autorotate mustn't stack on it
+    let orientation = video_config
+        .image_path
+        .as_deref()
+        .and_then(image_orientation_filter);
+
+    if let Some(image_path) = &video_config.image_path {
+        // With image: loop the image for video stream, at the rate the GOP is sized for
+        // rather than the image demuxer's default 25fps
+        args.extend_from_slice(&[
+            "-framerate".to_string(),
+            video_frame_rate(video_config).to_string(),
+            "-noautorotate".to_string(),
+            "-loop".to_string(),
+            "1".to_string(),
+            "-i".to_string(),
+            image_path.clone(),
+        ]);
+    } else {
+        // Without image: generate a plain background in the pad color
+        args.extend_from_slice(&[
+            "-f".to_string(),
+            "lavfi".to_string(),
+            "-i".to_string(),
+            format!(
+                "color={}:s={}x{}:r={}",
+                pad_color,
+                width,
+                height,
+                video_frame_rate(video_config)
+            ),
+        ]);
+    }
+
+    // Add audio input
+    args.extend_from_slice(&[
+        "-f".to_string(),
+        "concat".to_string(),
+        "-safe".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        concat_file_path.to_string_lossy().to_string(),
+    ]);
+
+    // A watermark or slideshow needs more video inputs, so the video comes out of a
+    // filter graph
+    let complex = video_config.watermark.is_some() || !layers.slides.is_empty();
+    let video_map = if complex { "[v]" } else { "0:v" };
+
+    // Chapter cues ride along as a text subtitle track
+    if let Some(chapters_path) = metadata.chapters_path {
+        args.extend_from_slice(&[
+            "-i".to_string(),
+            chapters_path.to_string_lossy().to_string(),
+            "-map".to_string(),
+            video_map.to_string(),
+            "-map".to_string(),
+            "1:a".to_string(),
+            "-map".to_string(),
+            "2:s".to_string(),
+            "-c:s".to_string(),
+            "mov_text".to_string(),
+            "-metadata:s:s:0".to_string(),
+            "title=Chapters".to_string(),
+        ]);
+    } else if complex {
+        args.extend_from_slice(&[
+            "-map".to_string(),
+            video_map.to_string(),
+            "-map".to_string(),
+            "1:a".to_string(),
+        ]);
+    }
+
+    // The logo follows; a single frame is enough, as overlay repeats it
+    let mut next_input = if metadata.chapters_path.is_some() {
+        3
+    } else {
+        2
+    };
+    let logo_input = next_input;
+    if let Some(watermark) = &video_config.watermark {
+        args.extend_from_slice(&["-i".to_string(), watermark.image_path.clone()]);
+        next_input += 1;
+    }
+
+    // Slides come last, each looped at the output rate so overlay can switch between them
+    let first_slide_input = next_input;
+    for slide in layers.slides {
+        args.extend_from_slice(&[
+            "-framerate".to_string(),
+            video_frame_rate(video_config).to_string(),
+            "-noautorotate".to_string(),
+            "-loop".to_string(),
+            "1".to_string(),
+            "-i".to_string(),
+            slide.image_path.clone(),
+        ]);
+    }
+
+    // Scale only if we have an image (the plain background is already frame-sized), using
+    // yuv444p for better color preservation with graphics
+    let mut background = Vec::new();
+    if video_config.image_path.is_some() {
+        background.extend(orientation.map(str::to_string));
+        background.push(scaling.clone());
+    }
+    let mut finish = layers.overlays.to_vec();
+    if video_config.image_path.is_some() || !layers.slides.is_empty() {
+        finish.push("format=yuv444p".to_string());
+    } else {
+        finish.push("format=yuv420p".to_string());
+    }
+    if complex {
+        if background.is_empty() {
+            background.push("null".to_string());
+        }
+        let mut graph = format!("[0:v]{}[bg0];", background.join(","));
+        let mut base = "bg0".to_string();
+        // Each slide is scaled onto its own full canvas and shown only in its windows
+        for (i, slide) in layers.slides.iter().enumerate() {
+            let mut filters: Vec<String> = image_orientation_filter(&slide.image_path)
+                .map(str::to_string)
+                .into_iter()
+                .collect();
+            filters.push(scaling.clone());
+            graph.push_str(&format!(
+                "[{}:v]{}[slide{}];[{}][slide{}]overlay=format=auto:enable={}[bg{}];",
+                first_slide_input + i,
+                filters.join(","),
+                i,
+                base,
+                i,
+                escape_filter_value(&slide.enable),
+                i + 1
+            ));
+            base = format!("bg{}", i + 1);
+        }
+        // The logo goes under any text overlays so it never hides them
+        if let Some(watermark) = &video_config.watermark {
+            let (x, y) = watermark.position.xy(("W", "H"), ("w", "h"));
+            graph.push_str(&format!(
+                "[{}:v]{}[logo];[{}][logo]overlay=x={}:y={}:format=auto[marked];",
+                logo_input,
+                watermark.logo_filter(width),
+                base,
+                x,
+                y
+            ));
+            base = "marked".to_string();
+        }
+        graph.push_str(&format!("[{}]{}[v]", base, finish.join(",")));
+        args.extend_from_slice(&["-filter_complex".to_string(), graph]);
+    } else {
+        background.extend(finish);
+        args.extend_from_slice(&["-vf".to_string(), background.join(",")]);
+    }
+
+    // Video and audio encoding settings
+    // Using CRF 12 for very high quality unless told otherwise, slow preset for better
+    // compression
+    args.extend_from_slice(&["-c:v".to_string(), "libx264".to_string()]);
+    match &video_config.rate_control {
+        Some(rate_control) => args.extend(rate_control.x264_args()),
+        None => args.extend_from_slice(&["-crf".to_string(), "12".to_string()]),
+    }
+    args.extend_from_slice(&[
+        "-preset".to_string(),
+        "slow".to_string(),
+        "-tune".to_string(),
+        "stillimage".to_string(),
+        "-g".to_string(),
+        keyframe_interval.to_string(),
+        "-keyint_min".to_string(),
+        keyframe_interval.to_string(),
+        // Pinned whatever the inputs run at, as the GOP and frame-based progress both
+        // count frames at this rate
+        "-r".to_string(),
+        video_frame_rate(video_config).to_string(),
+    ]);
+    // Overlay changes (counter, watermark) mustn't trigger scene-cut keyframes either
+    if video_config.minimal_motion {
+        args.extend_from_slice(&["-sc_threshold".to_string(), "0".to_string()]);
+    }
+    args.extend_from_slice(audio_encoder_args);
+    if let Some(threads) = video_config.threads {
+        args.extend_from_slice(&["-threads".to_string(), threads.to_string()]);
+    }
+    let movflags = if metadata.metadata_keys {
+        "+faststart+use_metadata_tags"
+    } else {
+        "+faststart"
+    };
+    args.extend_from_slice(&[
+        "-shortest".to_string(),
+        "-movflags".to_string(),
+        movflags.to_string(),
+        output_path.to_string(),
+    ]);
+
+    args
+}
+
+/// Clockwise rotation an image needs to display upright. Phone photos record this as EXIF
+/// Orientation, which FFmpeg exposes as a frame tag; rotated video frames carry a display
+/// matrix or the older `rotate` tag instead.
+fn probe_image_orientation(path: &str) -> Result<ImageOrientation, String> {
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-read_intervals",
+            "%+#1",
+            "-show_entries",
+            "stream_side_data=rotation:stream_tags=rotate:frame_tags=Orientation",
+            "-of",
+            "default=noprint_wrappers=1",
+            path,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFprobe error: {}", error));
+    }
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<i32>() else {
+            continue;
+        };
+        let orientation = match key {
+            "TAG:Orientation" => ImageOrientation::from_exif(value),
+            // The display matrix rotates counter-clockwise, the legacy tag clockwise
+            "rotation" => ImageOrientation::from_clockwise_degrees(-value),
+            "TAG:rotate" => ImageOrientation::from_clockwise_degrees(value),
+            _ => continue,
+        };
+        return Ok(orientation);
+    }
+
+    Ok(ImageOrientation::Upright)
+}
+
+/// Filter that turns an image upright, or None when it already is (or can't be probed)
+fn image_orientation_filter(path: &str) -> Option<&'static str> {
+    probe_image_orientation(path).ok()?.filter()
+}
+
+/// Keyframe spacing used when none is configured, in seconds
+const DEFAULT_KEYFRAME_INTERVAL_SECONDS: f64 = 2.0;
+
+/// GOP length in frames. x264's default of 250 frames is over four minutes at 1fps, so
+/// players can only seek to a few points; shorter GOPs seek smoothly, and on a mostly
+/// static picture each extra keyframe costs little.
+fn keyframe_interval_frames(video_config: &VideoConfig) -> Result<u32, String> {
+    let fps = video_frame_rate(video_config);
+    let seconds = video_config
+        .keyframe_interval
+        .unwrap_or(DEFAULT_KEYFRAME_INTERVAL_SECONDS);
+    let frames = (seconds * fps as f64).round();
+    if !frames.is_finite() || frames < 1.0 {
+        return Err(format!(
+            "Keyframe interval must be at least one frame ({:.3}s at {}fps)",
+            1.0 / fps as f64,
+            fps
+        ));
+    }
+    Ok(frames as u32)
+}
+
+/// Frame size: the orientation's standard resolution, with width/height overriding
+/// either side
+fn video_frame_size(video_config: &VideoConfig) -> (u32, u32) {
+    let (width, height) = video_config.orientation.size();
+    (
+        video_config.width.unwrap_or(width),
+        video_config.height.unwrap_or(height),
+    )
+}
+
+/// Frame rate of the rendered video. A static background only needs 1fps, but
+/// moving overlays need enough frames to animate smoothly.
+fn video_frame_rate(video_config: &VideoConfig) -> u32 {
+    if video_config.scrolling_log {
+        25
+    } else {
+        1
+    }
+}
+
+/// Escape a value for use as a filter option inside a filtergraph. Values pass through
+/// two parsers (the filtergraph, then the filter's options), so both levels are escaped.
+fn escape_filter_value(value: &str) -> String {
+    let escape = |text: &str, special: &[char]| {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if c == '\\' || special.contains(&c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    };
+
+    let option_level = escape(value, &['\'', ':']);
+    escape(&option_level, &['\'', '[', ']', ',', ';'])
+}
+
+/// Most clip names listed in the scrolling log before it's summarized
+const MAX_SCROLLING_LOG_NAMES: usize = 200;
+
+/// Longest clip name shown in the scrolling log; drawtext doesn't wrap lines
+const MAX_SCROLLING_LOG_NAME_CHARS: usize = 60;
+
+/// Write the scrolling log text (one clip name per line) for drawtext's textfile option
+fn write_scrolling_log(clips: &[AudioClip], path: &Path) -> Result<(), String> {
+    let mut lines: Vec<String> = clips
+        .iter()
+        .take(MAX_SCROLLING_LOG_NAMES)
+        .map(|clip| {
+            if clip.name.chars().count() > MAX_SCROLLING_LOG_NAME_CHARS {
+                let truncated: String = clip
+                    .name
+                    .chars()
+                    .take(MAX_SCROLLING_LOG_NAME_CHARS - 3)
+                    .collect();
+                format!("{}...", truncated)
+            } else {
+                clip.name.clone()
+            }
+        })
+        .collect();
+    if clips.len() > MAX_SCROLLING_LOG_NAMES {
+        lines.push(format!(
+            "...and {} more",
+            clips.len() - MAX_SCROLLING_LOG_NAMES
+        ));
+    }
+
+    fs::write(path, lines.join("\n")).map_err(|e| format!("Failed to write scrolling log: {}", e))
+}
+
+/// drawtext filter that scrolls the log from below the frame to above it. Without an
+/// explicit speed the text takes the whole video to pass, so the last name exits at the end.
+fn scrolling_log_filter(text_path: &Path, total_duration: f64, speed: Option<f64>) -> String {
+    let y = match speed {
+        Some(speed) => format!("h-t*{}", speed),
+        None => format!("h-(h+th)*t/{}", total_duration.max(1.0)),
+    };
+
+    format!(
+        "drawtext=textfile={}:fontcolor=white:fontsize=36:line_spacing=12:box=1:boxcolor=black@0.4:boxborderw=10:x=(w-tw)/2:y={}",
+        escape_filter_value(&text_path.to_string_lossy()),
+        escape_filter_value(&y)
+    )
+}
+
+const DEFAULT_COUNTER_FONT_SIZE: u32 = 32;
+
+/// One drawtext filter per clip showing "index / total" while that clip plays. Each window
+/// runs from the clip's start up to (not including) the next clip's, so they tile the
+/// timeline with no gap or overlap; the last stays on through any trailing frames.
+fn clip_counter_filters(clips: &[AudioClip], position: Corner, font_size: u32) -> Vec<String> {
+    let (x, y) = position.xy(("w", "h"), ("tw", "th"));
+    let placements = clip_placements(clips, 0.0);
+    let last = placements.len().saturating_sub(1);
+
+    placements
+        .iter()
+        .map(|placement| {
+            let enable = if placement.index == last {
+                format!("gte(t,{})", placement.start)
+            } else {
+                format!("gte(t,{})*lt(t,{})", placement.start, placement.end)
+            };
+            format!(
+                "drawtext=text={}:fontcolor=white:fontsize={}:box=1:boxcolor=black@0.5:boxborderw=8:x={}:y={}:enable={}",
+                escape_filter_value(&format!("{} / {}", placement.index + 1, clips.len())),
+                font_size,
+                x,
+                y,
+                escape_filter_value(&enable)
+            )
+        })
+        .collect()
+}
+
+/// Format milliseconds as a WebVTT timestamp (HH:MM:SS.mmm)
+fn format_vtt_timestamp(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000
+    )
+}
+
+/// Escape clip names for use as WebVTT cue text
+fn escape_vtt_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace("-->", "--&gt;")
+}
+
+/// Convert seconds to whole milliseconds, treating negative values as zero
+fn seconds_to_ms(seconds: f64) -> u64 {
+    (seconds.max(0.0) * 1000.0).round() as u64
+}
+
+/// Lay the clips out end to end with `gap_seconds` of silence between them.
+/// Offsets are accumulated in whole milliseconds so later clips never drift.
+fn clip_placements(clips: &[AudioClip], gap_seconds: f64) -> Vec<ClipPlacement> {
+    let gap_ms = seconds_to_ms(gap_seconds);
+    let mut placements = Vec::with_capacity(clips.len());
+    let mut start_ms: u64 = 0;
+
+    for (index, clip) in clips.iter().enumerate() {
+        let end_ms = start_ms + seconds_to_ms(clip.duration);
+        placements.push(ClipPlacement {
+            index,
+            start: start_ms as f64 / 1000.0,
+            end: end_ms as f64 / 1000.0,
+            start_ms,
+            end_ms,
+        });
+        start_ms = end_ms + gap_ms;
+    }
+
+    placements
+}
+
+/// Lay out clips `durations` long joined as the render joins them, so crossfades pull
+/// each clip back over the end of the one before
+fn joined_placements(durations: &[f64], join: ClipJoin) -> Vec<ClipPlacement> {
+    clip_start_times(durations, join)
+        .into_iter()
+        .zip(durations)
+        .enumerate()
+        .map(|(index, (start, duration))| {
+            let start_ms = seconds_to_ms(start);
+            let end_ms = seconds_to_ms(start + duration);
+            ClipPlacement {
+                index,
+                start: start_ms as f64 / 1000.0,
+                end: end_ms as f64 / 1000.0,
+                start_ms,
+                end_ms,
+            }
+        })
+        .collect()
+}
+
+/// Write a WebVTT file with one cue per clip spanning its place in the output
+fn write_vtt_chapters(clips: &[AudioClip], path: &Path) -> Result<(), String> {
+    let mut vtt = String::from("WEBVTT\n");
+
+    for (clip, placement) in clips.iter().zip(clip_placements(clips, 0.0)) {
+        vtt.push_str(&format!(
+            "\n{}\n{} --> {}\n{}\n",
+            placement.index + 1,
+            format_vtt_timestamp(placement.start_ms),
+            format_vtt_timestamp(placement.end_ms),
+            escape_vtt_text(&clip.name)
+        ));
+    }
+
+    fs::write(path, vtt).map_err(|e| format!("Failed to write chapter file: {}", e))
+}
+
+/// Format milliseconds as an SRT timestamp (HH:MM:SS,mmm)
+fn format_srt_timestamp(ms: u64) -> String {
+    format_vtt_timestamp(ms).replacen('.', ",", 1)
+}
+
+/// Make a clip name safe as SRT cue text: a blank line would end the cue early and an
+/// arrow could be read as a timing line, so names are kept to one line without arrows
+fn escape_srt_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("-->", "->")
+}
+
+/// Write an SRT with one cue per clip, named after it, spanning where the clip plays with
+/// `gap_seconds` between clips. Returns the path written, ready for editing and burn-in.
+#[tauri::command]
+fn generate_clip_srt(
+    clips: Vec<AudioClip>,
+    gap_seconds: f64,
+    output_path: String,
+) -> Result<String, String> {
+    if clips.is_empty() {
+        return Err("No clips provided".to_string());
+    }
+    if gap_seconds < 0.0 {
+        return Err("Gap can't be negative".to_string());
+    }
+    let clips = order_clips(clips)?;
+
+    let placements = clip_placements(&clips, gap_seconds);
+    let mut srt = String::new();
+    for (i, (clip, placement)) in clips.iter().zip(&placements).enumerate() {
+        // Touching cues would both show on the boundary frame in some players
+        let end_ms = match placements.get(i + 1) {
+            Some(next) => placement.end_ms.min(next.start_ms.saturating_sub(1)),
+            None => placement.end_ms,
+        };
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_srt_timestamp(placement.start_ms),
+            format_srt_timestamp(end_ms.max(placement.start_ms)),
+            match escape_srt_text(&clip.name) {
+                // An empty text line would read as the end of the cue
+                name if name.is_empty() => format!("Clip {}", i + 1),
+                name => name,
+            }
+        ));
+    }
+
+    fs::write(&output_path, srt).map_err(|e| format!("Failed to write SRT: {}", e))?;
+    Ok(output_path)
+}
+
+/// Timecode rate export_edl uses unless told otherwise
+const DEFAULT_EDL_FRAME_RATE: u32 = 30;
+
+/// CMX3600 numbers events with three digits
+const MAX_EDL_EVENTS: usize = 999;
+
+/// A frame count as non-drop-frame `HH:MM:SS:FF` timecode
+fn format_timecode(frames: u64, frame_rate: u32) -> String {
+    let rate = u64::from(frame_rate);
+    let seconds = frames / rate;
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60,
+        frames % rate
+    )
+}
+
+/// Where one clip's event sits in an EDL, in frames
+#[derive(Debug, PartialEq)]
+struct EdlEvent {
+    length: u64, // Both the source and the record length
+    record_in: u64,
+    record_out: u64,
+}
+
+/// Lay clips `durations` long end to end with `gap_seconds` between them, counting in
+/// whole frames at `frame_rate` so rounding never builds up from one event to the next
+fn edl_events(durations: &[f64], gap_seconds: f64, frame_rate: u32) -> Vec<EdlEvent> {
+    let to_frames = |seconds: f64| (seconds.max(0.0) * frame_rate as f64).round() as u64;
+    let gap_frames = to_frames(gap_seconds);
+    let mut record_in = 0;
+    durations
+        .iter()
+        .map(|&duration| {
+            let length = to_frames(duration);
+            let event = EdlEvent {
+                length,
+                record_in,
+                record_out: record_in + length,
+            };
+            record_in = event.record_out + gap_frames;
+            event
+        })
+        .collect()
+}
+
+/// Write a CMX3600 EDL with one audio event per clip, laid out with `gap_seconds` between
+/// clips, for rebuilding the compilation in an editor. Timecode is non-drop-frame at
+/// `frame_rate` (default 30). Positions are counted in whole frames, so every event's
+/// record length matches its source length and no two events overlap. Editors that
+/// break the "AX" reel out to a file read the path from the SOURCE FILE comment.
+/// Returns the path written.
+#[tauri::command]
+fn export_edl(
+    clips: Vec<AudioClip>,
+    gap_seconds: f64,
+    output_path: String,
+    frame_rate: Option<u32>,
+) -> Result<String, String> {
+    if clips.is_empty() {
+        return Err("No clips provided".to_string());
+    }
+    if clips.len() > MAX_EDL_EVENTS {
+        return Err(format!(
+            "An EDL holds at most {} events; got {} clips",
+            MAX_EDL_EVENTS,
+            clips.len()
+        ));
+    }
+    if gap_seconds < 0.0 {
+        return Err("Gap can't be negative".to_string());
+    }
+    let frame_rate = frame_rate.unwrap_or(DEFAULT_EDL_FRAME_RATE);
+    if !(1..=120).contains(&frame_rate) {
+        return Err(format!(
+            "Invalid EDL frame rate {} (expected 1-120)",
+            frame_rate
+        ));
+    }
+    let clips = order_clips(clips)?;
+
+    let title = Path::new(&output_path)
+        .file_stem()
+        .map(|stem| escape_srt_text(&stem.to_string_lossy()))
+        .unwrap_or_default();
+    let mut edl = format!("TITLE: {}\nFCM: NON-DROP FRAME\n", title);
+    let durations: Vec<f64> = clips.iter().map(|clip| clip.duration).collect();
+    let events = edl_events(&durations, gap_seconds, frame_rate);
+    for (i, (clip, event)) in clips.iter().zip(&events).enumerate() {
+        edl.push_str(&format!(
+            "\n{:03}  AX       AA     C        {} {} {} {}\n",
+            i + 1,
+            format_timecode(0, frame_rate),
+            format_timecode(event.length, frame_rate),
+            format_timecode(event.record_in, frame_rate),
+            format_timecode(event.record_out, frame_rate)
+        ));
+        // Comments are line-based, so names and paths must stay on one line
+        edl.push_str(&format!(
+            "* FROM CLIP NAME: {}\n* SOURCE FILE: {}\n",
+            escape_srt_text(&clip.name),
+            clip.path.replace(['\r', '\n'], " ")
+        ));
+    }
+
+    fs::write(&output_path, edl).map_err(|e| format!("Failed to write EDL: {}", e))?;
+    Ok(output_path)
+}
+
+/// Version of the timeline JSON schema, raised whenever a field changes meaning or goes
+/// away. Players should ignore fields they don't know, as new ones may be added.
+const TIMELINE_SCHEMA_VERSION: u32 = 1;
+
+/// Timeline JSON for web players: where each clip sits in a stitched output
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Timeline {
+    pub version: u32,  // TIMELINE_SCHEMA_VERSION
+    pub media: String, // File name of the output the offsets refer to, without its folder
+    pub duration_ms: u64,
+    pub clips: Vec<TimelineEntry>,
+}
+
+/// One clip of a timeline. Offsets are whole milliseconds from the start of the output,
+/// the same values the chapters and cues use; `end_ms` is exclusive.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub index: usize,
+    pub id: String,
+    pub name: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// From the clip's metadata, else its recorder filename
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub talkgroup: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>, // The clip's metadata, passed through as-is
+}
+
+/// Path of the timeline sidecar for an output file (`out.mp3` -> `out.timeline.json`)
+fn timeline_path_for(output_path: &str) -> PathBuf {
+    Path::new(output_path).with_extension("timeline.json")
+}
+
+/// Write a Timeline for the output at `output_path`, with the clips laid out `gap_seconds`
+/// apart as compute_timeline does, to a `.timeline.json` sidecar next to it. Returns the
+/// path written.
+#[tauri::command]
+fn export_timeline_json(
+    clips: Vec<AudioClip>,
+    gap_seconds: f64,
+    output_path: String,
+) -> Result<String, String> {
+    if clips.is_empty() {
+        return Err("No clips provided".to_string());
+    }
+    if gap_seconds < 0.0 {
+        return Err("Gap can't be negative".to_string());
+    }
+    let clips = order_clips(clips)?;
+    let media = Path::new(&output_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("'{}' isn't a file path", output_path))?;
+
+    let placements = clip_placements(&clips, gap_seconds);
+    let duration_ms = placements.last().map_or(0, |placement| placement.end_ms);
+    let entries = clips
+        .into_iter()
+        .zip(placements)
+        .map(|(clip, placement)| {
+            let talkgroup = clip
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata["talkgroup"].as_u64())
+                .and_then(|talkgroup| u32::try_from(talkgroup).ok())
+                .or_else(|| {
+                    match_recording_name(&clip.path, &[])
+                        .ok()
+                        .flatten()
+                        .and_then(|meta| meta.talkgroup)
+                });
+            TimelineEntry {
+                index: placement.index,
+                id: clip.id,
+                name: clip.name,
+                start_ms: placement.start_ms,
+                end_ms: placement.end_ms,
+                talkgroup,
+                metadata: clip.metadata,
+            }
+        })
+        .collect();
+
+    let timeline = Timeline {
+        version: TIMELINE_SCHEMA_VERSION,
+        media,
+        duration_ms,
+        clips: entries,
+    };
+    let path = timeline_path_for(&output_path);
+    let json = serde_json::to_string_pretty(&timeline).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write timeline: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Caption appearance for burn_subtitles, passed to libass as `force_style`. Unset fields
+/// keep libass's defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubtitleStyle {
+    pub font_name: Option<String>,
+    pub font_size: Option<u32>,
+    pub outline: Option<f64>, // Outline width in pixels
+}
+
+impl SubtitleStyle {
+    /// The `force_style` value, e.g. "FontName=Arial,FontSize=24,Outline=2"
+    fn force_style(&self) -> Result<Option<String>, String> {
+        let mut fields = Vec::new();
+        if let Some(font_name) = &self.font_name {
+            // force_style is itself a comma-separated list of Key=Value pairs
+            if font_name.is_empty() || font_name.contains([',', '=']) {
+                return Err(format!("Invalid font name '{}'", font_name));
+            }
+            fields.push(format!("FontName={}", font_name));
+        }
+        if let Some(font_size) = self.font_size {
+            if font_size == 0 {
+                return Err("Font size must be greater than zero".to_string());
+            }
+            fields.push(format!("FontSize={}", font_size));
+        }
+        if let Some(outline) = self.outline {
+            if outline < 0.0 {
+                return Err("Outline width can't be negative".to_string());
+            }
+            fields.push(format!("Outline={}", outline));
+        }
+        Ok((!fields.is_empty()).then(|| fields.join(",")))
+    }
+}
+
+/// Path as the subtitles filter should see it. On Windows, backslashes become forward
+/// slashes, which libass accepts and which avoid a second layer of escaping.
+fn subtitles_filter_path(path: &str) -> String {
+    if cfg!(windows) {
+        path.replace('\\', "/")
+    } else {
+        path.to_string()
+    }
+}
+
+/// Hardcode an SRT's captions onto an existing video with the subtitles filter. The audio
+/// is copied; the video is re-encoded, since burned-in text has to be drawn into every frame.
+#[tauri::command]
+async fn burn_subtitles(
+    video_path: String,
+    srt_path: String,
+    output_path: String,
+    style: Option<SubtitleStyle>,
+) -> StitchResult {
+    if !Path::new(&video_path).exists() {
+        return stitch_error(format!("Video not found: {}", video_path));
+    }
+    // Check the captions here, since FFmpeg's own parse errors don't say which cue is bad
+    if let Err(e) = fs::read_to_string(&srt_path)
+        .map_err(|e| format!("Failed to read subtitles: {}", e))
+        .and_then(|contents| parse_srt(&contents))
+    {
+        return stitch_error(e);
+    }
+    let force_style = match style.unwrap_or_default().force_style() {
+        Ok(force_style) => force_style,
+        Err(e) => return stitch_error(e),
+    };
+
+    let mut filter = format!(
+        "subtitles=filename={}",
+        escape_filter_value(&subtitles_filter_path(&srt_path))
+    );
+    if let Some(force_style) = force_style {
+        filter.push_str(&format!(
+            ":force_style={}",
+            escape_filter_value(&force_style)
+        ));
+    }
+
+    let info = get_audio_info(video_path.clone());
+    let args = vec![
+        "-y".to_string(),
+        "-i".to_string(),
+        video_path,
+        "-vf".to_string(),
+        filter,
+        "-c:v".to_string(),
+        "libx264".to_string(),
+        "-crf".to_string(),
+        "18".to_string(),
+        "-preset".to_string(),
+        "medium".to_string(),
+        "-c:a".to_string(),
+        "copy".to_string(),
+        "-movflags".to_string(),
+        "+faststart".to_string(),
+        output_path.clone(),
+    ];
+    let progress = ProgressPlan {
+        output_path: output_path.clone(),
+        duration: info.duration,
+        fps: None,
+    };
+    run_ffmpeg_with_progress(&args, output_path, Some(progress))
+}
+
+/// One timed cue from an SRT file
+struct SubtitleCue {
+    start_ms: u64,
+    end_ms: u64,
+    text: String,
+}
+
+/// Parse an SRT timestamp (`00:01:02,345`) into milliseconds
+fn parse_srt_timestamp(timestamp: &str) -> Option<u64> {
+    let (clock, millis) = timestamp.trim().split_once([',', '.'])?;
+    let mut parts = clock.split(':').map(|part| part.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis.parse::<u64>().ok()?)
+}
+
+/// Parse the cues of an SRT file. Multi-line cue text is joined into one line.
+fn parse_srt(contents: &str) -> Result<Vec<SubtitleCue>, String> {
+    let contents = contents
+        .trim_start_matches('\u{feff}')
+        .replace("\r\n", "\n");
+    let mut cues = Vec::new();
+
+    for block in contents
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+    {
+        let mut lines = block.lines().map(str::trim);
+        // The counter line is optional in practice, so look for the timing line directly
+        let Some(timing) = lines.by_ref().find(|line| line.contains("-->")) else {
+            return Err(format!("SRT block has no timing line: {}", block.trim()));
+        };
+        let (start, end) = timing
+            .split_once("-->")
+            .and_then(|(start, end)| {
+                // Position hints may follow the end timestamp
+                let end = end.split_whitespace().next()?;
+                Some((parse_srt_timestamp(start)?, parse_srt_timestamp(end)?))
+            })
+            .ok_or_else(|| format!("Invalid SRT timing line: {}", timing))?;
+        if end < start {
+            return Err(format!("SRT cue ends before it starts: {}", timing));
+        }
+
+        cues.push(SubtitleCue {
+            start_ms: start,
+            end_ms: end,
+            text: lines.collect::<Vec<_>>().join(" "),
+        });
+    }
+
+    if cues.is_empty() {
+        return Err("SRT file contains no cues".to_string());
+    }
+    Ok(cues)
+}
+
+/// Escape a value for an FFMETADATA file
+fn escape_ffmetadata(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Chapters embedded in a file, in file order, with times in milliseconds
+fn probe_chapters(path: &str) -> Result<Vec<SubtitleCue>, String> {
+    let output = ffprobe_command()
+        .args(["-v", "error", "-show_chapters", "-of", "json", path])
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFprobe error: {}", error));
+    }
+
+    let probe: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse ffprobe output: {}", e))?;
+    let seconds = |value: &serde_json::Value| value.as_str().and_then(|v| v.parse::<f64>().ok());
+    let chapters = probe["chapters"].as_array().cloned().unwrap_or_default();
+    chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            match (
+                seconds(&chapter["start_time"]),
+                seconds(&chapter["end_time"]),
+            ) {
+                (Some(start), Some(end)) => Ok(SubtitleCue {
+                    start_ms: seconds_to_ms(start),
+                    end_ms: seconds_to_ms(end),
+                    text: chapter["tags"]["title"]
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("Chapter {}", i + 1)),
+                }),
+                _ => Err(format!(
+                    "Chapter {} of '{}' has no start or end time",
+                    i + 1,
+                    path
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Where each clip starts once joined, in seconds, given the clips' (capped) lengths
+fn clip_start_times(durations: &[f64], join: ClipJoin) -> Vec<f64> {
+    let step = match join {
+        ClipJoin::Concat => 0.0,
+        ClipJoin::Crossfade(crossfade) => -crossfade.duration_seconds,
+        ClipJoin::MarkerTone(tone) => tone.duration_seconds,
+    };
+    let mut start = 0.0;
+    durations
+        .iter()
+        .map(|duration| {
+            let clip_start = start;
+            start += duration + step;
+            clip_start
+        })
+        .collect()
+}
+
+/// An appended file's chapters followed by one per new clip, each ending where the next
+/// begins and the last at `total_seconds`. Fails unless the starts strictly increase, which
+/// catches existing chapters that run on past the point the new clips begin.
+fn append_chapters(
+    existing: Vec<SubtitleCue>,
+    clips: &[AudioClip],
+    starts: &[f64],
+    total_seconds: f64,
+) -> Result<Vec<SubtitleCue>, String> {
+    let mut cues = existing;
+    for (i, (clip, start)) in clips.iter().zip(starts).enumerate() {
+        let name = clip.name.trim();
+        cues.push(SubtitleCue {
+            start_ms: seconds_to_ms(*start),
+            end_ms: u64::MAX,
+            text: if name.is_empty() {
+                format!("Clip {}", i + 1)
+            } else {
+                name.to_string()
+            },
+        });
+    }
+
+    for i in 1..cues.len() {
+        if cues[i].start_ms <= cues[i - 1].start_ms {
+            return Err(format!(
+                "Chapter '{}' would start at {}ms, not after '{}' at {}ms",
+                cues[i].text,
+                cues[i].start_ms,
+                cues[i - 1].text,
+                cues[i - 1].start_ms
+            ));
+        }
+    }
+    let total_ms = seconds_to_ms(total_seconds);
+    for i in 0..cues.len() {
+        let next_start = cues.get(i + 1).map_or(total_ms, |next| next.start_ms);
+        cues[i].end_ms = cues[i].end_ms.min(next_start);
+    }
+
+    Ok(cues)
+}
+
+/// The file an append extends, as a clip to place ahead of the new ones
+fn append_base_clip(path: &str) -> Result<AudioClip, String> {
+    let info = get_audio_info(path.to_string());
+    if !info.valid {
+        return Err(format!(
+            "Can't append to '{}': {}",
+            path,
+            info.error.unwrap_or_default()
+        ));
+    }
+    Ok(AudioClip {
+        id: "append_to".to_string(),
+        path: path.to_string(),
+        name: Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        duration: info.duration,
+        size: info.size,
+        metadata: None,
+        order: None,
+        pan: None,
+    })
+}
+
+/// Whether two paths name the same file, following links and relative paths when it exists
+fn same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => Path::new(a) == Path::new(b),
+    }
+}
+
+/// Write cues as FFMETADATA chapters, fitted to an output `duration_ms` long. Cues that
+/// start past the end are dropped and ones that run past it are cut short, with a warning each.
+fn write_ffmetadata_chapters(
+    cues: &[SubtitleCue],
+    duration_ms: u64,
+    path: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    let mut metadata = String::from(";FFMETADATA1\n");
+    let (mut dropped, mut truncated) = (0, 0);
+
+    for cue in cues {
+        if cue.start_ms >= duration_ms {
+            dropped += 1;
+            continue;
+        }
+        if cue.end_ms > duration_ms {
+            truncated += 1;
+        }
+        metadata.push_str(&format!(
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            cue.start_ms,
+            cue.end_ms.min(duration_ms),
+            escape_ffmetadata(&cue.text)
+        ));
+    }
+
+    if dropped > 0 {
+        warnings.push(format!(
+            "Dropped {} transcript cue(s) starting after the end of the output",
+            dropped
+        ));
+    }
+    if truncated > 0 {
+        warnings.push(format!(
+            "Truncated {} transcript cue(s) running past the end of the output",
+            truncated
+        ));
+    }
+
+    fs::write(path, metadata).map_err(|e| format!("Failed to write chapter metadata: {}", e))
+}
+
+/// Remux a finished output with the chapters from an FFMETADATA file, leaving the streams
+/// untouched
+fn mux_ffmetadata_chapters(output_path: &str, metadata_path: &Path) -> Result<(), String> {
+    let remuxed_path = partial_output_path(output_path);
+    run_ffmpeg_task(&[
+        "-y".to_string(),
+        "-i".to_string(),
+        output_path.to_string(),
+        "-i".to_string(),
+        metadata_path.to_string_lossy().to_string(),
+        "-map".to_string(),
+        "0".to_string(),
+        "-map_chapters".to_string(),
+        "1".to_string(),
+        "-c".to_string(),
+        "copy".to_string(),
+        remuxed_path.clone(),
+    ])
+    .and_then(|_| {
+        fs::rename(&remuxed_path, output_path)
+            .map_err(|e| format!("Failed to replace output with chaptered copy: {}", e))
+    })
+    .inspect_err(|_| {
+        let _ = fs::remove_file(&remuxed_path);
+    })
+}
+
+/// How often a cancellable FFmpeg run checks whether it has been cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Read a child's pipe to the end on its own thread, so FFmpeg can't stall on a full
+/// pipe while its exit is being polled
+fn drain_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// App handle for emitting events from code that isn't a command, set once at startup
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+/// Expected length of a render, used to turn FFmpeg's `-progress` reports into a percentage
+struct ProgressPlan {
+    output_path: String,
+    duration: f64,    // Seconds of output expected
+    fps: Option<f64>, // Output frame rate, for renders with a video stream
+}
+
+/// Payload of the `stitch-output-chunk` event: the next piece of a render written to `pipe:`
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputChunk {
+    pub job_id: Option<String>, // Set for renders started by the batch queue
+    pub sequence: u64,
+    pub data: Vec<u8>,
+    pub done: bool, // Set on a final, empty chunk once FFmpeg closes its stdout
+}
+
+/// Most bytes carried by one `stitch-output-chunk` event
+const OUTPUT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Forward FFmpeg's stdout as `stitch-output-chunk` events while it renders. Returns
+/// nothing for the Output, since the bytes have already gone to the caller.
+fn stream_output(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    let job_id = jobs::current_job_id();
+    thread::spawn(move || {
+        let Some(mut pipe) = pipe else {
+            return Vec::new();
+        };
+        let emit = |sequence: u64, data: Vec<u8>, done: bool| {
+            if let Some(app) = APP_HANDLE.get() {
+                let _ = app.emit(
+                    "stitch-output-chunk",
+                    OutputChunk {
+                        job_id: job_id.clone(),
+                        sequence,
+                        data,
+                        done,
+                    },
+                );
+            }
+        };
+
+        let mut buffer = vec![0; OUTPUT_CHUNK_BYTES];
+        let mut sequence = 0;
+        loop {
+            match pipe.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    emit(sequence, buffer[..read].to_vec(), false);
+                    sequence += 1;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+        // Passes that wrote to a file leave stdout empty and shouldn't end the stream
+        if sequence > 0 {
+            emit(sequence, Vec::new(), true);
+        }
+        Vec::new()
+    })
+}
+
+/// Payload of the `stitch-progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct StitchProgress {
+    pub job_id: Option<String>, // Set for renders started by the batch queue
+    pub output_path: String,
+    pub percent: f64,
+}
+
+/// Read FFmpeg's `-progress` reports from its stdout and emit `stitch-progress` events.
+/// A video's frame count tracks real work more closely than out_time, which jumps ahead
+/// while the encoder is still buffering, so frames win when both are reported; the
+/// percentage never goes backwards when switching between them.
+fn report_progress(
+    pipe: Option<impl Read + Send + 'static>,
+    plan: ProgressPlan,
+) -> thread::JoinHandle<Vec<u8>> {
+    let job_id = jobs::current_job_id();
+    thread::spawn(move || {
+        let Some(pipe) = pipe else {
+            return Vec::new();
+        };
+        let expected_frames = plan.fps.map(|fps| plan.duration * fps);
+        let (mut frame, mut out_time) = (None, None);
+        let mut percent: f64 = 0.0;
+
+        for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "frame" => frame = value.trim().parse::<f64>().ok(),
+                "out_time_us" => out_time = value.trim().parse::<f64>().ok().map(|us| us / 1e6),
+                // Each report ends with a progress line
+                "progress" => {
+                    let by_frames = frame
+                        .zip(expected_frames)
+                        .filter(|&(frame, expected)| frame > 0.0 && expected > 0.0)
+                        .map(|(frame, expected)| frame / expected);
+                    let by_time = out_time
+                        .filter(|_| plan.duration > 0.0)
+                        .map(|time| time / plan.duration);
+                    let current = if value.trim() == "end" {
+                        Some(1.0)
+                    } else {
+                        by_frames.or(by_time)
+                    };
+                    if let Some(current) = current {
+                        percent = percent.max(current.clamp(0.0, 1.0) * 100.0);
+                    }
+
+                    if let Some(app) = APP_HANDLE.get() {
+                        let _ = app.emit(
+                            "stitch-progress",
+                            StitchProgress {
+                                job_id: job_id.clone(),
+                                output_path: plan.output_path.clone(),
+                                percent,
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Progress reports are all that's written to stdout
+        Vec::new()
+    })
+}
+
+thread_local! {
+    /// Whether FFmpeg runs started on this thread get reduced priority
+    static LOW_PRIORITY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Keeps FFmpeg runs on this thread at the requested priority until dropped
+struct PriorityGuard(bool);
+
+impl PriorityGuard {
+    fn set(low_priority: bool) -> Self {
+        PriorityGuard(LOW_PRIORITY.replace(low_priority))
+    }
+}
+
+impl Drop for PriorityGuard {
+    fn drop(&mut self) {
+        LOW_PRIORITY.set(self.0);
+    }
+}
+
+thread_local! {
+    /// Whether FFmpeg runs started on this thread stream their stdout back as events
+    static STREAM_STDOUT: Cell<bool> = const { Cell::new(false) };
+}
+
+thread_local! {
+    /// Log of the FFmpeg runs on this thread, while a LogCapture is collecting it
+    static CAPTURED_LOG: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Collects the stderr of successful FFmpeg runs on this thread, for statistics filters
+/// print there, without another decoding pass to read them back
+struct LogCapture;
+
+impl LogCapture {
+    fn start() -> Self {
+        CAPTURED_LOG.set(Some(String::new()));
+        LogCapture
+    }
+
+    /// Stop collecting, returning everything logged since start
+    fn finish(self) -> String {
+        CAPTURED_LOG.take().unwrap_or_default()
+    }
+}
+
+impl Drop for LogCapture {
+    fn drop(&mut self) {
+        CAPTURED_LOG.set(None);
+    }
+}
+
+/// Streams the stdout of FFmpeg runs on this thread until dropped
+struct StreamGuard(bool);
+
+impl StreamGuard {
+    fn set(stream: bool) -> Self {
+        StreamGuard(STREAM_STDOUT.replace(stream))
+    }
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        STREAM_STDOUT.set(self.0);
+    }
+}
+
+/// Niceness used for low-priority renders on Unix (0 is normal, 19 the lowest)
+#[cfg(unix)]
+const LOW_PRIORITY_NICENESS: &str = "10";
+
+/// Start building an FFmpeg command, at reduced priority when this thread asked for it.
+/// FFmpeg watches stdin for its interactive keys (`q` to stop, overwrite prompts), so it is
+/// cut off from stdin entirely; otherwise a child that inherited a terminal or an idle pipe
+/// can wait on it forever and the render never finishes.
+fn ffmpeg_command() -> Command {
+    let mut command = ffmpeg_program();
+    command.arg("-nostdin").stdin(Stdio::null());
+    command
+}
+
+/// ffprobe, cut off from stdin like ffmpeg_command; it has no `-nostdin` of its own
+fn ffprobe_command() -> Command {
+    let mut command = Command::new("ffprobe");
+    command.stdin(Stdio::null());
+    command
+}
+
+/// The FFmpeg process itself, before any arguments. Unix lowers priority through `nice`,
+/// since std can't set a child's priority there; Windows has a creation flag for it.
+fn ffmpeg_program() -> Command {
+    if !LOW_PRIORITY.get() {
+        return Command::new("ffmpeg");
+    }
+
+    #[cfg(unix)]
+    {
+        let mut command = Command::new("nice");
+        command.args(["-n", LOW_PRIORITY_NICENESS, "ffmpeg"]);
+        command
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+        let mut command = Command::new("ffmpeg");
+        command.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+        command
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        Command::new("ffmpeg")
+    }
+}
+
+/// Check a requested encoder thread count against the machine's parallelism
+fn validate_threads(threads: Option<u32>) -> Result<(), String> {
+    let Some(threads) = threads else {
+        return Ok(());
+    };
+    let available = thread::available_parallelism()
+        .map(|n| n.get() as u32)
+        .unwrap_or(1);
+    if threads == 0 || threads > available {
+        return Err(format!(
+            "Threads must be between 1 and {} (the available parallelism)",
+            available
+        ));
+    }
+    Ok(())
+}
+
+/// How long an overwrite prompt waits for an answer unless told otherwise
+const DEFAULT_OVERWRITE_TIMEOUT_SECONDS: u64 = 30;
+
+/// Ask the frontend before replacing an existing output. The render waits for an answer
+/// to its `overwrite-confirm` event, sent with respond_overwrite; no answer in time is a no.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OverwritePrompt {
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>, // Defaults to DEFAULT_OVERWRITE_TIMEOUT_SECONDS
+}
+
+/// Payload of the `overwrite-confirm` event
+#[derive(Debug, Clone, Serialize)]
+pub struct OverwriteConfirm {
+    /// Pass back to respond_overwrite. Queued renders use their job id; direct calls get
+    /// one of their own.
+    pub job_id: String,
+    pub output_path: String,
+    pub timeout_seconds: u64,
+}
+
+/// Managed state: renders waiting on an overwrite answer, by job id
+#[derive(Default)]
+pub struct OverwritePrompts(Mutex<HashMap<String, mpsc::Sender<bool>>>);
+
+impl OverwritePrompts {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, mpsc::Sender<bool>>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// How an overwrite prompt ended
+enum OverwriteAnswer {
+    Allowed,
+    Refused,
+    TimedOut,
+    Cancelled,
+}
+
+/// Wait for permission to replace output_path, if there's anything there. Runs before
+/// FFmpeg is spawned; returns the result to give in place of the render when it can't go
+/// ahead.
+fn confirm_overwrite(output_path: &str, prompt: &OverwritePrompt) -> Option<StitchResult> {
+    if !Path::new(output_path).exists() {
+        return None;
+    }
+    let timeout_seconds = prompt
+        .timeout_seconds
+        .unwrap_or(DEFAULT_OVERWRITE_TIMEOUT_SECONDS);
+    if timeout_seconds == 0 {
+        return Some(stitch_error(
+            "confirm_overwrite's timeout must be at least 1 second",
+        ));
+    }
+    let Some((app, prompts)) = APP_HANDLE.get().and_then(|app| {
+        app.try_state::<OverwritePrompts>()
+            .map(|prompts| (app, prompts))
+    }) else {
+        return Some(stitch_error(format!(
+            "{} already exists, and there's no frontend to confirm replacing it",
+            output_path
+        )));
+    };
+
+    let job_id = jobs::current_job_id().unwrap_or_else(jobs::next_job_id);
+    let (sender, receiver) = mpsc::channel();
+    prompts.lock().insert(job_id.clone(), sender);
+    let _ = app.emit(
+        "overwrite-confirm",
+        OverwriteConfirm {
+            job_id: job_id.clone(),
+            output_path: output_path.to_string(),
+            timeout_seconds,
+        },
+    );
+
+    // Woken regularly so cancelling a queued job doesn't wait out the timeout
+    let cancel = jobs::current_cancel_flag();
+    let deadline = Instant::now() + Duration::from_secs(timeout_seconds);
+    let mut answer = loop {
+        if cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+        {
+            break OverwriteAnswer::Cancelled;
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break OverwriteAnswer::TimedOut;
+        }
+        match receiver.recv_timeout(remaining.min(CANCEL_POLL_INTERVAL)) {
+            Ok(true) => break OverwriteAnswer::Allowed,
+            Ok(false) | Err(RecvTimeoutError::Disconnected) => break OverwriteAnswer::Refused,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+    };
+    // Once the prompt is gone respond_overwrite rejects answers, but one may have landed
+    // just before, and respond_overwrite will have told its caller it was accepted
+    prompts.lock().remove(&job_id);
+    if let (OverwriteAnswer::TimedOut, Ok(allow)) = (&answer, receiver.try_recv()) {
+        answer = if allow {
+            OverwriteAnswer::Allowed
+        } else {
+            OverwriteAnswer::Refused
+        };
+    }
+
+    match answer {
+        OverwriteAnswer::Allowed => None,
+        OverwriteAnswer::Refused => Some(stitch_error(format!(
+            "Left the existing {} in place",
+            output_path
+        ))),
+        OverwriteAnswer::TimedOut => Some(stitch_error(format!(
+            "No answer on replacing {} within {}s, so it was left in place",
+            output_path, timeout_seconds
+        ))),
+        OverwriteAnswer::Cancelled => Some(StitchResult {
+            cancelled: true,
+            ..stitch_error("Cancelled")
+        }),
+    }
+}
+
+/// Answer a render's `overwrite-confirm` prompt
+#[tauri::command]
+fn respond_overwrite(
+    prompts: State<'_, OverwritePrompts>,
+    job_id: String,
+    allow: bool,
+) -> Result<(), String> {
+    // Taken out of the map, so a second answer to the same prompt is an error
+    let Some(sender) = prompts.lock().remove(&job_id) else {
+        return Err(format!(
+            "No overwrite prompt waiting for {} (it may have timed out)",
+            job_id
+        ));
+    };
+    sender
+        .send(allow)
+        .map_err(|_| format!("{} is no longer waiting for an answer", job_id))
+}
+
+/// Run FFmpeg to completion and collect its output. On a queued job's thread, cancelling
+/// the job kills FFmpeg and returns an `Interrupted` error. With a progress plan, FFmpeg's
+/// progress is reported through `stitch-progress` events as it runs.
+fn ffmpeg_output(args: &[String], progress: Option<ProgressPlan>) -> io::Result<Output> {
+    let cancel = jobs::current_cancel_flag();
+    let stream = STREAM_STDOUT.get();
+    if cancel.is_none() && progress.is_none() && !stream {
+        return ffmpeg_command().args(args).output();
+    }
+
+    // Progress reports share stdout, so they'd corrupt a streamed render
+    let progress = progress.filter(|_| !stream);
+    let mut command = ffmpeg_command();
+    if progress.is_some() {
+        command.args(["-progress", "pipe:1", "-nostats"]);
+    }
+    let mut child = command
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = match progress {
+        Some(plan) => report_progress(child.stdout.take(), plan),
+        None if stream => stream_output(child.stdout.take()),
+        None => drain_pipe(child.stdout.take()),
+    };
+    let stderr = drain_pipe(child.stderr.take());
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Output {
+                status,
+                stdout: stdout.join().unwrap_or_default(),
+                stderr: stderr.join().unwrap_or_default(),
+            });
+        }
+        if cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+        {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"));
+        }
+        thread::sleep(CANCEL_POLL_INTERVAL);
+    }
+}
+
+/// Run FFmpeg with the given arguments and report the outcome for output_path
+fn run_ffmpeg(args: &[String], output_path: String) -> StitchResult {
+    run_ffmpeg_with_progress(args, output_path, None)
+}
+
+/// run_ffmpeg, emitting progress events against the given plan
+fn run_ffmpeg_with_progress(
+    args: &[String],
+    output_path: String,
+    progress: Option<ProgressPlan>,
+) -> StitchResult {
+    let output = ffmpeg_output(args, progress);
+
+    match output {
+        Ok(output) => {
+            if output.status.success() {
+                CAPTURED_LOG.with_borrow_mut(|log| {
+                    if let Some(log) = log {
+                        log.push_str(&String::from_utf8_lossy(&output.stderr));
+                    }
+                });
+                StitchResult {
+                    success: true,
+                    output_path: Some(output_path),
+                    ..Default::default()
+                }
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                stitch_error(format!("FFmpeg error: {}", stderr))
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+            // FFmpeg was killed mid-write, so whatever it left at output_path (the final
+            // path, or the .part file with atomic_write) is incomplete
+            let _ = fs::remove_file(&output_path);
+            StitchResult {
+                cancelled: true,
+                ..stitch_error("Cancelled")
+            }
+        }
+        Err(e) => stitch_error(format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e)),
+    }
+}
+
+/// A file as it was when its format was probed: path, size and modification time
+type StreamFormatKey = (String, u64, Option<SystemTime>);
+
+/// Stream formats already probed, so the concat check at stitch time doesn't probe again
+/// for clips get_audio_info has read. An edited file gets a new key and is probed afresh.
+static STREAM_FORMATS: OnceLock<Mutex<HashMap<StreamFormatKey, StreamFormat>>> = OnceLock::new();
+
+fn stream_format_key(path: &str) -> Option<StreamFormatKey> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((path.to_string(), metadata.len(), metadata.modified().ok()))
+}
+
+fn remember_stream_format(path: &str, format: &StreamFormat) {
+    if let Some(key) = stream_format_key(path) {
+        STREAM_FORMATS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(key, format.clone());
+    }
+}
+
+/// probe_stream_format, answered from earlier probes of the same file where possible
+fn stream_format(path: &str) -> Result<StreamFormat, String> {
+    let cached = stream_format_key(path).and_then(|key| {
+        STREAM_FORMATS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&key)
+            .cloned()
+    });
+    if let Some(format) = cached {
+        return Ok(format);
+    }
+    let format = probe_stream_format(path)?;
+    remember_stream_format(path, &format);
+    Ok(format)
+}
+
+/// A probed stream property, or None for the placeholders ffprobe prints when it has none
+fn reported_value(value: &str) -> Option<&str> {
+    let value = value.trim();
+    (!value.is_empty() && value != "N/A" && value != "unknown").then_some(value)
+}
+
+/// Probe the format of a file's first audio stream
+fn probe_stream_format(path: &str) -> Result<StreamFormat, String> {
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "a:0",
+            "-show_entries",
+            "stream=codec_name,sample_rate,channels,profile",
+            "-of",
+            "default=noprint_wrappers=1",
+            path,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFprobe error: {}", error));
+    }
+
+    let mut format = StreamFormat::default();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Some(value) = reported_value(value) else {
+            continue;
+        };
+        match key {
+            "codec_name" => format.codec = Some(value.to_string()),
+            "sample_rate" => format.sample_rate = value.parse().ok(),
+            "channels" => format.channels = value.parse().ok(),
+            "profile" => format.profile = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(format)
+}
+
+/// Encoded bitrate of a file's first audio stream, in bits per second. Prefers the
+/// stream's own figure, then the container's, then size*8/duration. For VBR files every
+/// one of these is an average over the whole file.
+fn probe_bitrate(path: &str) -> Result<u64, String> {
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "a:0",
+            "-show_entries",
+            "stream=bit_rate:format=bit_rate,duration,size",
+            path,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFprobe error: {}", error));
+    }
+
+    // Stream and format both report bit_rate, so track which section each line is in
+    let (mut stream_rate, mut format_rate, mut duration, mut size) = (None, None, None, None);
+    let mut in_stream = false;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        match line.trim() {
+            "[STREAM]" => in_stream = true,
+            "[FORMAT]" => in_stream = false,
+            line => {
+                let Some((key, value)) = line.split_once('=') else {
+                    continue;
+                };
+                match (key, in_stream) {
+                    ("bit_rate", true) => stream_rate = value.parse::<u64>().ok(),
+                    ("bit_rate", false) => format_rate = value.parse::<u64>().ok(),
+                    ("duration", false) => duration = value.parse::<f64>().ok(),
+                    ("size", false) => size = value.parse::<u64>().ok(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let computed = match (size, duration) {
+        (Some(size), Some(duration)) if duration > 0.0 => {
+            Some((size as f64 * 8.0 / duration) as u64)
+        }
+        _ => None,
+    };
+    stream_rate
+        .or(format_rate)
+        .or(computed)
+        .filter(|&rate| rate > 0)
+        .ok_or_else(|| format!("Could not determine the bitrate of {}", path))
+}
+
+/// Levels of one channel, in dBFS; None for a silent channel (astats reports -inf)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ChannelLevel {
+    pub channel: u32, // 1-based, as astats numbers them
+    pub rms_db: Option<f64>,
+    pub peak_db: Option<f64>,
+}
+
+/// Per-channel levels of a file and how far apart they are
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChannelBalance {
+    pub channels: Vec<ChannelLevel>, // A single entry for mono
+    pub delta_db: Option<f64>,       // Loudest minus quietest RMS level; 0 for mono
+    pub warnings: Vec<String>,
+}
+
+/// RMS difference between channels past which channel_balance warns
+const CHANNEL_IMBALANCE_DB: f64 = 3.0;
+
+/// Measure each channel's RMS and peak level, e.g. to catch a dual-receiver capture with one
+/// side much louder than the other
+#[tauri::command]
+async fn channel_balance(path: String) -> Result<ChannelBalance, String> {
+    let output = ffmpeg_command()
+        .args([
+            "-hide_banner",
+            "-i",
+            &path,
+            "-map",
+            "0:a:0",
+            "-af",
+            "astats=metadata=0",
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFmpeg error: {}", error.trim()));
+    }
+
+    // Each "[Parsed_astats_0 @ 0x...] " line is a stat of the section opened by the last
+    // "Channel: N" line; the "Overall" section that follows isn't a channel
+    let db = |value: &str| value.trim().parse::<f64>().ok().filter(|db| db.is_finite());
+    let mut channels: Vec<ChannelLevel> = Vec::new();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stderr.lines() {
+        let Some((_, stat)) = line.split_once("] ") else {
+            continue;
+        };
+        let stat = stat.trim();
+        if stat == "Overall" {
+            break;
+        }
+        if let Some(channel) = stat.strip_prefix("Channel:") {
+            if let Ok(channel) = channel.trim().parse() {
+                channels.push(ChannelLevel {
+                    channel,
+                    ..Default::default()
+                });
+            }
+            continue;
+        }
+        let Some(current) = channels.last_mut() else {
+            continue;
+        };
+        if let Some(value) = stat.strip_prefix("RMS level dB:") {
+            current.rms_db = db(value);
+        } else if let Some(value) = stat.strip_prefix("Peak level dB:") {
+            current.peak_db = db(value);
+        }
+    }
+    if channels.is_empty() {
+        return Err("astats reported no channels".to_string());
+    }
+
+    let levels: Vec<f64> = channels
+        .iter()
+        .filter_map(|channel| channel.rms_db)
+        .collect();
+    let delta_db = match (
+        levels.iter().copied().reduce(f64::max),
+        levels.iter().copied().reduce(f64::min),
+    ) {
+        (Some(loudest), Some(quietest)) => Some(loudest - quietest),
+        _ => None,
+    };
+
+    let mut warnings = Vec::new();
+    if let Some(delta) = delta_db.filter(|&delta| delta > CHANNEL_IMBALANCE_DB) {
+        warnings.push(format!(
+            "Channels differ by {:.1} dB; consider rebalancing before stitching",
+            delta
+        ));
+    }
+    // A silent channel next to a live one is the most lopsided case of all
+    if channels.len() > 1 && !levels.is_empty() && levels.len() < channels.len() {
+        warnings.push("At least one channel is silent".to_string());
+    }
+
+    Ok(ChannelBalance {
+        channels,
+        delta_db,
+        warnings,
+    })
+}
+
+/// Measure a clip's DC offset as a fraction of full scale (0 is centred, ±1 is full scale)
+#[tauri::command]
+async fn detect_dc_offset(path: String) -> Result<f64, String> {
+    dc_offset(&path)
+}
+
+/// Report a clip's actual encoded bitrate in bits per second, e.g. to spot a "320k"
+/// file that is really a 128k re-encode
+#[tauri::command]
+async fn detect_bitrate(path: String) -> Result<u64, String> {
+    probe_bitrate(&path)
+}
+
+/// Sample rate the energy scan runs at; plenty for speech and cheap on long recordings
+const ENERGY_SCAN_SAMPLE_RATE: u32 = 8000;
+
+/// Longest block the energy scan measures at once, which is also how finely the
+/// loudest window's start is placed
+const ENERGY_SCAN_MAX_BLOCK_SECONDS: f64 = 0.5;
+
+/// Mean-square energy of consecutive blocks of a file's audio, with each block's start
+/// time. One astats pass, reset every block, rather than a run per candidate window.
+fn block_energies(path: &str, block_seconds: f64) -> Result<Vec<(f64, f64)>, String> {
+    let samples = ((block_seconds * ENERGY_SCAN_SAMPLE_RATE as f64).round() as u32).max(1);
+    let filter = format!(
+        "aformat=channel_layouts=mono,aresample={},asetnsamples=n={}:p=0,astats=metadata=1:reset=1,ametadata=mode=print:key=lavfi.astats.Overall.RMS_level",
+        ENERGY_SCAN_SAMPLE_RATE, samples
+    );
+    let output = ffmpeg_command()
+        .args([
+            "-hide_banner",
+            "-i",
+            path,
+            "-map",
+            "0:a:0",
+            "-af",
+            &filter,
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFmpeg error: {}", error.trim()));
+    }
+
+    // ametadata prints a "frame:N pts:N pts_time:T" line, then the value for that frame
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut blocks = Vec::new();
+    let mut start = None;
+    for line in stderr.lines() {
+        if let Some(time) = line.split("pts_time:").nth(1) {
+            start = time
+                .split_whitespace()
+                .next()
+                .and_then(|t| t.parse::<f64>().ok());
+        } else if let Some(value) = line.split("lavfi.astats.Overall.RMS_level=").nth(1) {
+            // A silent block reports -inf, which is no energy at all
+            let energy = value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|db| db.is_finite())
+                .map_or(0.0, |db| 10f64.powf(db / 10.0));
+            if let Some(start) = start.take() {
+                blocks.push((start, energy));
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+/// Find the `window` seconds of a file with the most energy, e.g. to pick a highlight for a
+/// teaser, returning its start and end. A file no longer than the window comes back whole.
+#[tauri::command]
+async fn find_peak_segment(path: String, window: f64) -> Result<(f64, f64), String> {
+    if !(window > 0.0 && window.is_finite()) {
+        return Err("window must be greater than zero".to_string());
+    }
+    let info = get_audio_info(path.clone());
+    if !info.valid {
+        return Err(info
+            .error
+            .unwrap_or_else(|| format!("Could not read {}", path)));
+    }
+    if info.duration <= window {
+        return Ok((0.0, info.duration));
+    }
+
+    let block_seconds = (window / 4.0).min(ENERGY_SCAN_MAX_BLOCK_SECONDS);
+    let blocks = block_energies(&path, block_seconds)?;
+    let span = ((window / block_seconds).round() as usize).max(1);
+    if blocks.len() <= span {
+        return Ok((0.0, info.duration));
+    }
+
+    // Running sum over `span` blocks; the earliest window wins a tie
+    let mut sum: f64 = blocks[..span].iter().map(|&(_, energy)| energy).sum();
+    let (mut best_index, mut best_sum) = (0, sum);
+    for i in 1..=blocks.len() - span {
+        sum += blocks[i + span - 1].1 - blocks[i - 1].1;
+        if sum > best_sum {
+            (best_index, best_sum) = (i, sum);
+        }
+    }
+
+    let start = blocks[best_index].0.min(info.duration - window).max(0.0);
+    Ok((start, start + window))
+}
+
+/// One recommended output setting and why it was picked
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettingSuggestion<T> {
+    pub value: T,
+    pub rationale: String,
+}
+
+/// Output settings matched to what the clips actually contain
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuggestedSettings {
+    pub bitrate: SettingSuggestion<String>, // In the same form stitch_audio takes, e.g. "32k"
+    pub sample_rate: SettingSuggestion<u32>,
+    pub channels: SettingSuggestion<u8>,
+    pub probed: usize, // Clips whose format could be read
+}
+
+/// MP3 bitrates in kbps, for rounding a source bitrate up to one the encoder offers
+const MP3_BITRATES_KBPS: [u32; 17] = [
+    8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+
+/// Bitrate that keeps speech clean at a sample rate and channel count, in kbps. Radio
+/// voice has nothing above half the sample rate, so low-rate sources need little.
+fn voice_bitrate_kbps(sample_rate: u32, channels: u8) -> u32 {
+    let mono = match sample_rate {
+        0..=8000 => 32,
+        8001..=16000 => 48,
+        16001..=24000 => 64,
+        _ => 96,
+    };
+    if channels > 1 {
+        mono * 3 / 2
+    } else {
+        mono
+    }
+}
+
+/// Probe the clips and recommend a bitrate, sample rate and channel count for them. Trunked
+/// systems usually record 8 kHz mono, where a stereo 320k encode only wastes space.
+#[tauri::command]
+async fn suggest_output_settings(clips: Vec<AudioClip>) -> Result<SuggestedSettings, String> {
+    let infos: Vec<AudioInfo> = clips
+        .iter()
+        .map(|clip| get_audio_info(clip.path.clone()))
+        .filter(|info| info.valid && info.sample_rate.is_some())
+        .collect();
+    if infos.is_empty() {
+        return Err("None of the clips could be probed".to_string());
+    }
+
+    let rates: Vec<u32> = infos.iter().filter_map(|info| info.sample_rate).collect();
+    let max_rate = rates.iter().copied().max().unwrap_or(44100);
+    let mixed_rates = rates.iter().any(|&rate| rate != max_rate);
+    let sample_rate = SettingSuggestion {
+        value: max_rate,
+        rationale: if mixed_rates {
+            format!(
+                "Clips range from {} to {} Hz; the highest keeps every clip's detail without resampling up for nothing",
+                rates.iter().min().unwrap_or(&max_rate),
+                max_rate
+            )
+        } else {
+            format!(
+                "Every clip is {} Hz; a higher rate adds no detail",
+                max_rate
+            )
+        },
+    };
+
+    let max_channels = infos
+        .iter()
+        .filter_map(|info| info.channels)
+        .max()
+        .unwrap_or(1);
+    let channels = SettingSuggestion {
+        value: max_channels.min(2),
+        rationale: if max_channels <= 1 {
+            "Every clip is mono; stereo would only duplicate the channel".to_string()
+        } else {
+            "Some clips have more than one channel; stereo keeps them".to_string()
+        },
+    };
+
+    let target = voice_bitrate_kbps(sample_rate.value, channels.value);
+    // Never suggest more than the best source was encoded at
+    let source_kbps = infos
+        .iter()
+        .filter_map(|info| info.bit_rate)
+        .max()
+        .map(|bps| bps.div_ceil(1000));
+    let source_cap = source_kbps.map(|kbps| {
+        MP3_BITRATES_KBPS
+            .iter()
+            .copied()
+            .find(|&rate| rate >= kbps)
+            .unwrap_or(320)
+    });
+    let bitrate = match source_cap {
+        Some(cap) if cap < target => SettingSuggestion {
+            value: format!("{}k", cap),
+            rationale: format!(
+                "The best source is only {} kbps, so encoding higher wouldn't improve it",
+                source_kbps.unwrap_or(cap)
+            ),
+        },
+        _ => SettingSuggestion {
+            value: format!("{}k", target),
+            rationale: format!(
+                "{} kbps keeps {} voice at {} Hz clear",
+                target,
+                if channels.value > 1 { "stereo" } else { "mono" },
+                sample_rate.value
+            ),
+        },
+    };
+
+    Ok(SuggestedSettings {
+        bitrate,
+        sample_rate,
+        channels,
+        probed: infos.len(),
+    })
+}
+
+/// Bytes set aside for tags and headers (ID3, Xing/Info, Ogg/Opus headers) when fitting an
+/// output to a size limit
+const TARGET_SIZE_HEADER_BYTES: u64 = 8 * 1024;
+
+/// libopus's usable bitrate range, in kbps
+const OPUS_BITRATE_RANGE_KBPS: (u32, u32) = (6, 510);
+
+/// Highest bitrate at which `total_duration` seconds of audio fits in `target_bytes`,
+/// as a bitrate string stitch_audio accepts. Headers and the usual container and
+/// encoder overshoot come off the budget first, then the rate is rounded down to one
+/// the encoder offers.
+#[tauri::command]
+fn bitrate_for_target_size(
+    total_duration: f64,
+    target_bytes: u64,
+    format: OutputFormat,
+) -> Result<String, String> {
+    if !(total_duration > 0.0 && total_duration.is_finite()) {
+        return Err("Duration must be greater than zero".to_string());
+    }
+    if format == OutputFormat::Mp4 {
+        return Err(
+            "Video size depends on the background as well as the audio; pick a bitrate for MP3 or Opus"
+                .to_string(),
+        );
+    }
+
+    let payload_bytes = target_bytes.saturating_sub(TARGET_SIZE_HEADER_BYTES) as f64;
+    let kbps = payload_bytes * 8.0 / OUTPUT_SIZE_MARGIN / total_duration / 1000.0;
+    let (lowest, rate) = match format {
+        OutputFormat::Opus => {
+            let (min, max) = OPUS_BITRATE_RANGE_KBPS;
+            (
+                min,
+                (kbps.floor() >= min as f64).then(|| (kbps.floor() as u32).min(max)),
+            )
+        }
+        _ => (
+            MP3_BITRATES_KBPS[0],
+            MP3_BITRATES_KBPS
+                .iter()
+                .rev()
+                .copied()
+                .find(|&rate| rate as f64 <= kbps),
+        ),
+    };
+
+    rate.map(|rate| format!("{}k", rate)).ok_or_else(|| {
+        format!(
+            "{} bytes is too small for {:.0}s of {}; even {}k needs about {} bytes",
+            target_bytes,
+            total_duration,
+            format.extension().to_uppercase(),
+            lowest,
+            (lowest as f64 * 1000.0 / 8.0 * total_duration * OUTPUT_SIZE_MARGIN) as u64
+                + TARGET_SIZE_HEADER_BYTES
+        )
+    })
+}
+
+/// Compare every clip's stream format against the first clip's, stopping at the first mismatch
+fn concat_plan(clips: &[AudioClip]) -> ConcatPlan {
+    let mut reference: Option<StreamFormat> = None;
+
+    for (index, clip) in clips.iter().enumerate() {
+        let format = match stream_format(&clip.path) {
+            Ok(format) => format,
+            Err(e) => {
+                return ConcatPlan {
+                    demuxer_compatible: false,
+                    mismatch_index: Some(index),
+                    mismatch_property: None,
+                    error: Some(e),
+                }
+            }
+        };
+
+        let Some(reference) = &reference else {
+            reference = Some(format);
+            continue;
+        };
+
+        let mismatch = if format.codec != reference.codec {
+            Some("codec")
+        } else if format.sample_rate != reference.sample_rate {
+            Some("sample_rate")
+        } else if format.channels != reference.channels {
+            Some("channels")
+        } else if format.profile != reference.profile {
+            Some("profile")
+        } else {
+            None
+        };
+        if let Some(property) = mismatch {
+            return ConcatPlan {
+                demuxer_compatible: false,
+                mismatch_index: Some(index),
+                mismatch_property: Some(property.to_string()),
+                error: None,
+            };
+        }
+    }
+
+    ConcatPlan {
+        demuxer_compatible: true,
+        mismatch_index: None,
+        mismatch_property: None,
+        error: None,
+    }
+}
+
+/// Check whether the clips can use the fast concat demuxer path
+#[tauri::command]
+async fn concat_compatibility(clips: Vec<AudioClip>) -> ConcatPlan {
+    concat_plan(&clips)
+}
+
+/// Stitch multiple audio clips into a single MP3 (or Opus) file. output_path can also be
+/// `pipe:`, streaming the render back as events, or an RTMP/HTTP URL to push it to.
+#[tauri::command]
+async fn stitch_audio(
+    clips: Vec<AudioClip>,
+    output_path: String,
+    bitrate: String,
+    audio_config: Option<AudioConfig>,
+) -> StitchResult {
+    let started = Instant::now();
+    if clips.is_empty() {
+        return stitch_error("No clips provided");
+    }
+    let clips = match order_clips(clips) {
+        Ok(clips) => clips,
+        Err(e) => return stitch_error(e),
+    };
+
+    let audio_config = audio_config.unwrap_or_default();
+    let mut warnings = Vec::new();
+    let target = match output_target(&output_path) {
+        Ok(target) => target,
+        Err(e) => return stitch_error(e),
+    };
+    let streamed = target != OutputTarget::File;
+    let (output_path, container) = if streamed {
+        // Nothing is left on disk afterwards to move, rewrite, or stamp
+        let file_only = [
+            ("atomic_write", audio_config.atomic_write),
+            ("write_manifest", audio_config.write_manifest),
+            ("encrypt", audio_config.encrypt.is_some()),
+            ("append_to", audio_config.append_to.is_some()),
+            ("transcript_srt", audio_config.transcript_srt.is_some()),
+            ("clip_chapters", audio_config.clip_chapters),
+            ("preserve_mtime", audio_config.preserve_mtime.is_some()),
+            ("inherit_mtime", audio_config.inherit_mtime),
+            (
+                "vbr_handling: fix_duration",
+                audio_config.vbr_handling == VbrHandling::FixDuration,
+            ),
+        ];
+        if let Some((option, _)) = file_only.iter().find(|(_, set)| *set) {
+            return stitch_error(format!(
+                "{} needs a file output, not {}",
+                option, output_path
+            ));
+        }
+        let container = match &audio_config.container {
+            Some(container) => container.clone(),
+            None => match stream_container(&output_path, audio_config.format) {
+                Ok(container) => container.to_string(),
+                Err(e) => return stitch_error(e),
+            },
+        };
+        (output_path, Some(container))
+    } else {
+        match resolve_output_path(
+            &output_path,
+            audio_config.format,
+            audio_config.container.as_deref(),
+        ) {
+            Ok(path) => (path, audio_config.container.clone()),
+            Err(e) => return stitch_error(e),
+        }
+    };
+
+    // Empty clips add nothing but can throw off offsets and chapter timing downstream
+    let (clips, empty_clips) = partition_empty_clips(clips);
+    let mut skipped: Vec<String> = empty_clips.into_iter().map(|clip| clip.path).collect();
+    if let (EmptyClipMode::Abort, Some(path)) = (audio_config.empty_clips, skipped.first()) {
+        return stitch_error(format!("Clip has no audio: {}", path));
+    }
+    let clips = if audio_config.skip_corrupt_clips {
+        let (intact, corrupt): (Vec<_>, Vec<_>) = clips
+            .into_iter()
+            .partition(|clip| clip_integrity(&clip.path).ok);
+        for clip in corrupt {
+            warnings.push(format!("Skipped corrupt clip: {}", clip.name));
+            skipped.push(clip.path);
+        }
+        intact
+    } else {
+        clips
+    };
+    if clips.is_empty() {
+        return stitch_error("None of the clips contain any audio");
+    }
+    // Done ahead of append_to, whose file keeps its edges like any finished output
+    let mut clips = clips;
+    let (edge_trims, silence_trimmed) = if audio_config.trim_inter_clip_silence {
+        match trim_edge_silence(&mut clips) {
+            Ok((trims, seconds)) => (trims, Some(seconds)),
+            Err(e) => return stitch_error(e),
+        }
+    } else {
+        (HashMap::new(), None)
+    };
+
+    // The existing output joins as one more clip, ahead of the new ones. Its chapters are
+    // read now, before an in-place render replaces it.
+    let existing_chapters = match &audio_config.append_to {
+        Some(existing) => {
+            if audio_config.transcript_srt.is_some() {
+                return stitch_error("Transcript chapters can't be combined with append_to");
+            }
+            if audio_config.cap_clip_length.is_some() {
+                return stitch_error("cap_clip_length can't be combined with append_to, as it would cut the existing file");
+            }
+            let base = match append_base_clip(existing) {
+                Ok(clip) => clip,
+                Err(e) => return stitch_error(e),
+            };
+            let chapters = match probe_chapters(existing) {
+                Ok(chapters) => chapters,
+                Err(e) => return stitch_error(e),
+            };
+            clips.insert(0, base);
+            Some(chapters)
+        }
+        None => None,
+    };
+    let in_place = audio_config
+        .append_to
+        .as_deref()
+        .is_some_and(|existing| same_file(existing, &output_path));
+
+    if audio_config.clip_chapters && audio_config.transcript_srt.is_some() {
+        return stitch_error("Transcript chapters can't be combined with clip_chapters");
+    }
+    // Parse the transcript up front so a bad file fails before the render, not after
+    let transcript = match &audio_config.transcript_srt {
+        Some(srt_path) => match fs::read_to_string(srt_path)
+            .map_err(|e| format!("Failed to read transcript: {}", e))
+            .and_then(|contents| parse_srt(&contents))
+        {
+            Ok(cues) => Some(cues),
+            Err(e) => return stitch_error(e),
+        },
+        None => None,
+    };
+
+    // Running out of disk mid-render leaves a corrupt file, so check before starting
+    if audio_config.check_disk_space && !streamed {
+        match estimated_output_bytes(&clips, &bitrate) {
+            Some(required_bytes) => match disk_status(&output_path, required_bytes) {
+                Ok(status) if !status.sufficient => {
+                    return stitch_error(format!(
+                        "Not enough disk space on {}: need about {} bytes, {} available",
+                        status.mount_point, status.required_bytes, status.available_bytes
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => warnings.push(format!("Skipped disk space check: {}", e)),
+            },
+            None => warnings.push(format!(
+                "Skipped disk space check: can't estimate size at bitrate '{}'",
+                bitrate
+            )),
+        }
+    }
+
+    let (output_filters, resampler) = match output_filter_chain(&audio_config, &mut warnings) {
+        Ok(chain) => chain,
+        Err(e) => return stitch_error(e),
+    };
+    let level_adjusted = audio_config.loudnorm.is_some() || audio_config.gain_db.is_some();
+    let speed = audio_config.speed.unwrap_or(1.0);
+
+    // Encoding above the best source bitrate only makes the file bigger
+    if audio_config.warn_bitrate_upscale {
+        let max_source_rate = clips
+            .iter()
+            .filter_map(|clip| probe_bitrate(&clip.path).ok())
+            .max();
+        if let (Some(source_rate), Some(output_rate)) = (max_source_rate, parse_bitrate(&bitrate)) {
+            if output_rate > source_rate {
+                warnings.push(format!(
+                    "Output bitrate {} is above the highest source bitrate ({} kbps); this won't improve quality",
+                    bitrate,
+                    source_rate / 1000
+                ));
+            }
+        }
+    }
+
+    let max_filter_inputs = audio_config
+        .max_filter_inputs
+        .unwrap_or(DEFAULT_MAX_FILTER_INPUTS);
+    if max_filter_inputs < 2 {
+        return stitch_error("max_filter_inputs must be at least 2");
+    }
+
+    // Per-clip filters need each clip as a separate input, which the concat demuxer can't do,
+    // so the demuxer stays the default for plain joins of any length
+    let mut encoder_args = match audio_encoder_args(
+        audio_config.format,
+        &bitrate,
+        audio_config.opus_application,
+        container.as_deref(),
+        &output_path,
+    ) {
+        Ok(args) => args,
+        Err(e) => return stitch_error(e),
+    };
+    if let Err(e) = validate_threads(audio_config.threads) {
+        return stitch_error(e);
+    }
+    if let Some(threads) = audio_config.threads {
+        encoder_args.extend_from_slice(&["-threads".to_string(), threads.to_string()]);
+    }
+    match creation_time_args(
+        audio_config.creation_time.as_deref(),
+        audio_config.creation_time_from_clips,
+        &clips,
+    ) {
+        Ok(args) => encoder_args.extend(args),
+        Err(e) => return stitch_error(e),
+    }
+    match location_args(
+        audio_config.location.as_ref(),
+        audio_config.location_from_clips,
+        &clips,
+        audio_config.format,
+    ) {
+        Ok(args) => encoder_args.extend(args),
+        Err(e) => return stitch_error(e),
+    }
+    if let Some(encrypt) = &audio_config.encrypt {
+        if let Err(e) = encrypt.validate() {
+            return stitch_error(e);
+        }
+    }
+    let _priority = PriorityGuard::set(audio_config.low_priority);
+    let clip_filters = match clip_filter_chain(&audio_config) {
+        Ok(filters) => filters,
+        Err(e) => return stitch_error(e),
+    };
+    if let Err(e) = validate_pans(&clips) {
+        return stitch_error(e);
+    }
+    // Panning only means something in stereo, even if every clip is mono
+    let panned = clips.iter().any(|clip| clip.pan.is_some());
+    if panned {
+        encoder_args.extend_from_slice(&["-ac".to_string(), "2".to_string()]);
+    }
+
+    // Only clips that differ from the target are converted; the rest pass untouched
+    let channel_remix = match audio_config.channel_target {
+        Some(target) => match plan_channel_remix(&clips, target) {
+            Ok((channels, _)) if panned && channels != 2 => {
+                return stitch_error(format!(
+                    "Panned clips need stereo output, but the channel target is {}",
+                    channel_layout_name(channels)
+                ));
+            }
+            Ok(plan) => Some(plan),
+            Err(e) => return stitch_error(e),
+        },
+        None => None,
+    };
+
+    // A stuck mic shouldn't dominate the output; from here on clips are timed at their
+    // capped length
+    let cap = audio_config.cap_clip_length;
+    if cap.is_some_and(|cap| !(cap > 0.0 && cap.is_finite())) {
+        return stitch_error("cap_clip_length must be greater than zero");
+    }
+    let capped = move |clip: &AudioClip| cap.map_or(clip.duration, |cap| clip.duration.min(cap));
+    let mut truncated: Vec<ClipTruncation> = clips
+        .iter()
+        .filter(|clip| capped(clip) < clip.duration)
+        .map(|clip| ClipTruncation {
+            path: clip.path.clone(),
+            original_seconds: clip.duration,
+            removed_seconds: clip.duration - capped(clip),
+        })
+        .collect();
+
+    let join = match clip_join(&audio_config, &clips, cap) {
+        Ok(join) => join,
+        Err(e) => return stitch_error(e),
+    };
+    let boundary_fade = match boundary_fade_seconds(&audio_config) {
+        Ok(fade) => fade,
+        Err(e) => return stitch_error(e),
+    };
+    // The cap's cuts happen per input, which the concat demuxer can't do. Every input is
+    // cut, not just those reported as too long, since a reported duration may be wrong.
+    let cap_inputs = cap.is_some();
+
+    let (dropped, length_limit) = match apply_length_limit(
+        &mut clips,
+        &audio_config,
+        cap,
+        join,
+        &mut truncated,
+        &mut warnings,
+    ) {
+        Ok(limit) => limit,
+        Err(e) => return stitch_error(e),
+    };
+
+    // Length of the joined clips before any speed change
+    let joins = clips.len().saturating_sub(1) as f64;
+    let joined_duration = clips.iter().map(capped).sum::<f64>()
+        + match join {
+            ClipJoin::Concat => 0.0,
+            ClipJoin::Crossfade(crossfade) => -crossfade.duration_seconds * joins,
+            ClipJoin::MarkerTone(tone) => tone.duration_seconds * joins,
+        };
+    // Chapter cues against the joined clips, before any speed change
+    let chapter_cues = match existing_chapters {
+        Some(existing) if !existing.is_empty() => {
+            let durations: Vec<f64> = clips.iter().map(capped).collect();
+            let starts = clip_start_times(&durations, join);
+            match append_chapters(existing, &clips[1..], &starts[1..], joined_duration) {
+                Ok(cues) => Some(cues),
+                Err(e) => return stitch_error(e),
+            }
+        }
+        _ if audio_config.clip_chapters => {
+            let durations: Vec<f64> = clips.iter().map(capped).collect();
+            let starts = clip_start_times(&durations, join);
+            match append_chapters(Vec::new(), &clips, &starts, joined_duration) {
+                Ok(cues) => Some(cues),
+                Err(e) => return stitch_error(e),
+            }
+        }
+        _ => transcript,
+    };
+    let mut output_duration = joined_duration / speed;
+    if let Some(target) = audio_config.pad_to_duration {
+        if output_duration > target {
+            if !audio_config.truncate_to_pad {
+                return stitch_error(format!(
+                    "Clips run {:.1}s, longer than the {:.1}s pad_to_duration",
+                    output_duration, target
+                ));
+            }
+            warnings.push(format!(
+                "Output cut from {:.1}s to the {:.1}s pad_to_duration",
+                output_duration, target
+            ));
+        }
+        // Also caps the length exactly, whatever the probed durations said
+        encoder_args.extend_from_slice(&["-t".to_string(), target.to_string()]);
+        output_duration = target;
+    }
+    // The last clip kept is cut where the output reaches the limit
+    if let (Some(max), None) = (length_limit, audio_config.pad_to_duration) {
+        encoder_args.extend_from_slice(&["-t".to_string(), max.to_string()]);
+        output_duration = output_duration.min(max);
+    }
+    let remixed = channel_remix.as_ref().map_or(0, |(_, paths)| paths.len());
+    let use_demuxer = filter_path_reasons(
+        &clip_filters,
+        cap_inputs,
+        panned,
+        boundary_fade,
+        remixed,
+        edge_trims.len(),
+        join,
+    )
+    .is_empty()
+        && match DemuxerFormats::check(&clips) {
+            DemuxerFormats::Match => true,
+            DemuxerFormats::Differ(warning) | DemuxerFormats::Unreadable(warning) => {
+                warnings.push(warning);
+                false
+            }
+        };
+    let vbr = vbr_plan(
+        &clips,
+        use_demuxer,
+        audio_config.vbr_handling,
+        audio_config.format,
+    );
+    let use_demuxer = vbr.use_demuxer;
+    warnings.extend(vbr.warning);
+    let stream_copy =
+        use_demuxer && can_stream_copy(&clips, &output_filters, audio_config.format, &bitrate);
+    if stream_copy {
+        encoder_args = stream_copy_args(&encoder_args);
+    }
+    // Asked only once the request has fully checked out, so a yes can't be followed by a
+    // settings error. Extending the output in place is the point of append_to, so that
+    // needs no prompt.
+    if let (false, false, Some(prompt)) = (streamed, in_place, &audio_config.confirm_overwrite) {
+        if let Some(result) = confirm_overwrite(&output_path, prompt) {
+            return result;
+        }
+    }
+    if audio_config.encrypt.is_some() {
+        let prompt = audio_config.confirm_overwrite.as_ref();
+        if let Some(result) = confirm_encrypted_overwrite(&output_path, prompt) {
+            return result;
+        }
+    }
+    // FFmpeg can't overwrite a file it's still reading from
+    let render_partial = audio_config.atomic_write || in_place;
+    let render_path = if render_partial {
+        partial_output_path(&output_path)
+    } else {
+        output_path.clone()
+    };
+    let log_capture = audio_config.remove_dc_offset.then(LogCapture::start);
+    let mut result = {
+        // Scoped to the render, so the analysis passes below keep their stdout
+        let _stream = StreamGuard::set(target == OutputTarget::Pipe);
+        if use_demuxer {
+            run_with_concat_list(
+                &clips,
+                "ffmpeg_concat_list.txt",
+                render_path.clone(),
+                |concat_file_path| {
+                    audio_args(
+                        concat_file_path,
+                        &output_filters,
+                        &encoder_args,
+                        &render_path,
+                    )
+                },
+            )
+        } else {
+            run_filter_path(
+                &clips,
+                &ClipChain {
+                    filters: &clip_filters,
+                    max_length: cap,
+                    pan: panned,
+                    remix: channel_remix
+                        .as_ref()
+                        .map(|(channels, paths)| ChannelRemix {
+                            channels: *channels,
+                            paths,
+                        }),
+                    boundary_fade,
+                    edge_trims: Some(&edge_trims),
+                },
+                join,
+                &output_filters,
+                &encoder_args,
+                render_path.clone(),
+                max_filter_inputs,
+            )
+        }
+    };
+    if render_partial {
+        commit_partial_output(&mut result, &render_path, &output_path);
+    }
+    result.concat_method = Some(if use_demuxer { "demuxer" } else { "filter" }.to_string());
+    result.concat_reason = vbr.reason;
+    result.resampler = resampler;
+    result.warnings.extend(warnings);
+    result.skipped = skipped;
+    result.truncated = truncated;
+    result.dropped = dropped;
+    result.stream_copy = stream_copy;
+    result.silence_trimmed = silence_trimmed;
+    if let Some((channels, paths)) = &channel_remix {
+        result.output_channels = Some(*channels);
+        result.channels_converted = Some(paths.len());
+    }
+    if result.success {
+        result.duration = Some(output_duration);
+    }
+
+    // The blocker removes whatever bias was there, so the measured offset is what went
+    if let (true, Some(log_capture)) = (result.success, log_capture) {
+        let offsets = dc_offsets_in_log(&log_capture.finish());
+        if !offsets.is_empty() {
+            result.dc_offset_removed = Some(offsets.iter().sum::<f64>() / offsets.len() as f64);
+        }
+    }
+
+    // Only pay for the extra analysis pass when levels were actually changed
+    let analyze_levels = result.success && (level_adjusted || audio_config.limiter.is_some());
+    if analyze_levels && streamed {
+        result
+            .warnings
+            .push("Skipped the output peak check, as the output isn't a file".to_string());
+    } else if analyze_levels {
+        match peak_level_db(&output_path) {
+            Ok(peak) => {
+                if level_adjusted && peak >= -CLIPPING_MARGIN_DB {
+                    result.clipping_warning = true;
+                    result.warnings.push(format!(
+                        "Output peaks at {:.2} dBFS after normalization/gain; consider lowering the gain",
+                        peak
+                    ));
+                }
+                // alimiter doesn't report gain reduction, so infer it from peaks at the ceiling
+                if let Some(limiter) = &audio_config.limiter {
+                    result.limiter_engaged =
+                        Some(peak >= limiter.limit_db - LIMITER_ENGAGED_MARGIN_DB);
+                }
+            }
+            Err(e) => result.warnings.push(e),
+        }
+    }
+
+    if result.success && vbr.fix_header {
+        if let Err(e) = rewrite_mp3_header(&output_path) {
+            result.warnings.push(format!(
+                "The output's duration header wasn't rewritten: {}",
+                e
+            ));
+        }
+    }
+
+    if let (true, Some(cues)) = (result.success, &chapter_cues) {
+        // Cues are timed against the joined clips, so a speed change moves them too
+        let cues: Vec<SubtitleCue> = cues
+            .iter()
+            .map(|cue| SubtitleCue {
+                start_ms: (cue.start_ms as f64 / speed).round() as u64,
+                end_ms: (cue.end_ms as f64 / speed).round() as u64,
+                text: cue.text.clone(),
+            })
+            .collect();
+        let duration_ms = seconds_to_ms(output_duration);
+        let metadata_path = unique_temp_path("ffmpeg_chapters.txt");
+        let embedded =
+            write_ffmetadata_chapters(&cues, duration_ms, &metadata_path, &mut result.warnings)
+                .and_then(|_| mux_ffmetadata_chapters(&output_path, &metadata_path));
+        let _ = fs::remove_file(&metadata_path);
+        if let Err(e) = embedded {
+            result
+                .warnings
+                .push(format!("Chapters were not embedded: {}", e));
+        }
+    }
+
+    // Checked last, since the chapter remux above rewrites the file
+    if result.success && audio_config.format == OutputFormat::Mp3 && !streamed {
+        match mp3_has_gapless_info(&output_path) {
+            Ok(gapless) => {
+                if !gapless {
+                    result.warnings.push(
+                        "Output has no gapless header; players may add silence at the start and end"
+                            .to_string(),
+                    );
+                }
+                result.gapless = Some(gapless);
+            }
+            Err(e) => result.warnings.push(e),
+        }
+    }
+
+    let final_path = match &audio_config.encrypt {
+        Some(encrypt) => encrypt_output(&mut result, &output_path, encrypt),
+        None => output_path.clone(),
+    };
+
+    // An explicit timestamp wins over inheriting one from the clips
+    let mtime = match audio_config.preserve_mtime {
+        Some(secs) => Some(UNIX_EPOCH + Duration::from_secs(secs)),
+        None if audio_config.inherit_mtime => earliest_clip_mtime(&clips),
+        None => None,
+    };
+    if let (true, Some(mtime)) = (result.success, mtime) {
+        match apply_mtime(&final_path, mtime) {
+            Ok(secs) => result.mtime = Some(secs),
+            Err(e) => result.warnings.push(e),
+        }
+    }
+    record_render_stats(&mut result, started, output_duration);
+
+    if audio_config.write_manifest {
+        // The manifest records the request, which didn't list the appended file
+        if audio_config.append_to.is_some() {
+            clips.remove(0);
+        }
+        // Nor the trims, which a rerun finds again from the files themselves
+        for clip in &mut clips {
+            if let Some(trim) = edge_trims.get(&clip.path) {
+                clip.duration += trim.lead + trim.trail;
+            }
+        }
+        let encrypt = audio_config.encrypt.clone();
+        let manifest = StitchManifest {
+            format: audio_config.format,
+            clips,
+            output_path,
+            bitrate,
+            audio_config: Some(audio_config),
+            video_config: None,
+        };
+        attach_manifest(&mut result, &manifest);
+        if let (Some(encrypt), Some(path)) = (encrypt, result.manifest_path.clone()) {
+            result.manifest_path = encrypt_sidecar(&mut result, &path, &encrypt);
+        }
+    }
+
+    result
+}
+
+/// One input of a described pipeline, with the filters it goes through before the join
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PipelineInput {
+    pub path: String,
+    pub filters: Vec<String>, // Always empty on the demuxer path
+}
+
+/// How an audio stitch would run, as worked out by describe_pipeline
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PipelineDescription {
+    pub concat_method: String, // "demuxer" or "filter", as the render would report it
+    pub stream_copy: bool,     // Audio copied as-is, with no decode or encode at all
+    pub inputs: Vec<PipelineInput>, // In output order, without skipped or dropped clips
+    pub join: String,          // The concat filter, crossfade or tone; "demuxer" otherwise
+    pub output_filters: Vec<String>, // Applied once to the joined output, in order
+    pub notes: Vec<String>,    // What decided the method and what it costs, in plain words
+    pub warnings: Vec<String>, // Those the stitch would give before rendering
+}
+
+/// Work out how stitch_audio would run a request without rendering anything: whether it
+/// takes the demuxer or the filter path and why, each input's filters, the join, and the
+/// output filters. Built from the same helpers stitch_audio decides with, so the two
+/// can't drift apart. Clips are still probed, as the stitch probes them.
+#[tauri::command]
+async fn describe_pipeline(request: StitchManifest) -> Result<PipelineDescription, String> {
+    if request.format == OutputFormat::Mp4 {
+        return Err(
+            "describe_pipeline covers audio stitches; stitch_video always re-encodes".to_string(),
+        );
+    }
+    if request.clips.is_empty() {
+        return Err("No clips provided".to_string());
+    }
+    let audio_config = request.audio_config.unwrap_or_default();
+    let mut warnings = Vec::new();
+    let mut notes = Vec::new();
+
+    let (mut clips, empty_clips) = partition_empty_clips(order_clips(request.clips)?);
+    if let (EmptyClipMode::Abort, Some(clip)) = (audio_config.empty_clips, empty_clips.first()) {
+        return Err(format!("Clip has no audio: {}", clip.path));
+    }
+    if !empty_clips.is_empty() {
+        notes.push(format!("{} empty clip(s) are left out", empty_clips.len()));
+    }
+    if audio_config.skip_corrupt_clips {
+        notes.push(
+            "skip_corrupt_clips decodes every clip in full before the render starts".to_string(),
+        );
+    }
+    if clips.is_empty() {
+        return Err("None of the clips contain any audio".to_string());
+    }
+    let edge_trims = if audio_config.trim_inter_clip_silence {
+        let (trims, seconds) = trim_edge_silence(&mut clips)?;
+        notes.push(format!(
+            "trim_inter_clip_silence decodes every clip once to find its edges ({:.1}s of dead air found)",
+            seconds
+        ));
+        trims
+    } else {
+        HashMap::new()
+    };
+    if let Some(existing) = &audio_config.append_to {
+        clips.insert(0, append_base_clip(existing)?);
+    }
+
+    let (output_filters, _) = output_filter_chain(&audio_config, &mut warnings)?;
+    let max_filter_inputs = audio_config
+        .max_filter_inputs
+        .unwrap_or(DEFAULT_MAX_FILTER_INPUTS);
+    if max_filter_inputs < 2 {
+        return Err("max_filter_inputs must be at least 2".to_string());
+    }
+    let clip_filters = clip_filter_chain(&audio_config)?;
+    validate_pans(&clips)?;
+    let panned = clips.iter().any(|clip| clip.pan.is_some());
+    let channel_remix = audio_config
+        .channel_target
+        .map(|target| plan_channel_remix(&clips, target))
+        .transpose()?;
+    if let (true, Some((channels, _))) = (panned, &channel_remix) {
+        if *channels != 2 {
+            return Err(format!(
+                "Panned clips need stereo output, but the channel target is {}",
+                channel_layout_name(*channels)
+            ));
+        }
+    }
+
+    let cap = audio_config.cap_clip_length;
+    if cap.is_some_and(|cap| !(cap > 0.0 && cap.is_finite())) {
+        return Err("cap_clip_length must be greater than zero".to_string());
+    }
+    let mut truncated: Vec<ClipTruncation> = clips
+        .iter()
+        .filter(|clip| cap.is_some_and(|cap| clip.duration > cap))
+        .map(|clip| ClipTruncation {
+            path: clip.path.clone(),
+            original_seconds: clip.duration,
+            removed_seconds: clip.duration - cap.unwrap_or(clip.duration),
+        })
+        .collect();
+    let join = clip_join(&audio_config, &clips, cap)?;
+    let boundary_fade = boundary_fade_seconds(&audio_config)?;
+    let cap_inputs = cap.is_some();
+    apply_length_limit(
+        &mut clips,
+        &audio_config,
+        cap,
+        join,
+        &mut truncated,
+        &mut warnings,
+    )?;
+
+    let remixed = channel_remix.as_ref().map_or(0, |(_, paths)| paths.len());
+    let reasons = filter_path_reasons(
+        &clip_filters,
+        cap_inputs,
+        panned,
+        boundary_fade,
+        remixed,
+        edge_trims.len(),
+        join,
+    );
+    let use_demuxer = reasons.is_empty()
+        && match DemuxerFormats::check(&clips) {
+            DemuxerFormats::Match => true,
+            DemuxerFormats::Differ(warning) => {
+                warnings.push(warning);
+                notes.push("Clip formats differ, so every clip is decoded and joined with the concat filter".to_string());
+                false
+            }
+            DemuxerFormats::Unreadable(warning) => {
+                warnings.push(warning);
+                notes.push("Not every clip's format could be read, so the join goes through the concat filter".to_string());
+                false
+            }
+        };
+    let vbr = vbr_plan(
+        &clips,
+        use_demuxer,
+        audio_config.vbr_handling,
+        audio_config.format,
+    );
+    let use_demuxer = vbr.use_demuxer;
+    warnings.extend(vbr.warning);
+    notes.extend(vbr.reason);
+    let stream_copy = use_demuxer
+        && can_stream_copy(
+            &clips,
+            &output_filters,
+            audio_config.format,
+            &request.bitrate,
+        );
+
+    if stream_copy {
+        notes.push("Clips are copied as-is: nothing is decoded or re-encoded".to_string());
+    } else if use_demuxer {
+        notes.push(
+            "Clips are read as one stream by the concat demuxer and encoded once".to_string(),
+        );
+        if !output_filters.is_empty() {
+            notes.push("Output filters force a full re-encode of the joined audio".to_string());
+        } else if audio_config.format == OutputFormat::Mp3 {
+            notes.push(
+                "Clips aren't all MP3 at the output bitrate, so the join is re-encoded".to_string(),
+            );
+        }
+    } else {
+        notes.extend(reasons.into_iter().map(|reason| {
+            format!(
+                "{}, so every clip is decoded as its own filter input",
+                reason
+            )
+        }));
+        if clips.len() > max_filter_inputs {
+            check_chunked_join(join, clips.len())?;
+            notes.push(format!(
+                "{} clips are over max_filter_inputs, so they are filtered {} at a time to lossless intermediates first",
+                clips.len(),
+                max_filter_inputs
+            ));
+        }
+    }
+    if audio_config.loudnorm.is_some()
+        || audio_config.gain_db.is_some()
+        || audio_config.limiter.is_some()
+    {
+        notes.push("The finished output is decoded once more to check its peak level".to_string());
+    }
+    if audio_config.clip_chapters
+        || audio_config.transcript_srt.is_some()
+        || audio_config.append_to.is_some()
+    {
+        notes
+            .push("Chapters are added afterwards by a remux, which copies the streams".to_string());
+    }
+
+    let chain = ClipChain {
+        filters: &clip_filters,
+        max_length: cap,
+        pan: panned,
+        remix: channel_remix
+            .as_ref()
+            .map(|(channels, paths)| ChannelRemix {
+                channels: *channels,
+                paths,
+            }),
+        boundary_fade,
+        edge_trims: Some(&edge_trims),
+    };
+    let inputs = clips
+        .iter()
+        .map(|clip| PipelineInput {
+            path: clip.path.clone(),
+            filters: if use_demuxer {
+                Vec::new()
+            } else {
+                chain.clip_filters(clip)
+            },
+        })
+        .collect();
+    let join = match join {
+        _ if use_demuxer => "demuxer".to_string(),
+        ClipJoin::Concat => format!("concat=n={}:v=0:a=1", clips.len()),
+        ClipJoin::Crossfade(crossfade) => crossfade.filter(),
+        ClipJoin::MarkerTone(tone) => tone.filter(),
+    };
+
+    Ok(PipelineDescription {
+        concat_method: if use_demuxer { "demuxer" } else { "filter" }.to_string(),
+        stream_copy,
+        inputs,
+        join,
+        output_filters,
+        notes,
+        warnings,
+    })
+}
+
+/// Clips and seconds a preview mix covers unless told otherwise
+const PREVIEW_CLIP_COUNT: usize = 3;
+const PREVIEW_SECONDS: f64 = 30.0;
+
+/// Loudness of a file as loudnorm's analysis pass measures it; None where it reports
+/// nothing usable (-inf for silence)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LoudnessMeasurement {
+    pub integrated_lufs: Option<f64>,
+    pub true_peak_db: Option<f64>,
+    pub lra: Option<f64>,
+}
+
+/// A short render of the first clips, for trying level settings before a full stitch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreviewMix {
+    pub path: String,
+    pub clip_count: usize,
+    pub duration: Option<f64>,
+    pub loudness: LoudnessMeasurement,
+    pub warnings: Vec<String>,
+}
+
+/// Folder preview mixes are written to, emptied by clear_preview_mixes
+fn preview_dir() -> PathBuf {
+    std::env::temp_dir().join("stitch_previews")
+}
+
+/// Measure a file's integrated loudness, true peak and loudness range with loudnorm's
+/// analysis pass, which prints them as JSON at the end of its log
+fn measure_loudness(path: &str) -> Result<LoudnessMeasurement, String> {
+    let output = ffmpeg_command()
+        .args([
+            "-hide_banner",
+            "-i",
+            path,
+            "-af",
+            "loudnorm=print_format=json",
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFmpeg error: {}", error.trim()));
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let report: serde_json::Value = stderr
+        .rfind('{')
+        .zip(stderr.rfind('}'))
+        .and_then(|(start, end)| serde_json::from_str(stderr.get(start..=end)?).ok())
+        .ok_or_else(|| format!("loudnorm reported no measurement for {}", path))?;
+    // Every value comes as a string
+    let value = |key: &str| {
+        report
+            .get(key)
+            .and_then(|value| value.as_str())
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|value| value.is_finite())
+    };
+    Ok(LoudnessMeasurement {
+        integrated_lufs: value("input_i"),
+        true_peak_db: value("input_tp"),
+        lra: value("input_lra"),
+    })
+}
+
+/// A clip's place in a loudness ranking
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClipLoudness {
+    pub index: usize, // Position in the list given
+    pub id: String,
+    pub path: String,
+    pub name: String,
+    pub integrated_lufs: Option<f64>,
+    pub true_peak_db: Option<f64>,
+    /// Nothing above loudnorm's gate, so there's no level to report. Ranked ahead of
+    /// every measured clip.
+    pub silent: bool,
+    pub error: Option<String>, // Set when the clip couldn't be measured; ranked last
+}
+
+/// Measure every clip's integrated loudness and rank them quietest first, to surface
+/// transmissions that may be inaudible. Silent clips lead the list, and clips that
+/// couldn't be measured close it. Clips are measured in parallel, with `probe-progress`
+/// events as each finishes.
+#[tauri::command]
+async fn rank_clips_by_loudness(window: tauri::Window, clips: Vec<AudioClip>) -> Vec<ClipLoudness> {
+    let measurements = probe_batch(&window, "rank_clips_by_loudness", &clips, measure_loudness);
+    let mut ranked: Vec<ClipLoudness> = clips
+        .into_iter()
+        .zip(measurements)
+        .enumerate()
+        .map(|(index, (clip, measurement))| {
+            let (integrated_lufs, true_peak_db, error) = match measurement {
+                Ok(loudness) => (loudness.integrated_lufs, loudness.true_peak_db, None),
+                Err(e) => (None, None, Some(e)),
+            };
+            ClipLoudness {
+                index,
+                id: clip.id,
+                path: clip.path,
+                name: clip.name,
+                integrated_lufs,
+                true_peak_db,
+                silent: error.is_none() && integrated_lufs.is_none(),
+                error,
+            }
+        })
+        .collect();
+
+    // Stable, so clips that tie keep their list order
+    let rank = |clip: &ClipLoudness| match (clip.silent, clip.integrated_lufs) {
+        (true, _) => 0,
+        (false, Some(_)) => 1,
+        (false, None) => 2,
+    };
+    ranked.sort_by(|a, b| {
+        rank(a).cmp(&rank(b)).then_with(|| {
+            a.integrated_lufs
+                .unwrap_or_default()
+                .total_cmp(&b.integrated_lufs.unwrap_or_default())
+        })
+    });
+    ranked
+}
+
+/// Render the first `clip_count` clips (default 3), cut to `seconds` (default 30), with
+/// the given level and filter settings, and measure the result. Previews go to a folder
+/// of their own; anything done beside or to a finished output (manifests, chapters,
+/// encryption, timestamps) is skipped.
+#[tauri::command]
+async fn preview_mix(
+    clips: Vec<AudioClip>,
+    bitrate: String,
+    audio_config: Option<AudioConfig>,
+    clip_count: Option<usize>,
+    seconds: Option<f64>,
+) -> Result<PreviewMix, String> {
+    let clip_count = clip_count.unwrap_or(PREVIEW_CLIP_COUNT);
+    if clip_count == 0 {
+        return Err("A preview needs at least one clip".to_string());
+    }
+    let seconds = seconds.unwrap_or(PREVIEW_SECONDS);
+    if !(seconds > 0.0 && seconds.is_finite()) {
+        return Err("Preview length must be greater than zero".to_string());
+    }
+    let mut clips = order_clips(clips)?;
+    clips.truncate(clip_count);
+    let clip_count = clips.len();
+
+    let mut config = audio_config.unwrap_or_default();
+    config.write_manifest = false;
+    config.atomic_write = false;
+    config.confirm_overwrite = None;
+    config.check_disk_space = false;
+    config.encrypt = None;
+    config.append_to = None;
+    config.transcript_srt = None;
+    config.preserve_mtime = None;
+    config.inherit_mtime = false;
+    config.creation_time = None;
+    config.creation_time_from_clips = false;
+    config.location = None;
+    config.location_from_clips = false;
+    config.pad_to_duration = None;
+    config.truncate_to_pad = false;
+    // The length limit does the cutting: later clips are dropped, the last one cut short
+    config.max_total_duration = Some(
+        config
+            .max_total_duration
+            .map_or(seconds, |max| max.min(seconds)),
+    );
+    config.truncate_to_max = true;
+
+    let dir = preview_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create preview folder: {}", e))?;
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let path = dir
+        .join(format!("preview_{:x}.{}", nanos, config.format.extension()))
+        .to_string_lossy()
+        .to_string();
+
+    let result = stitch_audio(clips, path.clone(), bitrate, Some(config)).await;
+    if !result.success {
+        return Err(result
+            .error
+            .unwrap_or_else(|| "Preview render failed".to_string()));
+    }
+    let loudness = measure_loudness(&path)?;
+
+    Ok(PreviewMix {
+        path,
+        clip_count,
+        duration: result.duration,
+        loudness,
+        warnings: result.warnings,
+    })
+}
+
+/// Delete every preview mix, returning how many files were removed
+#[tauri::command]
+fn clear_preview_mixes() -> Result<usize, String> {
+    let entries = match fs::read_dir(preview_dir()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read preview folder: {}", e)),
+    };
+    let mut removed = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// A single line of a user-supplied concat list
+enum ConcatLine {
+    /// A `file` directive, resolved to the path FFmpeg will open
+    File(PathBuf),
+    /// Any other directive, comment, or blank line, kept verbatim
+    Other(String),
+}
+
+/// A user-supplied concat list after validation
+struct ConcatList {
+    lines: Vec<ConcatLine>,
+    /// True when some entries only resolve once re-escaped, so the list can't be fed as-is
+    needs_rewrite: bool,
+}
+
+impl ConcatList {
+    fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.lines.iter().filter_map(|line| match line {
+            ConcatLine::File(path) => Some(path),
+            ConcatLine::Other(_) => None,
+        })
+    }
+}
+
+/// Unescape a concat list token the way FFmpeg does (single quotes and backslashes)
+fn unescape_concat_token(token: &str) -> String {
+    let mut result = String::new();
+    let mut chars = token.chars();
+    let mut in_quotes = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => in_quotes = !in_quotes,
+            '\\' if !in_quotes => {
+                if let Some(escaped) = chars.next() {
+                    result.push(escaped);
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Parse a concat list, checking that every referenced file exists
+fn parse_concat_list(concat_path: &Path) -> Result<ConcatList, String> {
+    let contents = fs::read_to_string(concat_path)
+        .map_err(|e| format!("Failed to read concat list: {}", e))?;
+
+    // Relative entries are resolved against the list's own directory, as FFmpeg does
+    let base_dir = concat_path.parent().unwrap_or(Path::new("."));
+
+    let mut lines = Vec::new();
+    let mut needs_rewrite = false;
+    let mut missing = Vec::new();
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        let Some(raw) = trimmed.strip_prefix("file ") else {
+            lines.push(ConcatLine::Other(line.to_string()));
+            continue;
+        };
+        let raw = raw.trim();
+
+        // Prefer FFmpeg's reading of the entry; fall back to the literal text for
+        // lists written without escaping (e.g. unquoted Windows backslash paths)
+        let parsed = base_dir.join(unescape_concat_token(raw));
+        let literal = base_dir.join(raw.trim_matches('\''));
+
+        if parsed.exists() {
+            lines.push(ConcatLine::File(parsed));
+        } else if literal.exists() {
+            needs_rewrite = true;
+            lines.push(ConcatLine::File(literal));
+        } else {
+            missing.push(raw.to_string());
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(format!(
+            "Concat list references missing files: {}",
+            missing.join(", ")
+        ));
+    }
+
+    Ok(ConcatList {
+        lines,
+        needs_rewrite,
+    })
+}
+
+/// Write a validated concat list back out with every file entry re-escaped
+fn write_normalized_concat_list(concat_list: &ConcatList, path: &Path) -> Result<(), String> {
+    let mut concat_file =
+        File::create(path).map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+    for line in &concat_list.lines {
+        let written = match line {
+            ConcatLine::File(file) => writeln!(
+                concat_file,
+                "file '{}'",
+                escape_concat_path(&file.to_string_lossy())
+            ),
+            ConcatLine::Other(other) => writeln!(concat_file, "{}", other),
+        };
+        if written.is_err() {
+            return Err("Failed to write concat list".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Stitch the files referenced by an existing FFmpeg concat list
+#[tauri::command]
+async fn stitch_from_concat_file(
+    concat_path: String,
+    output_path: String,
+    bitrate: String,
+    format: OutputFormat,
+) -> StitchResult {
+    let concat_list = match parse_concat_list(Path::new(&concat_path)) {
+        Ok(list) => list,
+        Err(e) => return stitch_error(e),
+    };
+
+    let clip_count = concat_list.files().count();
+    if clip_count == 0 {
+        return stitch_error("Concat list does not reference any files");
+    }
+
+    let encoder_args = match format {
+        OutputFormat::Mp4 => None,
+        audio_format => {
+            match audio_encoder_args(
+                audio_format,
+                &bitrate,
+                OpusApplication::default(),
+                None,
+                &output_path,
+            ) {
+                Ok(args) => Some(args),
+                Err(e) => return stitch_error(e),
+            }
+        }
+    };
+
+    // Feed the user's list directly unless some entries had to be re-escaped
+    let rewritten_path = unique_temp_path("ffmpeg_user_concat_list.txt");
+    let list_path = if concat_list.needs_rewrite {
+        if let Err(e) = write_normalized_concat_list(&concat_list, &rewritten_path) {
+            return stitch_error(e);
+        }
+        rewritten_path.clone()
+    } else {
+        PathBuf::from(&concat_path)
+    };
+
+    let args = match encoder_args {
+        Some(encoder_args) => audio_args(&list_path, &[], &encoder_args, &output_path),
+        None => {
+            let video_config = VideoConfig::default();
+            let keyframe_interval = match keyframe_interval_frames(&video_config) {
+                Ok(frames) => frames,
+                Err(e) => return stitch_error(e),
+            };
+            let aac_args = match aac_encoder_args(AacSettings::default(), &bitrate) {
+                Ok(aac) => aac.args,
+                Err(e) => return stitch_error(e),
+            };
+            video_args(
+                &list_path,
+                &Mp4Metadata::default(),
+                &VideoLayers {
+                    slides: &[],
+                    overlays: &[],
+                },
+                &output_path,
+                &aac_args,
+                keyframe_interval,
+                &video_config,
+            )
+        }
+    };
+    let mut result = run_ffmpeg(&args, output_path);
+
+    // Clean up temp file
+    if concat_list.needs_rewrite {
+        let _ = fs::remove_file(&rewritten_path);
+    }
+
+    result.clip_count = Some(clip_count);
+    result
+}
+
+/// Get image file information using ffprobe
+#[tauri::command]
+fn get_image_info(path: String) -> ImageInfo {
+    let path_buf = PathBuf::from(&path);
+
+    // Check if file exists
+    if !path_buf.exists() {
+        return ImageInfo {
+            width: 0,
+            height: 0,
+            valid: false,
+            error: Some("File does not exist".to_string()),
+        };
+    }
+
+    // Get image dimensions using ffprobe
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=width,height",
+            "-of",
+            "csv=s=x:p=0",
+            &path,
+        ])
+        .output();
+
+    match output {
+        Ok(output) => {
+            if output.status.success() {
+                let dimensions = String::from_utf8_lossy(&output.stdout);
+                let parts: Vec<&str> = dimensions.trim().split('x').collect();
+                if parts.len() == 2 {
+                    let width: u32 = parts[0].parse().unwrap_or(0);
+                    let height: u32 = parts[1].parse().unwrap_or(0);
+                    if width > 0 && height > 0 {
+                        return ImageInfo {
+                            width,
+                            height,
+                            valid: true,
+                            error: None,
+                        };
+                    }
+                }
+                ImageInfo {
+                    width: 0,
+                    height: 0,
+                    valid: false,
+                    error: Some("Could not parse image dimensions".to_string()),
+                }
+            } else {
+                let error = String::from_utf8_lossy(&output.stderr);
+                ImageInfo {
+                    width: 0,
+                    height: 0,
+                    valid: false,
+                    error: Some(format!("FFprobe error: {}", error)),
+                }
+            }
+        }
+        Err(e) => ImageInfo {
+            width: 0,
+            height: 0,
+            valid: false,
+            error: Some(format!(
+                "Failed to run ffprobe: {}. Is FFmpeg installed?",
+                e
+            )),
+        },
+    }
+}
+
+/// Stitch multiple audio clips into a single MP4 video file with optional background image
+#[tauri::command]
+async fn stitch_video(
+    clips: Vec<AudioClip>,
+    output_path: String,
+    bitrate: String,
+    video_config: VideoConfig,
+) -> StitchResult {
+    let started = Instant::now();
+    if clips.is_empty() {
+        return stitch_error("No clips provided");
+    }
+    let clips = match order_clips(clips) {
+        Ok(clips) => clips,
+        Err(e) => return stitch_error(e),
+    };
+    let output_path = match resolve_output_path(&output_path, OutputFormat::Mp4, None) {
+        Ok(path) => path,
+        Err(e) => return stitch_error(e),
+    };
+
+    // Chapters are kept next to the output so they're usable even when not muxed
+    let chapters_path = Path::new(&output_path).with_extension("chapters.vtt");
+    let muxed_chapters = (video_config.video_chapters && video_config.mux_chapters)
+        .then_some(chapters_path.as_path());
+
+    let duration: f64 = clips.iter().map(|clip| clip.duration).sum();
+    let keyframe_interval = if video_config.minimal_motion {
+        if video_config.scrolling_log {
+            return stitch_error("minimal_motion can't be combined with scrolling_log");
+        }
+        if video_config.keyframe_interval.is_some() {
+            return stitch_error(
+                "minimal_motion places its own keyframe; leave keyframe_interval unset",
+            );
+        }
+        // A GOP longer than the whole video at 1fps leaves only the opening keyframe
+        duration.ceil() as u32 + 1
+    } else {
+        match keyframe_interval_frames(&video_config) {
+            Ok(frames) => frames,
+            Err(e) => return stitch_error(e),
+        }
+    };
+    if let Some(rate_control) = &video_config.rate_control {
+        if let Err(e) = rate_control.validate(video_config.aac_vbr) {
+            return stitch_error(e);
+        }
+    }
+    // Also the audio export's default, and what the manifest records
+    let bitrate = match &video_config.rate_control {
+        Some(rate_control) => rate_control.audio_bitrate(&bitrate).to_string(),
+        None => bitrate,
+    };
+    let aac = match aac_encoder_args(AacSettings::from_config(&video_config), &bitrate) {
+        Ok(aac) => aac,
+        Err(e) => return stitch_error(e),
+    };
+    let mut aac_args = aac.args;
+    // Also applied to the audio export below
+    let creation_time = match creation_time_args(
+        video_config.creation_time.as_deref(),
+        video_config.creation_time_from_clips,
+        &clips,
+    ) {
+        Ok(args) => args,
+        Err(e) => return stitch_error(e),
+    };
+    aac_args.extend_from_slice(&creation_time);
+    // A location tag is dropped from MP4 unless metadata is written as free-form keys
+    let metadata_keys = match location_args(
+        video_config.location.as_ref(),
+        video_config.location_from_clips,
+        &clips,
+        OutputFormat::Mp4,
+    ) {
+        Ok(args) => {
+            let tagged = !args.is_empty();
+            aac_args.extend(args);
+            tagged
+        }
+        Err(e) => return stitch_error(e),
+    };
+    if let Err(e) = validate_threads(video_config.threads) {
+        return stitch_error(e);
+    }
+    if let Some(color) = &video_config.pad_color {
+        if let Err(e) = validate_color(color) {
+            return stitch_error(e);
+        }
+    }
+    // Same limits as preview_framing, whose previews should match the render
+    let (width, height) = video_frame_size(&video_config);
+    if width == 0 || height == 0 || !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return stitch_error(format!(
+            "Video size {}x{} must be non-zero and even",
+            width, height
+        ));
+    }
+    if let Some(watermark) = &video_config.watermark {
+        if let Err(e) = watermark.validate() {
+            return stitch_error(e);
+        }
+    }
+    if let Some(encrypt) = &video_config.encrypt {
+        if let Err(e) = encrypt.validate() {
+            return stitch_error(e);
+        }
+    }
+    let slides = match &video_config.background_slideshow {
+        Some(images) => {
+            if let Err(e) = validate_slideshow(images, &video_config) {
+                return stitch_error(e);
+            }
+            slideshow_slides(images, &clips, video_config.slideshow_interval)
+        }
+        None => Vec::new(),
+    };
+    let _priority = PriorityGuard::set(video_config.low_priority);
+
+    // Check the audio export up front so a bad format doesn't cost a full video render
+    let audio_export = match &video_config.also_export_audio {
+        Some(export) => {
+            let extension = match export.format {
+                OutputFormat::Mp3 => "mp3",
+                OutputFormat::Opus => "opus",
+                OutputFormat::Mp4 => {
+                    return stitch_error("The exported audio must be MP3 or Opus");
+                }
+            };
+            let audio_path = Path::new(&output_path)
+                .with_extension(extension)
+                .to_string_lossy()
+                .to_string();
+            let audio_bitrate = export.bitrate.as_deref().unwrap_or(&bitrate);
+            match audio_encoder_args(
+                export.format,
+                audio_bitrate,
+                OpusApplication::default(),
+                None,
+                &audio_path,
+            ) {
+                Ok(mut encoder_args) => {
+                    encoder_args.extend_from_slice(&creation_time);
+                    match location_args(
+                        video_config.location.as_ref(),
+                        video_config.location_from_clips,
+                        &clips,
+                        export.format,
+                    ) {
+                        Ok(args) => encoder_args.extend(args),
+                        Err(e) => return stitch_error(e),
+                    }
+                    Some((audio_path, encoder_args))
+                }
+                Err(e) => return stitch_error(e),
+            }
+        }
+        None => None,
+    };
+
+    let mut overlay_filters = Vec::new();
+    let scrolling_log_path = unique_temp_path("ffmpeg_scrolling_log.txt");
+    if video_config.scrolling_log {
+        if let Some(speed) = video_config.scroll_speed {
+            if speed <= 0.0 {
+                return stitch_error("Scroll speed must be greater than zero");
+            }
+        }
+        let total_duration = clip_placements(&clips, 0.0)
+            .last()
+            .map(|placement| placement.end)
+            .unwrap_or(0.0);
+        overlay_filters.push(scrolling_log_filter(
+            &scrolling_log_path,
+            total_duration,
+            video_config.scroll_speed,
+        ));
+    }
+    if video_config.clip_counter {
+        let font_size = video_config
+            .counter_font_size
+            .unwrap_or(DEFAULT_COUNTER_FONT_SIZE);
+        if font_size == 0 {
+            return stitch_error("Counter font size must be greater than zero");
+        }
+        overlay_filters.extend(clip_counter_filters(
+            &clips,
+            video_config.counter_position,
+            font_size,
+        ));
+    }
+
+    // Asked only once the request has fully checked out, so a yes can't be followed by a
+    // settings error, and before anything is written. The chapter file and audio export
+    // are replaced along with the video, so each is asked about in turn.
+    if let Some(prompt) = &video_config.confirm_overwrite {
+        let chapters = video_config
+            .video_chapters
+            .then(|| chapters_path.to_string_lossy().to_string());
+        let export = audio_export.as_ref().map(|(path, _)| path.clone());
+        for path in [Some(output_path.clone()), chapters, export]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(result) = confirm_overwrite(&path, prompt) {
+                return result;
+            }
+        }
+    }
+    if video_config.encrypt.is_some() {
+        let prompt = video_config.confirm_overwrite.as_ref();
+        if let Some(result) = confirm_encrypted_overwrite(&output_path, prompt) {
+            return result;
+        }
+    }
+    if video_config.video_chapters {
+        if let Err(e) = write_vtt_chapters(&clips, &chapters_path) {
+            return stitch_error(e);
+        }
+    }
+    if video_config.scrolling_log {
+        if let Err(e) = write_scrolling_log(&clips, &scrolling_log_path) {
+            return stitch_error(e);
+        }
+    }
+
+    // Written here rather than through run_with_concat_list so the audio export can
+    // decode the same list
+    let concat_file_path = unique_temp_path("ffmpeg_video_concat_list.txt");
+    if let Err(e) = write_concat_list(clips.iter().map(|clip| &clip.path), &concat_file_path) {
+        return stitch_error(e);
+    }
+
+    let render_path = if video_config.atomic_write {
+        partial_output_path(&output_path)
+    } else {
+        output_path.clone()
+    };
+    let args = video_args(
+        &concat_file_path,
+        &Mp4Metadata {
+            chapters_path: muxed_chapters,
+            metadata_keys,
+        },
+        &VideoLayers {
+            slides: &slides,
+            overlays: &overlay_filters,
+        },
+        &render_path,
+        &aac_args,
+        keyframe_interval,
+        &video_config,
+    );
+    // video_args pins the output to this rate, so the frame count tracks the render
+    let progress = ProgressPlan {
+        output_path: output_path.clone(),
+        duration,
+        fps: Some(video_frame_rate(&video_config) as f64),
+    };
+    let mut result = run_ffmpeg_with_progress(&args, render_path.clone(), Some(progress));
+    if video_config.atomic_write {
+        commit_partial_output(&mut result, &render_path, &output_path);
+    }
+    result.audio_encoder = Some(aac.encoder.to_string());
+    result.warnings.extend(aac.warnings);
+
+    if let (true, Some((audio_path, encoder_args))) = (result.success, audio_export) {
+        let args = audio_args(&concat_file_path, &[], &encoder_args, &audio_path);
+        let audio_result = run_ffmpeg(&args, audio_path);
+        if audio_result.success {
+            result.audio_output_path = audio_result.output_path;
+        } else {
+            result.warnings.push(format!(
+                "Video rendered, but the audio export failed: {}",
+                audio_result.error.unwrap_or_default()
+            ));
+        }
+    }
+
+    if let Some(encrypt) = &video_config.encrypt {
+        encrypt_output(&mut result, &output_path, encrypt);
+        if let (true, Some(audio_path)) = (result.success, result.audio_output_path.clone()) {
+            // The export gets its own result, so a failure here only costs the export
+            let mut export = StitchResult {
+                success: true,
+                ..Default::default()
+            };
+            let encrypted = encrypt_output(&mut export, &audio_path, encrypt);
+            match export.error {
+                None => result.audio_output_path = Some(encrypted),
+                Some(e) => {
+                    result.audio_output_path = None;
+                    result
+                        .warnings
+                        .push(format!("Audio export was not kept: {}", e));
+                }
+            }
+        }
+    }
+
+    // Clean up temp file
+    let _ = fs::remove_file(&concat_file_path);
+    if result.cancelled && video_config.video_chapters {
+        // The sidecar was written ahead of a video that no longer exists
+        let _ = fs::remove_file(&chapters_path);
+    } else if video_config.video_chapters {
+        let path = chapters_path.to_string_lossy().to_string();
+        result.chapters_path = match (&video_config.encrypt, result.success) {
+            (Some(encrypt), true) => encrypt_sidecar(&mut result, &path, encrypt),
+            (Some(_), false) => {
+                // Nothing encrypted to go with it, and it mustn't stay behind in plain text
+                let _ = fs::remove_file(&path);
+                None
+            }
+            (None, _) => Some(path),
+        };
+    }
+    if video_config.scrolling_log {
+        let _ = fs::remove_file(&scrolling_log_path);
+    }
+    if result.success && !slides.is_empty() {
+        result.slideshow_images = Some(slides.len());
+    }
+    record_render_stats(&mut result, started, duration);
+
+    if video_config.write_manifest {
+        let encrypt = video_config.encrypt.clone();
+        let manifest = StitchManifest {
+            format: OutputFormat::Mp4,
+            clips,
+            output_path,
+            bitrate,
+            audio_config: None,
+            video_config: Some(video_config),
+        };
+        attach_manifest(&mut result, &manifest);
+        if let (Some(encrypt), Some(path)) = (encrypt, result.manifest_path.clone()) {
+            result.manifest_path = encrypt_sidecar(&mut result, &path, &encrypt);
+        }
+    }
+
+    result
+}
+
+/// Frame rate of the animated waveform video
+const WAVEFORM_FRAME_RATE: u32 = 25;
+
+/// Format of a file's first video stream, as far as concat compatibility is concerned
+#[derive(Debug, Clone, Default, PartialEq)]
+struct VideoStreamFormat {
+    codec: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    pix_fmt: Option<String>,
+    frame_rate: Option<String>, // As ffprobe reports it, e.g. "30000/1001"
+}
+
+/// Probe the format of a file's first video stream
+fn probe_video_format(path: &str) -> Result<VideoStreamFormat, String> {
+    let output = ffprobe_command()
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=codec_name,width,height,pix_fmt,r_frame_rate",
+            "-of",
+            "default=noprint_wrappers=1",
+            path,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFprobe error: {}", error));
+    }
+
+    let mut format = VideoStreamFormat::default();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() || value == "N/A" || value == "0/0" {
+            continue;
+        }
+        match key {
+            "codec_name" => format.codec = Some(value.to_string()),
+            "width" => format.width = value.parse().ok(),
+            "height" => format.height = value.parse().ok(),
+            "pix_fmt" => format.pix_fmt = Some(value.to_string()),
+            "r_frame_rate" => format.frame_rate = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    if format.codec.is_none() {
+        return Err(format!("{} has no video stream", path));
+    }
+
+    Ok(format)
+}
+
+/// concat_plan for video files. The demuxer copies streams without rescaling or retiming
+/// them, so every clip's video has to match the first clip's as well as its audio.
+fn video_concat_plan(clips: &[AudioClip]) -> ConcatPlan {
+    let mut reference: Option<VideoStreamFormat> = None;
+
+    for (index, clip) in clips.iter().enumerate() {
+        let format = match probe_video_format(&clip.path) {
+            Ok(format) => format,
+            Err(e) => {
+                return ConcatPlan {
+                    demuxer_compatible: false,
+                    mismatch_index: Some(index),
+                    mismatch_property: None,
+                    error: Some(e),
+                }
+            }
+        };
+
+        let Some(reference) = &reference else {
+            reference = Some(format);
+            continue;
+        };
+
+        let mismatch = if format.codec != reference.codec {
+            Some("video codec")
+        } else if (format.width, format.height) != (reference.width, reference.height) {
+            Some("resolution")
+        } else if format.pix_fmt != reference.pix_fmt {
+            Some("pixel format")
+        } else if format.frame_rate != reference.frame_rate {
+            Some("frame rate")
+        } else {
+            None
+        };
+        if let Some(property) = mismatch {
+            return ConcatPlan {
+                demuxer_compatible: false,
+                mismatch_index: Some(index),
+                mismatch_property: Some(property.to_string()),
+                error: None,
+            };
+        }
+    }
+
+    concat_plan(clips)
+}
+
+/// Decode every clip and conform it to the first clip's size and frame rate (letterboxed
+/// where the aspect differs), then join them through the concat filter. A clip without
+/// audio gets silence, so the audio stays in step with the picture.
+fn video_filter_args(
+    clips: &[AudioClip],
+    reference: &VideoStreamFormat,
+    audio_encoder_args: &[String],
+    output_path: &str,
+) -> Result<Vec<String>, String> {
+    // yuv420p needs even dimensions
+    let width = reference.width.unwrap_or(1920) & !1;
+    let height = reference.height.unwrap_or(1080) & !1;
+    let frame_rate = reference.frame_rate.as_deref().unwrap_or("30");
+
+    let mut args: Vec<String> = vec!["-y".to_string()]; // Overwrite output
+    let mut graph = String::new();
+    for (i, clip) in clips.iter().enumerate() {
+        args.extend_from_slice(&["-i".to_string(), clip.path.clone()]);
+        graph.push_str(&format!(
+            "[{i}:v]scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1,fps={fps},format=yuv420p[v{i}];",
+            i = i,
+            w = width,
+            h = height,
+            fps = frame_rate
+        ));
+        if probe_stream_format(&clip.path)?.codec.is_some() {
+            graph.push_str(&format!(
+                "[{}:a]aresample=48000,aformat=sample_fmts=fltp:channel_layouts=stereo[a{}];",
+                i, i
+            ));
+        } else {
+            graph.push_str(&format!(
+                "anullsrc=r=48000:cl=stereo,atrim=duration={}[a{}];",
+                clip.duration, i
+            ));
+        }
+    }
+    for i in 0..clips.len() {
+        graph.push_str(&format!("[v{}][a{}]", i, i));
+    }
+    graph.push_str(&format!("concat=n={}:v=1:a=1[v][a]", clips.len()));
+
+    args.extend_from_slice(&[
+        "-filter_complex".to_string(),
+        graph,
+        "-map".to_string(),
+        "[v]".to_string(),
+        "-map".to_string(),
+        "[a]".to_string(),
+        "-c:v".to_string(),
+        "libx264".to_string(),
+        "-crf".to_string(),
+        "18".to_string(),
+        "-preset".to_string(),
+        "medium".to_string(),
+    ]);
+    args.extend_from_slice(audio_encoder_args);
+    args.extend_from_slice(&[
+        "-movflags".to_string(),
+        "+faststart".to_string(),
+        output_path.to_string(),
+    ]);
+    Ok(args)
+}
+
+/// Join video files end to end into one MP4. With prefer_copy, clips whose streams all
+/// match the first clip's are joined by the concat demuxer without re-encoding, and
+/// anything else falls back to the re-encoding filter path. Without it every clip is
+/// re-encoded even when they'd copy cleanly, e.g. to give the output one consistent
+/// keyframe structure. `bitrate` sets the AAC audio when re-encoding.
+#[tauri::command]
+async fn stitch_video_clips(
+    clips: Vec<AudioClip>,
+    output_path: String,
+    bitrate: String,
+    prefer_copy: bool,
+) -> StitchResult {
+    let started = Instant::now();
+    if clips.is_empty() {
+        return stitch_error("No clips provided");
+    }
+    let clips = match order_clips(clips) {
+        Ok(clips) => clips,
+        Err(e) => return stitch_error(e),
+    };
+    let output_path = match resolve_output_path(&output_path, OutputFormat::Mp4, None) {
+        Ok(path) => path,
+        Err(e) => return stitch_error(e),
+    };
+
+    let reason = if prefer_copy {
+        let plan = video_concat_plan(&clips);
+        match (
+            plan.demuxer_compatible,
+            plan.mismatch_index,
+            plan.mismatch_property,
+            plan.error,
+        ) {
+            (true, ..) => None,
+            (false, Some(index), Some(property), _) => Some(format!(
+                "Clip {} has a different {} than the first clip, so every clip was re-encoded",
+                index + 1,
+                property
+            )),
+            (false, _, _, Some(e)) => return stitch_error(e),
+            _ => Some("The clips couldn't be compared, so every clip was re-encoded".to_string()),
+        }
+    } else {
+        Some("prefer_copy is off, so every clip was re-encoded".to_string())
+    };
+    let stream_copy = reason.is_none();
+
+    let duration: f64 = clips.iter().map(|clip| clip.duration).sum();
+    let progress = ProgressPlan {
+        output_path: output_path.clone(),
+        duration,
+        fps: None,
+    };
+    let mut result = if stream_copy {
+        run_with_concat_list(
+            &clips,
+            "ffmpeg_video_concat_list.txt",
+            output_path.clone(),
+            |concat_file_path| {
+                vec![
+                    "-y".to_string(),
+                    "-f".to_string(),
+                    "concat".to_string(),
+                    "-safe".to_string(),
+                    "0".to_string(),
+                    "-i".to_string(),
+                    concat_file_path.to_string_lossy().to_string(),
+                    "-map".to_string(),
+                    "0:v:0".to_string(),
+                    "-map".to_string(),
+                    "0:a:0?".to_string(),
+                    "-c".to_string(),
+                    "copy".to_string(),
+                    "-movflags".to_string(),
+                    "+faststart".to_string(),
+                    output_path.clone(),
+                ]
+            },
+        )
+    } else {
+        let args = aac_encoder_args(AacSettings::default(), &bitrate).and_then(|aac| {
+            let reference = probe_video_format(&clips[0].path)?;
+            video_filter_args(&clips, &reference, &aac.args, &output_path)
+        });
+        match args {
+            Ok(args) => run_ffmpeg_with_progress(&args, output_path.clone(), Some(progress)),
+            Err(e) => stitch_error(e),
+        }
+    };
+
+    result.clip_count = Some(clips.len());
+    result.concat_method = Some(if stream_copy { "demuxer" } else { "filter" }.to_string());
+    result.concat_reason = Some(reason.unwrap_or_else(|| {
+        "All clips share codecs, resolution, and frame rate, so they were copied as-is".to_string()
+    }));
+    result.stream_copy = stream_copy;
+    if result.success {
+        result.duration = Some(duration);
+    }
+    record_render_stats(&mut result, started, duration);
+    result
+}
+
+/// Total length past which a waveform render gets a warning about its CPU cost, in seconds
+const WAVEFORM_LONG_RENDER_SECONDS: f64 = 30.0 * 60.0;
+
+/// Stitch clips into an MP4 whose picture is the audio's own waveform, animated in sync.
+/// Unlike stitch_video's still background every frame is drawn and encoded, so this is
+/// far slower for long files.
+#[tauri::command]
+async fn stitch_waveform_video(
+    clips: Vec<AudioClip>,
+    output_path: String,
+    bitrate: String,
+    color: String,
+    size: (u32, u32),
+    mode: Option<WaveformMode>,
+) -> StitchResult {
+    if clips.is_empty() {
+        return stitch_error("No clips provided");
+    }
+    let clips = match order_clips(clips) {
+        Ok(clips) => clips,
+        Err(e) => return stitch_error(e),
+    };
+    let (width, height) = size;
+    if width == 0 || height == 0 || width % 2 != 0 || height % 2 != 0 {
+        return stitch_error("Waveform size must be non-zero and even (required by yuv420p)");
+    }
+
+    let waveform = format!(
+        "[0:a]showwaves=s={}x{}:mode={}:colors={}:rate={},format=yuv420p[v]",
+        width,
+        height,
+        mode.unwrap_or_default().as_str(),
+        escape_filter_value(&color),
+        WAVEFORM_FRAME_RATE
+    );
+
+    let mut result = run_with_concat_list(
+        &clips,
+        "ffmpeg_waveform_concat_list.txt",
+        output_path.clone(),
+        |concat_file_path| {
+            vec![
+                "-y".to_string(),
+                "-f".to_string(),
+                "concat".to_string(),
+                "-safe".to_string(),
+                "0".to_string(),
+                "-i".to_string(),
+                concat_file_path.to_string_lossy().to_string(),
+                "-filter_complex".to_string(),
+                waveform,
+                "-map".to_string(),
+                "[v]".to_string(),
+                "-map".to_string(),
+                "0:a".to_string(),
+                "-c:v".to_string(),
+                "libx264".to_string(),
+                "-preset".to_string(),
+                "veryfast".to_string(),
+                "-c:a".to_string(),
+                "aac".to_string(),
+                "-b:a".to_string(),
+                bitrate,
+                "-movflags".to_string(),
+                "+faststart".to_string(),
+                output_path,
+            ]
+        },
+    );
+
+    let total_duration: f64 = clips.iter().map(|clip| clip.duration).sum();
+    if total_duration > WAVEFORM_LONG_RENDER_SECONDS {
+        result.warnings.push(format!(
+            "Waveform video is {:.0} minutes long; animated waveforms are CPU-heavy, and a still background renders much faster at this length",
+            total_duration / 60.0
+        ));
+    }
+
+    result
+}
+
+/// Stitch clips into an MP4 showing the waveform on top and a scrolling spectrogram below,
+/// both drawn live from the joined audio. Two visualizations per frame make this even
+/// heavier than stitch_waveform_video.
+#[tauri::command]
+async fn stitch_spectrogram_video(
+    clips: Vec<AudioClip>,
+    output_path: String,
+    bitrate: String,
+    size: (u32, u32),
+    style: Option<SplitScreenStyle>,
+) -> StitchResult {
+    if clips.is_empty() {
+        return stitch_error("No clips provided");
+    }
+    let clips = match order_clips(clips) {
+        Ok(clips) => clips,
+        Err(e) => return stitch_error(e),
+    };
+    let style = style.unwrap_or_default();
+    let (width, height) = size;
+    if width == 0 || height < 4 || !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return stitch_error(
+            "Video size must be even, with a height of at least 4 (required by yuv420p)",
+        );
+    }
+    if !(style.split_ratio > 0.0 && style.split_ratio < 1.0) {
+        return stitch_error("split_ratio must be between 0 and 1");
+    }
+    if !SPECTRUM_COLOR_MODES.contains(&style.spectrum_color.as_str()) {
+        return stitch_error(format!(
+            "Unknown spectrum color '{}' (expected one of {})",
+            style.spectrum_color,
+            SPECTRUM_COLOR_MODES.join(", ")
+        ));
+    }
+    if let Err(e) = validate_color(&style.waveform_color) {
+        return stitch_error(e);
+    }
+
+    // Both halves need even heights for yuv420p, and each at least one chroma row
+    let top = ((height as f64 * style.split_ratio / 2.0).round() as u32 * 2).clamp(2, height - 2);
+    let bottom = height - top;
+    let graph = format!(
+        "[0:a]asplit=2[wa][sa];\
+         [wa]showwaves=s={w}x{top}:mode={mode}:colors={color}:rate={fps},format=yuv420p[top];\
+         [sa]showspectrum=s={w}x{bottom}:slide=scroll:color={scheme},fps={fps},format=yuv420p[bottom];\
+         [top][bottom]vstack[v]",
+        w = width,
+        top = top,
+        bottom = bottom,
+        mode = style.waveform_mode.as_str(),
+        color = escape_filter_value(&style.waveform_color),
+        scheme = style.spectrum_color,
+        fps = WAVEFORM_FRAME_RATE
+    );
+
+    let mut result = run_with_concat_list(
+        &clips,
+        "ffmpeg_spectrogram_concat_list.txt",
+        output_path.clone(),
+        |concat_file_path| {
+            vec![
+                "-y".to_string(),
+                "-f".to_string(),
+                "concat".to_string(),
+                "-safe".to_string(),
+                "0".to_string(),
+                "-i".to_string(),
+                concat_file_path.to_string_lossy().to_string(),
+                "-filter_complex".to_string(),
+                graph,
+                "-map".to_string(),
+                "[v]".to_string(),
+                "-map".to_string(),
+                "0:a".to_string(),
+                "-c:v".to_string(),
+                "libx264".to_string(),
+                "-preset".to_string(),
+                "veryfast".to_string(),
+                "-c:a".to_string(),
+                "aac".to_string(),
+                "-b:a".to_string(),
+                bitrate,
+                "-movflags".to_string(),
+                "+faststart".to_string(),
+                output_path,
+            ]
+        },
+    );
+
+    let total_duration: f64 = clips.iter().map(|clip| clip.duration).sum();
+    if total_duration > WAVEFORM_LONG_RENDER_SECONDS {
+        result.warnings.push(format!(
+            "Spectrogram video is {:.0} minutes long; drawing a waveform and spectrogram for every frame is CPU-heavy",
+            total_duration / 60.0
+        ));
+    }
+
+    result
+}
+
+/// Render a grid image of every clip's waveform, each cell labelled with the clip name.
+/// Cells are drawn one clip at a time to temp PNGs, then tiled in clip order.
+#[tauri::command]
+async fn generate_waveform_montage(
+    clips: Vec<AudioClip>,
+    output_path: String,
+    columns: u32,
+    cell_size: (u32, u32),
+) -> StitchResult {
+    if clips.is_empty() {
+        return stitch_error("No clips provided");
+    }
+    let clips = match order_clips(clips) {
+        Ok(clips) => clips,
+        Err(e) => return stitch_error(e),
+    };
+    if columns == 0 {
+        return stitch_error("Montage needs at least one column");
+    }
+    let (width, height) = cell_size;
+    if width == 0 || height == 0 {
+        return stitch_error("Cell size must be non-zero");
+    }
+
+    let temp_dir = std::env::temp_dir();
+    let run_id = temp_run_id();
+    let cell_path =
+        |i: usize| temp_dir.join(format!("ffmpeg_montage_cell_{}_{:04}.png", run_id, i));
+    let cleanup = |count: usize| {
+        for i in 0..count {
+            let _ = fs::remove_file(cell_path(i));
+        }
+    };
+
+    let font_size = (height / 8).max(12);
+    for (i, clip) in clips.iter().enumerate() {
+        // drawtext expands %{...} sequences, so a literal percent sign is doubled
+        let label: String = clip
+            .name
+            .chars()
+            .take(MAX_SCROLLING_LOG_NAME_CHARS)
+            .collect::<String>()
+            .replace('%', "%%");
+        let cell = format!(
+            "[0:a]showwavespic=s={}x{}:colors=white,drawtext=text={}:fontcolor=white:fontsize={}:box=1:boxcolor=black@0.5:x=4:y=4",
+            width,
+            height,
+            escape_filter_value(&label),
+            font_size
+        );
+        let rendered = run_ffmpeg_task(&[
+            "-y".to_string(),
             "-i".to_string(),
-            image_path.clone(),
+            clip.path.clone(),
+            "-filter_complex".to_string(),
+            cell,
+            "-frames:v".to_string(),
+            "1".to_string(),
+            cell_path(i).to_string_lossy().to_string(),
         ]);
+        if let Err(e) = rendered {
+            cleanup(i + 1);
+            return stitch_error(format!(
+                "Failed to draw waveform for '{}': {}",
+                clip.name, e
+            ));
+        }
+    }
+
+    // tile flushes a partly filled last row at end of input, padding it with the background
+    let rows = clips.len().div_ceil(columns as usize);
+    let args = vec![
+        "-y".to_string(),
+        "-framerate".to_string(),
+        "1".to_string(),
+        "-start_number".to_string(),
+        "0".to_string(),
+        "-i".to_string(),
+        temp_dir
+            .join(format!("ffmpeg_montage_cell_{}_%04d.png", run_id))
+            .to_string_lossy()
+            .to_string(),
+        "-vf".to_string(),
+        format!("tile={}x{}:padding=4:color=black", columns, rows),
+        "-frames:v".to_string(),
+        "1".to_string(),
+        output_path.clone(),
+    ];
+    let mut result = run_ffmpeg(&args, output_path);
+    cleanup(clips.len());
+
+    result.clip_count = Some(clips.len());
+    result
+}
+
+/// Re-run the stitch recorded in a manifest, optionally writing to a different output path
+#[tauri::command]
+async fn rerun_from_manifest(manifest_path: String, output_path: Option<String>) -> StitchResult {
+    let contents = match fs::read_to_string(&manifest_path) {
+        Ok(contents) => contents,
+        Err(e) => return stitch_error(format!("Failed to read manifest: {}", e)),
+    };
+    let manifest: StitchManifest = match serde_json::from_str(&contents) {
+        Ok(manifest) => manifest,
+        Err(e) => return stitch_error(format!("Invalid manifest: {}", e)),
+    };
+
+    // Refuse to produce a different output than the original if any clip has moved
+    let moved: Vec<&str> = manifest
+        .clips
+        .iter()
+        .filter(|clip| !Path::new(&clip.path).exists())
+        .map(|clip| clip.path.as_str())
+        .collect();
+    if !moved.is_empty() {
+        return stitch_error(format!(
+            "Clips are no longer at their recorded paths: {}",
+            moved.join(", ")
+        ));
+    }
+
+    let mut manifest = manifest;
+    if let Some(output_path) = output_path {
+        manifest.output_path = output_path;
+    }
+    run_stitch(manifest).await
+}
+
+/// Run the stitch described by a manifest through the matching stitch command
+async fn run_stitch(manifest: StitchManifest) -> StitchResult {
+    match manifest.format {
+        OutputFormat::Mp4 => {
+            stitch_video(
+                manifest.clips,
+                manifest.output_path,
+                manifest.bitrate,
+                manifest.video_config.unwrap_or_default(),
+            )
+            .await
+        }
+        OutputFormat::Mp3 | OutputFormat::Opus => {
+            stitch_audio(
+                manifest.clips,
+                manifest.output_path,
+                manifest.bitrate,
+                manifest.audio_config,
+            )
+            .await
+        }
+    }
+}
+
+/// Build the pan filter that folds `channels` input channels to mono
+fn downmix_filter(mode: &DownmixMode, channels: u32) -> Result<String, String> {
+    let terms: Vec<String> = match mode {
+        DownmixMode::Sum => {
+            // Averaged rather than summed so correlated channels can't clip
+            let weight = 1.0 / channels as f64;
+            (0..channels)
+                .map(|c| format!("{:.4}*c{}", weight, c))
+                .collect()
+        }
+        DownmixMode::LeftOnly => vec!["c0".to_string()],
+        DownmixMode::RightOnly => {
+            if channels < 2 {
+                return Err("RightOnly needs at least two input channels".to_string());
+            }
+            vec!["c1".to_string()]
+        }
+        DownmixMode::Pan { weights } => {
+            if weights.len() != channels as usize {
+                return Err(format!(
+                    "Expected {} pan weights (one per channel), got {}",
+                    channels,
+                    weights.len()
+                ));
+            }
+            weights
+                .iter()
+                .enumerate()
+                .map(|(c, weight)| format!("{}*c{}", weight, c))
+                .collect()
+        }
+    };
+
+    Ok(format!("pan=mono|c0={}", terms.join("+")))
+}
+
+/// Fold a multichannel file down to mono with an explicit channel mix, instead of the
+/// blind `-ac 1` sum that can cancel out-of-phase voice
+#[tauri::command]
+async fn downmix_to_mono(input: String, output: String, mode: DownmixMode) -> StitchResult {
+    let channels = match probe_stream_format(&input) {
+        Ok(StreamFormat {
+            channels: Some(channels),
+            ..
+        }) => channels,
+        Ok(_) => return stitch_error("Could not determine the input's channel count"),
+        Err(e) => return stitch_error(e),
+    };
+
+    let filter = match downmix_filter(&mode, channels) {
+        Ok(filter) => filter,
+        Err(e) => return stitch_error(e),
+    };
+
+    let args = vec![
+        "-y".to_string(),
+        "-i".to_string(),
+        input,
+        "-af".to_string(),
+        filter.clone(),
+        output.clone(),
+    ];
+    let mut result = run_ffmpeg(&args, output);
+    result.downmix = Some(filter);
+    result
+}
+
+/// Run FFmpeg for a side task whose only output is files on disk
+fn run_ffmpeg_task(args: &[String]) -> Result<(), String> {
+    let output = ffmpeg_command()
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+
+    if output.status.success() {
+        Ok(())
     } else {
-        // Without image: generate black background
-        args.extend_from_slice(&[
-            "-f".to_string(),
-            "lavfi".to_string(),
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("FFmpeg error: {}", stderr))
+    }
+}
+
+/// Shortest segment worth writing; anything smaller is detector jitter at a boundary
+const MIN_SPLIT_SEGMENT_SECONDS: f64 = 0.01;
+
+/// Find silent regions with silencedetect. A silence still open at the end of the input
+/// runs to `total_duration`.
+fn detect_silences(
+    input: &str,
+    threshold_db: f64,
+    min_silence_seconds: f64,
+    total_duration: f64,
+) -> Result<Vec<TimeRange>, String> {
+    let filter = format!(
+        "silencedetect=noise={}dB:d={}",
+        threshold_db, min_silence_seconds
+    );
+    let output = ffmpeg_command()
+        .args([
+            "-hide_banner",
+            "-i",
+            input,
+            "-af",
+            &filter,
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(format!("FFmpeg error: {}", stderr));
+    }
+
+    let value_after = |line: &str, key: &str| -> Option<f64> {
+        line.split(key)
+            .nth(1)?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    };
+
+    let mut silences = Vec::new();
+    let mut open_start = None;
+    for line in stderr.lines() {
+        if let Some(start) = value_after(line, "silence_start:") {
+            open_start = Some(start.max(0.0));
+        } else if let Some(end) = value_after(line, "silence_end:") {
+            let start = open_start.take().unwrap_or(0.0);
+            silences.push(TimeRange { start, end });
+        }
+    }
+    if let Some(start) = open_start {
+        silences.push(TimeRange {
+            start,
+            end: total_duration,
+        });
+    }
+
+    Ok(silences)
+}
+
+/// What counts as dead air at a clip's edge for trim_inter_clip_silence: this quiet, for
+/// at least this long
+const EDGE_SILENCE_THRESHOLD_DB: f64 = -50.0;
+const EDGE_SILENCE_MIN_SECONDS: f64 = 0.2;
+/// Dead air left either side of a trimmed edge, so speech isn't cut at the detector's
+/// onset, and how close to the clip's ends a silence must reach to count as an edge
+const EDGE_SILENCE_KEEP_SECONDS: f64 = 0.05;
+const EDGE_SILENCE_SLACK_SECONDS: f64 = 0.05;
+
+/// Seconds skipped at a clip's start and end
+#[derive(Debug, Clone, Copy)]
+struct EdgeTrim {
+    lead: f64,
+    trail: f64,
+}
+
+/// A clip's leading and trailing dead air, ignoring silences inside it. None when the edges
+/// are already tight, or the whole clip is silent and there's no speech to keep.
+fn detect_edge_silence(clip: &AudioClip) -> Result<Option<EdgeTrim>, String> {
+    let silences = detect_silences(
+        &clip.path,
+        EDGE_SILENCE_THRESHOLD_DB,
+        EDGE_SILENCE_MIN_SECONDS,
+        clip.duration,
+    )?;
+    let lead = silences
+        .first()
+        .filter(|silence| silence.start <= EDGE_SILENCE_SLACK_SECONDS)
+        .map_or(0.0, |silence| silence.end);
+    let trail = silences
+        .last()
+        .filter(|silence| silence.end >= clip.duration - EDGE_SILENCE_SLACK_SECONDS)
+        .map_or(0.0, |silence| clip.duration - silence.start);
+    if lead + trail >= clip.duration {
+        return Ok(None);
+    }
+
+    let trim = EdgeTrim {
+        lead: (lead - EDGE_SILENCE_KEEP_SECONDS).max(0.0),
+        trail: (trail - EDGE_SILENCE_KEEP_SECONDS).max(0.0),
+    };
+    Ok((trim.lead + trim.trail > 0.0).then_some(trim))
+}
+
+/// Find each clip's edge silence and shorten its duration to match, returning the trims by
+/// path and the seconds removed in all. Every clip is decoded once to find its edges.
+fn trim_edge_silence(clips: &mut [AudioClip]) -> Result<(HashMap<String, EdgeTrim>, f64), String> {
+    let mut trims = HashMap::new();
+    let mut removed = 0.0;
+    for clip in clips.iter_mut() {
+        let trim = detect_edge_silence(clip)
+            .map_err(|e| format!("Failed to find the silence in '{}': {}", clip.name, e))?;
+        if let Some(trim) = trim {
+            clip.duration -= trim.lead + trim.trail;
+            removed += trim.lead + trim.trail;
+            trims.insert(clip.path.clone(), trim);
+        }
+    }
+    Ok((trims, removed))
+}
+
+/// Split a recording into separate files at its silences. With `detect_only` nothing is
+/// written, so the regions can be previewed and the thresholds tuned first.
+#[tauri::command]
+async fn split_on_silence(
+    input: String,
+    output_dir: String,
+    config: SilenceSplitConfig,
+) -> Result<SilenceSplit, String> {
+    if !(-100.0..=0.0).contains(&config.threshold_db) {
+        return Err("Silence threshold must be between -100 and 0 dB".to_string());
+    }
+    if config.min_silence_seconds <= 0.0 {
+        return Err("Minimum silence duration must be greater than zero".to_string());
+    }
+    if config.merge_short_gaps.is_some_and(|gap| gap < 0.0) {
+        return Err("merge_short_gaps cannot be negative".to_string());
+    }
+
+    let info = get_audio_info(input.clone());
+    if !info.valid {
+        return Err(info.error.unwrap_or_else(|| "Invalid input".to_string()));
+    }
+
+    let silences = detect_silences(
+        &input,
+        config.threshold_db,
+        config.min_silence_seconds,
+        info.duration,
+    )?;
+
+    // Segments are the stretches between the silences long enough to split on
+    let merge_gap = config.merge_short_gaps.unwrap_or(0.0);
+    let mut segments = Vec::new();
+    let mut cursor = 0.0;
+    for silence in silences
+        .iter()
+        .filter(|silence| silence.end - silence.start >= merge_gap)
+    {
+        if silence.start - cursor >= MIN_SPLIT_SEGMENT_SECONDS {
+            segments.push(SplitSegment {
+                start: cursor,
+                end: silence.start,
+                path: None,
+            });
+        }
+        cursor = silence.end;
+    }
+    if info.duration - cursor >= MIN_SPLIT_SEGMENT_SECONDS {
+        segments.push(SplitSegment {
+            start: cursor,
+            end: info.duration,
+            path: None,
+        });
+    }
+
+    if !config.detect_only {
+        fs::create_dir_all(&output_dir)
+            .map_err(|e| format!("Failed to create output folder: {}", e))?;
+        let input_path = Path::new(&input);
+        let stem = input_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let extension = input_path
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+
+        for (i, segment) in segments.iter_mut().enumerate() {
+            let path = Path::new(&output_dir)
+                .join(format!("{}_{:03}{}", stem, i + 1, extension))
+                .to_string_lossy()
+                .to_string();
+            // Cutting at silence doesn't need sample accuracy, so the streams are copied
+            run_ffmpeg_task(&[
+                "-y".to_string(),
+                "-ss".to_string(),
+                segment.start.to_string(),
+                "-to".to_string(),
+                segment.end.to_string(),
+                "-i".to_string(),
+                input.clone(),
+                "-c".to_string(),
+                "copy".to_string(),
+                path.clone(),
+            ])?;
+            segment.path = Some(path);
+        }
+    }
+
+    Ok(SilenceSplit { silences, segments })
+}
+
+/// Write each embedded chapter of `input` to its own file in `output_dir`, returning the
+/// paths in chapter order. Files are numbered and named from the chapter title, e.g.
+/// `03 Metro Police TG 1234.mp3`, and tagged with it. The audio is stream copied, so cuts
+/// land on the nearest packet; a video's audio is written as M4A.
+#[tauri::command]
+async fn split_by_chapters(input: String, output_dir: String) -> Result<Vec<String>, String> {
+    if !Path::new(&input).is_file() {
+        return Err(format!("File not found: {}", input));
+    }
+    let chapters = probe_chapters(&input)?;
+    if chapters.is_empty() {
+        return Err(format!("'{}' has no chapters to split at", input));
+    }
+    fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create output folder: {}", e))?;
+
+    let extension = match Path::new(&input)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .as_deref()
+    {
+        Some("mp4" | "m4v" | "mov") => "m4a".to_string(),
+        Some(ext) => ext.to_string(),
+        None => "mka".to_string(),
+    };
+    let total = chapters.len();
+    let mut paths = Vec::with_capacity(total);
+    for (i, chapter) in chapters.iter().enumerate() {
+        let title = match sanitize_filename(&chapter.text) {
+            title if title.is_empty() => format!("Chapter {}", i + 1),
+            title => title,
+        };
+        let path = Path::new(&output_dir)
+            .join(format!("{:02} {}.{}", i + 1, title, extension))
+            .to_string_lossy()
+            .to_string();
+        if same_file(&path, &input) {
+            return Err(format!("Chapter {} would overwrite the input file", i + 1));
+        }
+        run_ffmpeg_task(&[
+            "-y".to_string(),
+            "-ss".to_string(),
+            (chapter.start_ms as f64 / 1000.0).to_string(),
+            "-to".to_string(),
+            (chapter.end_ms as f64 / 1000.0).to_string(),
             "-i".to_string(),
-            "color=black:s=1920x1080:r=1".to_string(),
-        ]);
+            input.clone(),
+            "-map".to_string(),
+            "0:a:0".to_string(),
+            "-c".to_string(),
+            "copy".to_string(),
+            // Each track is one chapter, so the full list would only mislead players
+            "-map_chapters".to_string(),
+            "-1".to_string(),
+            "-metadata".to_string(),
+            format!("title={}", chapter.text),
+            "-metadata".to_string(),
+            format!("track={}/{}", i + 1, total),
+            path.clone(),
+        ])
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&path);
+        })?;
+        paths.push(path);
     }
 
-    // Add audio input
-    args.extend_from_slice(&[
-        "-f".to_string(),
-        "concat".to_string(),
-        "-safe".to_string(),
-        "0".to_string(),
+    Ok(paths)
+}
+
+/// Cut the [start, end] range out of a finished audio file, returning the new duration.
+/// The kept parts are trimmed and rejoined through the concat filter, so the cut is
+/// sample-accurate rather than snapped to the nearest packet as a stream copy would be.
+#[tauri::command]
+async fn remove_segment(
+    input: String,
+    output: String,
+    start: f64,
+    end: f64,
+) -> Result<f64, String> {
+    let info = get_audio_info(input.clone());
+    if !info.valid {
+        return Err(info.error.unwrap_or_else(|| "Invalid input".to_string()));
+    }
+    if !(0.0..end).contains(&start) || end > info.duration {
+        return Err(format!(
+            "Invalid range {:.3}-{:.3}s (the file is {:.3}s long)",
+            start, end, info.duration
+        ));
+    }
+
+    let keep_head = start > 0.0;
+    let keep_tail = end < info.duration;
+    let graph = match (keep_head, keep_tail) {
+        (true, true) => format!(
+            "[0:a]asplit=2[head][tail];\
+             [head]atrim=end={},asetpts=PTS-STARTPTS[a];\
+             [tail]atrim=start={},asetpts=PTS-STARTPTS[b];\
+             [a][b]concat=n=2:v=0:a=1[out]",
+            start, end
+        ),
+        (true, false) => format!("[0:a]atrim=end={},asetpts=PTS-STARTPTS[out]", start),
+        (false, true) => format!("[0:a]atrim=start={},asetpts=PTS-STARTPTS[out]", end),
+        (false, false) => return Err("Removing the whole file would leave nothing".to_string()),
+    };
+
+    run_ffmpeg_task(&[
+        "-y".to_string(),
+        "-i".to_string(),
+        input,
+        "-filter_complex".to_string(),
+        graph,
+        "-map".to_string(),
+        "[out]".to_string(),
+        output.clone(),
+    ])?;
+
+    let trimmed = get_audio_info(output);
+    if trimmed.valid {
+        Ok(trimmed.duration)
+    } else {
+        Err(trimmed
+            .error
+            .unwrap_or_else(|| "Could not read the trimmed file".to_string()))
+    }
+}
+
+/// Length of the sample clip each preset renders, in seconds
+const BENCHMARK_SAMPLE_SECONDS: f64 = 5.0;
+
+/// x264 presets compared by benchmark_presets, fastest first
+const BENCHMARK_PRESETS: [&str; 3] = ["veryfast", "medium", "slow"];
+
+/// Render the first few seconds of a sample clip the way stitch_video would (looped
+/// background, scaling, CRF and audio encode) with each preset, so the UI can recommend the
+/// slowest preset the machine can afford. video_config picks the background and size.
+#[tauri::command]
+async fn benchmark_presets(
+    sample_path: String,
+    bitrate: String,
+    video_config: Option<VideoConfig>,
+) -> Result<Vec<PresetBenchmark>, String> {
+    let info = get_audio_info(sample_path.clone());
+    if !info.valid {
+        return Err(info.error.unwrap_or_else(|| "Invalid sample".to_string()));
+    }
+    let sampled_seconds = info.duration.min(BENCHMARK_SAMPLE_SECONDS);
+    if sampled_seconds <= 0.0 {
+        return Err("Sample has no duration to encode".to_string());
+    }
+    let video_config = video_config.unwrap_or_default();
+    let keyframe_interval = keyframe_interval_frames(&video_config)?;
+    let aac = aac_encoder_args(AacSettings::from_config(&video_config), &bitrate)?;
+
+    let list_path = unique_temp_path("ffmpeg_benchmark_list.txt");
+    write_concat_list([&sample_path], &list_path)?;
+    let mut benchmarks = Vec::with_capacity(BENCHMARK_PRESETS.len());
+    for preset in BENCHMARK_PRESETS {
+        let output_path = unique_temp_path(&format!("ffmpeg_benchmark_{}.mp4", preset))
+            .to_string_lossy()
+            .to_string();
+        let mut args = video_args(
+            &list_path,
+            &Mp4Metadata::default(),
+            &VideoLayers {
+                slides: &[],
+                overlays: &[],
+            },
+            &output_path,
+            &aac.args,
+            keyframe_interval,
+            &video_config,
+        );
+        // Same render with the preset swapped in, cut short ahead of the output path
+        if let Some(index) = args.iter().position(|arg| arg == "-preset") {
+            args[index + 1] = preset.to_string();
+        }
+        let output_index = args.len() - 1;
+        args.splice(
+            output_index..output_index,
+            ["-t".to_string(), sampled_seconds.to_string()],
+        );
+
+        let started = std::time::Instant::now();
+        let output = ffmpeg_command().args(&args).output();
+        let seconds = started.elapsed().as_secs_f64();
+        let size = fs::metadata(&output_path).map(|m| m.len()).unwrap_or(0);
+        let _ = fs::remove_file(&output_path);
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                let _ = fs::remove_file(&list_path);
+                return Err(format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e));
+            }
+        };
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            let _ = fs::remove_file(&list_path);
+            return Err(format!("FFmpeg error: {}", stderr));
+        }
+        // The final stats line carries the total frame count
+        let frames = stderr
+            .rsplit("frame=")
+            .find_map(|rest| rest.split_whitespace().next()?.parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        benchmarks.push(PresetBenchmark {
+            preset: preset.to_string(),
+            seconds,
+            fps: if seconds > 0.0 { frames / seconds } else { 0.0 },
+            bytes_per_second: size as f64 / sampled_seconds,
+        });
+    }
+    let _ = fs::remove_file(&list_path);
+
+    Ok(benchmarks)
+}
+
+/// Write an upright copy of an image, applying its EXIF orientation to the pixels.
+/// Returns the orientation that was corrected.
+#[tauri::command]
+async fn normalize_image_orientation(
+    input: String,
+    output: String,
+) -> Result<ImageOrientation, String> {
+    let orientation = probe_image_orientation(&input)?;
+
+    let mut args = vec![
+        "-y".to_string(),
+        "-noautorotate".to_string(),
         "-i".to_string(),
-        concat_file_path.to_str().unwrap().to_string(),
+        input,
+    ];
+    if let Some(filter) = orientation.filter() {
+        args.extend_from_slice(&["-vf".to_string(), filter.to_string()]);
+    }
+    args.extend_from_slice(&[
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-q:v".to_string(),
+        "2".to_string(),
+        output,
     ]);
+    run_ffmpeg_task(&args)?;
+
+    Ok(orientation)
+}
+
+/// Render how a background image will be framed in the video as a single PNG, with the same
+/// rotation, scaling and padding filters stitch_video uses
+#[tauri::command]
+async fn preview_framing(
+    image_path: String,
+    fit_mode: ImageFitMode,
+    width: u32,
+    height: u32,
+    output_png: String,
+    pad_color: Option<String>,
+    safe_guides: Option<SafeGuides>,
+) -> Result<String, String> {
+    if !Path::new(&image_path).exists() {
+        return Err(format!("Image not found: {}", image_path));
+    }
+    // The video encodes yuv420p/yuv444p at the same sizes, so keep the preview to sizes
+    // a render could actually use
+    if width == 0 || height == 0 || !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err("Preview size must be non-zero and even".to_string());
+    }
+    if let Some(color) = &pad_color {
+        validate_color(color)?;
+    }
+    if let Some(guides) = &safe_guides {
+        guides.validate()?;
+    }
+
+    let pad_color = escape_filter_value(pad_color.as_deref().unwrap_or("black"));
+    let mut filters: Vec<String> = image_orientation_filter(&image_path)
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    filters.push(background_scaling_filter(
+        &fit_mode, width, height, &pad_color,
+    ));
+    if let Some(guides) = &safe_guides {
+        filters.extend(guides.filters());
+    }
+
+    run_ffmpeg_task(&[
+        "-y".to_string(),
+        "-noautorotate".to_string(),
+        "-i".to_string(),
+        image_path,
+        "-vf".to_string(),
+        filters.join(","),
+        "-frames:v".to_string(),
+        "1".to_string(),
+        output_png.clone(),
+    ])?;
+
+    Ok(output_png)
+}
+
+/// Rebuild a playable MP4 from an interrupted or damaged one. A stream copy rewrites the
+/// index, which is enough when the moov atom survived. Without a moov atom the sample
+/// tables have to be rebuilt from a healthy `reference` file made with the same settings,
+/// which needs the untrunc tool on PATH.
+#[tauri::command]
+async fn repair_mp4(input: String, output: String, reference: Option<String>) -> StitchResult {
+    if !Path::new(&input).exists() {
+        return stitch_error("Input file does not exist");
+    }
+    if Path::new(&input) == Path::new(&output) {
+        return stitch_error("Repair output must be a different file than the input");
+    }
+
+    let args: Vec<String> = [
+        "-y",
+        "-err_detect",
+        "ignore_err",
+        "-i",
+        &input,
+        "-map",
+        "0",
+        "-c",
+        "copy",
+        "-movflags",
+        "+faststart",
+        &output,
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    let mut result = run_ffmpeg(&args, output.clone());
+
+    if !result.success && !result.cancelled {
+        let error = result.error.take().unwrap_or_default();
+        if !error.contains("moov atom not found") {
+            return stitch_error(error);
+        }
+        let Some(reference) = reference else {
+            return stitch_error(
+                "The file has no moov atom (the render was cut off); repairing it needs a reference MP4 rendered with the same settings",
+            );
+        };
+        result = untrunc_mp4(&reference, &input, &output);
+    }
+    if !result.success {
+        return result;
+    }
+
+    // How much survived: the repaired length, and the share of the input's bytes kept
+    let info = get_audio_info(output.clone());
+    let input_bytes = fs::metadata(&input).map(|m| m.len()).unwrap_or(0);
+    result.duration = Some(info.duration);
+    result.output_bytes = Some(info.size);
+    if input_bytes > 0 {
+        result.warnings.push(format!(
+            "Recovered {:.1}s of media ({:.0}% of the input's {} bytes)",
+            info.duration,
+            info.size as f64 / input_bytes as f64 * 100.0,
+            input_bytes
+        ));
+    }
+
+    result
+}
+
+/// Rebuild the moov atom of `input` from the codec setup in `reference` with untrunc
+fn untrunc_mp4(reference: &str, input: &str, output: &str) -> StitchResult {
+    if !Path::new(reference).exists() {
+        return stitch_error("Reference file does not exist");
+    }
+    match Command::new("untrunc")
+        .args(["-dst", output, reference, input])
+        .output()
+    {
+        Ok(out) if out.status.success() && Path::new(output).exists() => StitchResult {
+            success: true,
+            output_path: Some(output.to_string()),
+            ..Default::default()
+        },
+        Ok(out) => stitch_error(format!(
+            "untrunc could not rebuild the file: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )),
+        Err(e) => stitch_error(format!(
+            "Failed to run untrunc: {}. Repairing a file with no moov atom needs untrunc installed",
+            e
+        )),
+    }
+}
+
+/// Fail clearly when FFmpeg was built without chromaprint
+fn require_chromaprint() -> Result<(), String> {
+    if ffmpeg_has_library("chromaprint") {
+        Ok(())
+    } else {
+        Err(missing_library_error("Fingerprinting", "chromaprint"))
+    }
+}
+
+/// Fingerprint one clip with the chromaprint muxer, which writes to stdout in `format`
+/// (base64, the compressed fingerprint, or raw)
+fn chromaprint_output(path: &str, format: &str) -> Result<Vec<u8>, String> {
+    let output = ffmpeg_command()
+        .args([
+            "-v",
+            "error",
+            "-i",
+            path,
+            "-f",
+            "chromaprint",
+            "-fp_format",
+            format,
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("FFmpeg error: {}", stderr));
+    }
+    if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        return Err(format!("No fingerprint produced for {}", path));
+    }
+    Ok(output.stdout)
+}
+
+/// A clip's fingerprint in chromaprint's compressed base64 form
+fn chromaprint(path: &str) -> Result<String, String> {
+    let output = chromaprint_output(path, "base64")?;
+    Ok(String::from_utf8_lossy(&output).trim().to_string())
+}
+
+/// A clip's fingerprint as chromaprint's sub-fingerprints, one 32-bit word per ~0.12s
+/// of audio. The muxer writes them in native byte order, little-endian on every
+/// platform the app ships for.
+fn chromaprint_raw(path: &str) -> Result<Vec<u32>, String> {
+    Ok(chromaprint_output(path, "raw")?
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        .collect())
+}
+
+/// Furthest the clips are slid against each other when aligning, in sub-fingerprints
+/// (about 10 seconds)
+const MAX_FINGERPRINT_SHIFT: usize = 80;
+
+/// Fewest overlapping sub-fingerprints (about 2 seconds) a comparison is scored on
+const MIN_FINGERPRINT_OVERLAP: usize = 16;
+
+/// Similarity of two fingerprints at their best alignment: the bit error rate over the
+/// overlapping words, mapped so unrelated audio (about half the bits differ) scores 0
+/// and identical audio 1
+fn fingerprint_similarity(a: &[u32], b: &[u32]) -> Option<f64> {
+    let shifts = (0..=MAX_FINGERPRINT_SHIFT).flat_map(|shift| [(shift, 0), (0, shift)]);
+    shifts
+        .filter_map(|(skip_a, skip_b)| {
+            let (a, b) = (a.get(skip_a..)?, b.get(skip_b..)?);
+            let overlap = a.len().min(b.len());
+            if overlap < MIN_FINGERPRINT_OVERLAP {
+                return None;
+            }
+            let differing: u32 = a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum();
+            let error_rate = differing as f64 / (overlap as f64 * 32.0);
+            Some((1.0 - 2.0 * error_rate).clamp(0.0, 1.0))
+        })
+        .max_by(|x, y| x.total_cmp(y))
+}
+
+/// Score how alike two clips sound, from 0 (unrelated) to 1 (the same audio), by aligning
+/// their chromaprint fingerprints and comparing the overlap, so recordings that differ in
+/// length or start up to ~10 seconds apart still line up. Chromaprint models pitch
+/// content tuned for music: on narrowband voice, stretches of silence, static or squelch
+/// tails score alike, so treat mid-range scores as "worth a listen" rather than a match,
+/// and clips under about two seconds can't be compared at all.
+#[tauri::command]
+async fn compare_clips(a: String, b: String) -> Result<f64, String> {
+    require_chromaprint()?;
+    let (fingerprint_a, fingerprint_b) = (chromaprint_raw(&a)?, chromaprint_raw(&b)?);
+    fingerprint_similarity(&fingerprint_a, &fingerprint_b)
+        .ok_or_else(|| "Clips are too short to compare (about 2 seconds needed)".to_string())
+}
+
+/// Compute a perceptual fingerprint of a clip's audio. Unlike a byte hash it survives
+/// re-encoding, so the same transmission can be matched across sessions.
+#[tauri::command]
+async fn fingerprint_clip(path: String) -> Result<String, String> {
+    require_chromaprint()?;
+    chromaprint(&path)
+}
+
+/// fingerprint_clip over a clip list, one entry per clip in order, with `probe-progress`
+/// events as each finishes
+#[tauri::command]
+async fn fingerprint_clips(
+    window: tauri::Window,
+    clips: Vec<AudioClip>,
+) -> Result<Vec<ClipFingerprint>, String> {
+    require_chromaprint()?;
+
+    Ok(probe_batch(&window, "fingerprint_clips", &clips, |path| {
+        let (fingerprint, error) = match chromaprint(path) {
+            Ok(fingerprint) => (Some(fingerprint), None),
+            Err(e) => (None, Some(e)),
+        };
+        ClipFingerprint {
+            path: path.to_string(),
+            fingerprint,
+            error,
+        }
+    }))
+}
+
+/// Source file types batch_transcode picks up, the same ones the clip picker offers
+const AUDIO_EXTENSIONS: [&str; 8] = ["mp3", "wav", "ogg", "flac", "m4a", "aac", "wma", "opus"];
+
+/// Target format for batch_transcode; unset rate and channels keep each file's own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscodeSettings {
+    pub format: OutputFormat,
+    pub bitrate: String,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u32>,
+}
+
+/// Outcome of one file in a batch_transcode
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranscodeResult {
+    pub input_path: String,
+    pub output_path: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
 
-    // Apply video filter only if we have an image (black background is already 1920x1080)
-    if video_config.image_path.is_some() {
-        args.extend_from_slice(&["-vf".to_string(), vf]);
-    } else {
-        args.extend_from_slice(&["-vf".to_string(), "format=yuv420p".to_string()]);
+/// Supported audio files under `dir`, recursively, in name order. `skip` (the output
+/// folder, when it sits inside the input) isn't descended into.
+fn collect_audio_files(dir: &Path, skip: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            if !same_file(&path.to_string_lossy(), &skip.to_string_lossy()) {
+                collect_audio_files(&path, skip, files)?;
+            }
+        } else if path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.as_str()))
+        {
+            files.push(path);
+        }
     }
+    Ok(())
+}
 
-    // Video and audio encoding settings
-    // Using CRF 12 for very high quality, slow preset for better compression
-    args.extend_from_slice(&[
-        "-c:v".to_string(),
-        "libx264".to_string(),
-        "-crf".to_string(),
-        "12".to_string(),
-        "-preset".to_string(),
-        "slow".to_string(),
-        "-tune".to_string(),
-        "stillimage".to_string(),
-        "-c:a".to_string(),
-        "aac".to_string(),
-        "-b:a".to_string(),
-        bitrate.clone(),
-        "-shortest".to_string(),
-        "-movflags".to_string(),
-        "+faststart".to_string(),
-        output_path.clone(),
-    ]);
+/// Convert every supported audio file under `input_dir` to one format, so a messy folder
+/// stitches on the demuxer (or stream copy) path. Outputs keep their path relative to
+/// `input_dir`, with the new extension. Files run `max_parallel` at a time (default
+/// MAX_PROBE_WORKERS) with `probe-progress` events, and a failure only fails that file.
+#[tauri::command]
+async fn batch_transcode(
+    window: tauri::Window,
+    input_dir: String,
+    output_dir: String,
+    settings: TranscodeSettings,
+    max_parallel: Option<usize>,
+) -> Result<Vec<TranscodeResult>, String> {
+    if settings.format == OutputFormat::Mp4 {
+        return Err("batch_transcode converts to MP3 or Opus".to_string());
+    }
+    if let Some(rate) = settings.sample_rate {
+        if !(8000..=192000).contains(&rate) {
+            return Err(format!(
+                "Invalid sample rate {} (expected 8000-192000 Hz)",
+                rate
+            ));
+        }
+    }
+    if settings
+        .channels
+        .is_some_and(|channels| !(1..=8).contains(&channels))
+    {
+        return Err("Channels must be between 1 and 8".to_string());
+    }
+    if max_parallel == Some(0) {
+        return Err("max_parallel must be at least 1".to_string());
+    }
+    let input_root = Path::new(&input_dir);
+    if !input_root.is_dir() {
+        return Err(format!("Input folder not found: {}", input_dir));
+    }
+    let output_root = Path::new(&output_dir);
+    fs::create_dir_all(output_root)
+        .map_err(|e| format!("Failed to create {}: {}", output_dir, e))?;
 
-    // Run FFmpeg
-    let output = Command::new("ffmpeg").args(&args).output();
+    let mut files = Vec::new();
+    collect_audio_files(input_root, output_root, &mut files)?;
+    let inputs: Vec<String> = files
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
 
-    // Clean up temp file
-    let _ = fs::remove_file(&concat_file_path);
+    // Decide every output up front, so two sources that map to one name (a.wav and
+    // a.flac) fail visibly instead of racing to overwrite each other
+    let mut claimed = HashSet::new();
+    let outputs: HashMap<String, Result<String, String>> = files
+        .iter()
+        .zip(&inputs)
+        .map(|(file, input)| {
+            let relative = file.strip_prefix(input_root).unwrap_or(file);
+            let output = output_root
+                .join(relative)
+                .with_extension(settings.format.extension())
+                .to_string_lossy()
+                .to_string();
+            let planned = if same_file(input, &output) {
+                Err("Output would overwrite the source file".to_string())
+            } else if !claimed.insert(output.clone()) {
+                Err(format!("Another file already converts to {}", output))
+            } else {
+                Ok(output)
+            };
+            (input.clone(), planned)
+        })
+        .collect();
 
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                StitchResult {
+    let workers = max_parallel.unwrap_or(MAX_PROBE_WORKERS);
+    Ok(run_batch(
+        &window,
+        "batch_transcode",
+        &inputs,
+        workers,
+        |input| {
+            let converted = outputs[input]
+                .clone()
+                .and_then(|output| transcode_file(input, &output, &settings).map(|_| output));
+            match converted {
+                Ok(output) => TranscodeResult {
+                    input_path: input.to_string(),
+                    output_path: Some(output),
                     success: true,
-                    output_path: Some(output_path),
                     error: None,
-                }
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                StitchResult {
-                    success: false,
+                },
+                Err(e) => TranscodeResult {
+                    input_path: input.to_string(),
                     output_path: None,
-                    error: Some(format!("FFmpeg error: {}", stderr)),
-                }
+                    success: false,
+                    error: Some(e),
+                },
             }
-        }
-        Err(e) => StitchResult {
-            success: false,
-            output_path: None,
-            error: Some(format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e)),
         },
+    ))
+}
+
+/// Transcode one file to the batch settings, removing any partial output on failure
+fn transcode_file(input: &str, output: &str, settings: &TranscodeSettings) -> Result<(), String> {
+    if let Some(parent) = Path::new(output).parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut args: Vec<String> = vec![
+        "-y".to_string(),
+        "-i".to_string(),
+        input.to_string(),
+        "-vn".to_string(), // Drop cover art, which MP3/Opus outputs may not carry
+    ];
+    if let Some(rate) = settings.sample_rate {
+        args.extend_from_slice(&["-ar".to_string(), rate.to_string()]);
+    }
+    if let Some(channels) = settings.channels {
+        args.extend_from_slice(&["-ac".to_string(), channels.to_string()]);
+    }
+    args.extend(audio_encoder_args(
+        settings.format,
+        &settings.bitrate,
+        OpusApplication::default(),
+        None,
+        output,
+    )?);
+    args.push(output.to_string());
+
+    run_ffmpeg_task(&args).inspect_err(|_| {
+        let _ = fs::remove_file(output);
+    })
+}
+
+/// What archive_session writes. The output format, loudness target and any other
+/// processing come from `audio_config`; without a loudnorm there, the archive is
+/// normalized to the LoudnormConfig defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveSettings {
+    pub bitrate: String,
+    #[serde(default)]
+    pub audio_config: Option<AudioConfig>,
+    #[serde(default)]
+    pub patterns: Option<Vec<String>>, // Extra recorder filename templates, as for label_recordings
+}
+
+/// archive_session's steps, in order, as reported by `archive-progress` events
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveStage {
+    Scanning,
+    Probing,
+    Ordering,
+    Stitching,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveProgress {
+    pub stage: ArchiveStage,
+    pub clip_count: usize, // Files found, or clips still in, as of this stage
+}
+
+/// A file archive_session found but left out of the archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveSkip {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Outcome of a successful archive_session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    pub output_path: String, // The .enc file when audio_config encrypts
+    pub manifest_path: Option<String>,
+    pub included: Vec<String>, // Clip paths, in output order
+    pub skipped: Vec<ArchiveSkip>,
+    pub total_duration: f64, // Output length in seconds
+    pub output_size: u64,
+    pub warnings: Vec<String>,
+}
+
+/// Repackage a folder of recordings as one archive file. Every supported audio file under
+/// `input_dir` is probed, put in recording order by label_recordings (which also names
+/// the chapters), then stitched loudness-normalized with a chapter per clip and a manifest
+/// sidecar. Mixed codecs are fine: they just take the filter path. `archive-progress`
+/// events mark each stage, around the stages' own `probe-progress` and `stitch-progress`.
+#[tauri::command]
+async fn archive_session(
+    window: tauri::Window,
+    input_dir: String,
+    output_path: String,
+    settings: ArchiveSettings,
+) -> Result<ArchiveSummary, String> {
+    if output_target(&output_path)? != OutputTarget::File {
+        return Err("archive_session writes to a file, not a stream".to_string());
+    }
+    let input_root = Path::new(&input_dir);
+    if !input_root.is_dir() {
+        return Err(format!("Input folder not found: {}", input_dir));
+    }
+    let progress = |stage, clip_count| {
+        let _ = window.emit("archive-progress", ArchiveProgress { stage, clip_count });
+    };
+
+    progress(ArchiveStage::Scanning, 0);
+    let mut files = Vec::new();
+    collect_audio_files(input_root, Path::new(&output_path), &mut files)?;
+    // Archiving into the input folder again mustn't pick up the previous archive
+    let paths: Vec<String> = files
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .filter(|path| !same_file(path, &output_path))
+        .collect();
+    if paths.is_empty() {
+        return Err(format!("No audio files found in {}", input_dir));
+    }
+
+    progress(ArchiveStage::Probing, paths.len());
+    let infos = run_batch(
+        &window,
+        "archive_session",
+        &paths,
+        MAX_PROBE_WORKERS,
+        |path| get_audio_info(path.to_string()),
+    );
+    let mut skipped = Vec::new();
+    let mut clips = Vec::new();
+    for (i, (path, info)) in paths.iter().zip(infos).enumerate() {
+        if !info.valid {
+            skipped.push(ArchiveSkip {
+                path: path.clone(),
+                reason: info.error.unwrap_or_else(|| "Unreadable".to_string()),
+            });
+            continue;
+        }
+        clips.push(AudioClip {
+            id: format!("archive-{}", i),
+            path: path.clone(),
+            name: Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            duration: info.duration,
+            size: info.size,
+            metadata: None,
+            order: None,
+            pan: None,
+        });
+    }
+    if clips.is_empty() {
+        return Err(format!(
+            "None of the {} audio files in {} could be read",
+            paths.len(),
+            input_dir
+        ));
+    }
+
+    progress(ArchiveStage::Ordering, clips.len());
+    let clips = label_recordings(clips, settings.patterns)?;
+
+    progress(ArchiveStage::Stitching, clips.len());
+    let mut audio_config = settings.audio_config.unwrap_or_default();
+    audio_config
+        .loudnorm
+        .get_or_insert_with(LoudnormConfig::default);
+    audio_config.clip_chapters = true;
+    audio_config.write_manifest = true;
+    let result = stitch_audio(
+        clips.clone(),
+        output_path,
+        settings.bitrate,
+        Some(audio_config),
+    )
+    .await;
+    let written = match (result.success, &result.output_path) {
+        (true, Some(path)) => path.clone(),
+        _ => return Err(result.error.unwrap_or_else(|| "Archive failed".to_string())),
+    };
+
+    // The stitch can still leave clips out: empty or corrupt ones, or past a length limit
+    for path in &result.skipped {
+        skipped.push(ArchiveSkip {
+            path: path.clone(),
+            reason: "No decodable audio".to_string(),
+        });
+    }
+    for path in &result.dropped {
+        skipped.push(ArchiveSkip {
+            path: path.clone(),
+            reason: "Past max_total_duration".to_string(),
+        });
+    }
+    let included: Vec<String> = clips
+        .into_iter()
+        .map(|clip| clip.path)
+        .filter(|path| !result.skipped.contains(path) && !result.dropped.contains(path))
+        .collect();
+    let output_size = fs::metadata(&written).map(|meta| meta.len()).unwrap_or(0);
+
+    progress(ArchiveStage::Done, included.len());
+    Ok(ArchiveSummary {
+        output_path: written,
+        manifest_path: result.manifest_path,
+        included,
+        skipped,
+        total_duration: result.duration.unwrap_or(0.0),
+        output_size,
+        warnings: result.warnings,
+    })
+}
+
+/// Write JPEG thumbnails of a video every `interval_seconds`, returning their paths in order.
+/// `quality` is the JPEG qscale (2 = best, 31 = smallest); videos shorter than one
+/// interval still get a thumbnail of their first frame.
+#[tauri::command]
+async fn generate_thumbnails(
+    video_path: String,
+    output_dir: String,
+    interval_seconds: f64,
+    size: (u32, u32),
+    quality: Option<u32>,
+) -> Result<Vec<String>, String> {
+    if !Path::new(&video_path).exists() {
+        return Err("Video file does not exist".to_string());
+    }
+    if interval_seconds <= 0.0 {
+        return Err("Thumbnail interval must be greater than zero".to_string());
+    }
+    let (width, height) = size;
+    if width == 0 || height == 0 {
+        return Err("Thumbnail size must be non-zero".to_string());
+    }
+    let quality = quality.unwrap_or(3);
+    if !(2..=31).contains(&quality) {
+        return Err("JPEG quality must be between 2 and 31".to_string());
+    }
+
+    fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create thumbnail folder: {}", e))?;
+    let pattern = Path::new(&output_dir).join("thumb_%04d.jpg");
+
+    let thumbnail_args = |extra: &[&str]| -> Vec<String> {
+        let mut args: Vec<String> = vec!["-y".to_string(), "-i".to_string(), video_path.clone()];
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        args.extend_from_slice(&[
+            "-q:v".to_string(),
+            quality.to_string(),
+            pattern.to_string_lossy().to_string(),
+        ]);
+        args
+    };
+    let collect_thumbnails = || -> Vec<String> {
+        let mut paths: Vec<String> = fs::read_dir(&output_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        name.starts_with("thumb_") && name.ends_with(".jpg")
+                    })
+                    .map(|path| path.to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    };
+
+    let scale = format!("scale={}:{}", width, height);
+    let sampled = format!("fps=1/{},{}", interval_seconds, scale);
+    run_ffmpeg_task(&thumbnail_args(&["-vf", &sampled]))?;
+
+    let mut thumbnails = collect_thumbnails();
+    if thumbnails.is_empty() {
+        // Too short for the fps filter to emit a frame; grab the first one instead
+        run_ffmpeg_task(&thumbnail_args(&["-vf", &scale, "-frames:v", "1"]))?;
+        thumbnails = collect_thumbnails();
+    }
+
+    Ok(thumbnails)
+}
+
+/// Frame rate and width GIF previews are capped at; beyond these, files grow quickly
+/// for little visible gain
+const GIF_MAX_FPS: u32 = 15;
+const GIF_MAX_WIDTH: u32 = 640;
+
+/// Render `duration` seconds of a video from `start` as a looping GIF, returning its path.
+/// A first pass builds a palette from the range itself, which the second pass maps frames
+/// onto, so colors hold up far better than with GIF's default palette. fps and width are
+/// capped at GIF_MAX_FPS and GIF_MAX_WIDTH; the height follows the aspect ratio.
+#[tauri::command]
+async fn render_gif_preview(
+    video_path: String,
+    output_gif: String,
+    start: f64,
+    duration: f64,
+    fps: u32,
+    width: u32,
+) -> Result<String, String> {
+    let info = get_audio_info(video_path.clone());
+    if !info.valid {
+        return Err(info.error.unwrap_or_else(|| "Invalid video".to_string()));
+    }
+    if !(start >= 0.0 && duration > 0.0 && start + duration <= info.duration) {
+        return Err(format!(
+            "Invalid range {:.3}s + {:.3}s (the video is {:.3}s long)",
+            start, duration, info.duration
+        ));
+    }
+    if fps == 0 || width == 0 {
+        return Err("GIF fps and width must be non-zero".to_string());
+    }
+
+    let frames = format!(
+        "fps={},scale={}:-2:flags=lanczos",
+        fps.min(GIF_MAX_FPS),
+        width.min(GIF_MAX_WIDTH)
+    );
+    let input = [
+        "-ss".to_string(),
+        start.to_string(),
+        "-t".to_string(),
+        duration.to_string(),
+        "-i".to_string(),
+        video_path,
+    ];
+    let palette_path = unique_temp_path("ffmpeg_gif_palette.png");
+    let palette = palette_path.to_string_lossy().to_string();
+
+    // stats_mode=diff favors what changes over a static background
+    let mut palette_args = vec!["-y".to_string()];
+    palette_args.extend_from_slice(&input);
+    palette_args.extend_from_slice(&[
+        "-vf".to_string(),
+        format!("{},palettegen=stats_mode=diff", frames),
+        palette.clone(),
+    ]);
+
+    let mut gif_args = vec!["-y".to_string()];
+    gif_args.extend_from_slice(&input);
+    gif_args.extend_from_slice(&[
+        "-i".to_string(),
+        palette,
+        "-lavfi".to_string(),
+        format!(
+            "{}[frames];[frames][1:v]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle",
+            frames
+        ),
+        "-loop".to_string(),
+        "0".to_string(),
+        "-f".to_string(),
+        "gif".to_string(),
+        output_gif.clone(),
+    ]);
+
+    let rendered = run_ffmpeg_task(&palette_args).and_then(|_| run_ffmpeg_task(&gif_args));
+    let _ = fs::remove_file(&palette_path);
+    if rendered.is_err() {
+        let _ = fs::remove_file(&output_gif);
     }
+    rendered.map(|_| output_gif)
 }
 
 /// Check if FFmpeg is available on the system
 #[tauri::command]
 fn check_ffmpeg() -> Result<String, String> {
-    let output = Command::new("ffmpeg").args(["-version"]).output();
+    let output = ffmpeg_command().args(["-version"]).output();
 
     match output {
         Ok(output) => {
@@ -449,19 +9985,712 @@ fn check_ffmpeg() -> Result<String, String> {
     }
 }
 
+/// Compute where each clip starts and ends in the output, for timeline rendering. Given the
+/// audio config, clips are capped and joined as the render would, so crossfades overlap
+/// and marker tones sit between them. There's no separate intro or outro: the stitcher
+/// has none to offset by, so one is passed as the first or last clip and placed with them.
+#[tauri::command]
+fn compute_timeline(
+    clips: Vec<AudioClip>,
+    gap_seconds: f64,
+    audio_config: Option<AudioConfig>,
+) -> Result<Vec<ClipPlacement>, String> {
+    let Some(audio_config) = audio_config else {
+        return Ok(clip_placements(&clips, gap_seconds));
+    };
+    let cap = audio_config.cap_clip_length;
+    match clip_join(&audio_config, &clips, cap)? {
+        ClipJoin::Concat => {
+            let capped: Vec<AudioClip> = clips
+                .into_iter()
+                .map(|mut clip| {
+                    clip.duration = cap.map_or(clip.duration, |cap| clip.duration.min(cap));
+                    clip
+                })
+                .collect();
+            Ok(clip_placements(&capped, gap_seconds))
+        }
+        _ if gap_seconds > 0.0 => {
+            Err("A gap can't be combined with crossfades or marker tones".to_string())
+        }
+        join => {
+            let durations: Vec<f64> = clips
+                .iter()
+                .map(|clip| cap.map_or(clip.duration, |cap| clip.duration.min(cap)))
+                .collect();
+            Ok(joined_placements(&durations, join))
+        }
+    }
+}
+
+/// Check that FFmpeg understands a color, whether a name ("navy") or hex ("#1a2b3c")
+fn validate_color(color: &str) -> Result<(), String> {
+    if color.trim().is_empty() {
+        return Err("Color is empty".to_string());
+    }
+
+    let output = ffmpeg_command()
+        .args([
+            "-hide_banner",
+            "-v",
+            "error",
+            "-f",
+            "lavfi",
+            "-i",
+            &format!("color=c={}:s=2x2", escape_filter_value(color)),
+            "-frames:v",
+            "1",
+            "-f",
+            "null",
+            "-",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a color FFmpeg recognizes", color))
+    }
+}
+
+/// Check that an audio filter string parses by running it over a fraction of a second of silence
+#[tauri::command]
+fn validate_filter(filter: String) -> Result<(), String> {
+    if filter.trim().is_empty() {
+        return Err("Filter is empty".to_string());
+    }
+
+    // Generated input and a null muxer, so nothing touches the disk
+    let output = ffmpeg_command()
+        .args([
+            "-hide_banner",
+            "-v",
+            "error",
+            "-f",
+            "lavfi",
+            "-i",
+            "anullsrc=r=44100:cl=stereo",
+            "-af",
+            &filter,
+            "-t",
+            "0.1",
+            "-f",
+            "null",
+            "-",
+        ])
+        .output();
+
+    match output {
+        Ok(output) => {
+            if output.status.success() {
+                Ok(())
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                Err(format!("Invalid filter: {}", stderr.trim()))
+            }
+        }
+        Err(e) => Err(format!("Failed to run FFmpeg: {}. Is FFmpeg installed?", e)),
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .setup(|app| {
+            let _ = APP_HANDLE.set(app.handle().clone());
+            // Before the queue starts, as jobs it resumes may ask to overwrite
+            app.manage(OverwritePrompts::default());
+
+            // Reload the persisted queue and resume anything left unfinished
+            let queue_path = app.path().app_config_dir()?.join("job_queue.json");
+            let queue = jobs::JobQueue::load(queue_path);
+            queue.start();
+            app.manage(queue);
+            app.manage(BuildInfoCache::default());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_audio_info,
             get_image_info,
             stitch_audio,
             stitch_video,
+            stitch_from_concat_file,
+            rerun_from_manifest,
+            validate_filter,
+            compute_timeline,
+            concat_compatibility,
+            generate_thumbnails,
+            downmix_to_mono,
+            check_disk_space,
+            split_on_silence,
+            detect_bitrate,
+            stitch_waveform_video,
+            remove_segment,
+            detect_dc_offset,
+            benchmark_presets,
+            normalize_image_orientation,
+            generate_waveform_montage,
+            check_clip_integrity,
+            check_clips_integrity,
+            fingerprint_clip,
+            fingerprint_clips,
+            load_clips_from_json,
+            suggest_output_settings,
+            decrypt_file,
+            generate_clip_srt,
+            burn_subtitles,
+            channel_balance,
+            time_range_output_path,
+            preview_framing,
+            dump_media_info,
+            repair_mp4,
+            bitrate_for_target_size,
+            batch_transcode,
+            parse_recording_filename,
+            label_recordings,
+            stitch_spectrogram_video,
+            compare_clips,
+            find_peak_segment,
+            render_gif_preview,
+            export_edl,
+            stitch_video_clips,
+            ffmpeg_build_info,
+            preview_mix,
+            clear_preview_mixes,
+            archive_session,
+            describe_pipeline,
+            split_by_chapters,
+            export_timeline_json,
+            respond_overwrite,
+            rank_clips_by_loudness,
+            jobs::enqueue_stitch,
+            jobs::list_jobs,
+            jobs::cancel_stitch,
+            jobs::set_max_parallel,
+            jobs::clear_completed_jobs,
+            jobs::retry_failed_jobs,
             check_ffmpeg
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that render or probe need both tools, and pass without doing anything when
+    /// either is missing
+    pub(crate) fn ffmpeg_available() -> bool {
+        ["ffmpeg", "ffprobe"].iter().all(|program| {
+            Command::new(program)
+                .arg("-version")
+                .output()
+                .is_ok_and(|output| output.status.success())
+        })
+    }
+
+    /// An empty directory of its own for one test's files
+    pub(crate) fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rts-test-{}-{}", name, temp_run_id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Render `seconds` of a sine tone to `path`, in the format its extension names
+    fn sine_clip(path: &Path, seconds: f64, frequency: u32) -> AudioClip {
+        let source = format!(
+            "sine=frequency={}:sample_rate=44100:duration={}",
+            frequency, seconds
+        );
+        lavfi_clip(path, &source, seconds)
+    }
+
+    /// Render a lavfi `source` that runs `seconds` to `path` as a clip
+    fn lavfi_clip(path: &Path, source: &str, seconds: f64) -> AudioClip {
+        let status = Command::new("ffmpeg")
+            .args(["-v", "error", "-nostdin", "-y", "-f", "lavfi", "-i", source])
+            .arg(path)
+            .status()
+            .unwrap();
+        assert!(status.success(), "couldn't render {}", path.display());
+        AudioClip {
+            id: path.to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            name: path.file_stem().unwrap().to_string_lossy().to_string(),
+            duration: seconds,
+            size: fs::metadata(path).unwrap().len(),
+            metadata: None,
+            order: None,
+            pan: None,
+        }
+    }
+
+    fn path_string(path: &Path) -> String {
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn target_size_snaps_mp3_down_to_an_encoder_bitrate() {
+        // 125.9 kbps of budget, which LAME only offers as 112k
+        assert_eq!(
+            bitrate_for_target_size(60.0, 1_000_000, OutputFormat::Mp3).unwrap(),
+            "112k"
+        );
+        assert_eq!(
+            bitrate_for_target_size(60.0, 1_100_000, OutputFormat::Mp3).unwrap(),
+            "128k"
+        );
+        assert_eq!(
+            bitrate_for_target_size(60.0, 100_000_000, OutputFormat::Mp3).unwrap(),
+            "320k"
+        );
+    }
+
+    #[test]
+    fn target_size_keeps_opus_within_its_range() {
+        assert_eq!(
+            bitrate_for_target_size(60.0, 2_000_000, OutputFormat::Opus).unwrap(),
+            "252k"
+        );
+        assert_eq!(
+            bitrate_for_target_size(60.0, 100_000_000, OutputFormat::Opus).unwrap(),
+            "510k"
+        );
+        assert_eq!(
+            bitrate_for_target_size(60.0, 60_000, OutputFormat::Opus).unwrap(),
+            "6k"
+        );
+    }
+
+    #[test]
+    fn target_size_leaves_room_for_headers_and_overshoot() {
+        // 128 kbps exactly once the header allowance is added back, but not the margin
+        let bytes = 128_000 / 8 * 60 + TARGET_SIZE_HEADER_BYTES;
+        assert_eq!(
+            bitrate_for_target_size(60.0, bytes, OutputFormat::Mp3).unwrap(),
+            "112k"
+        );
+        // Room for 128k with the margin, but only without the header allowance
+        let bytes = (128_000.0 / 8.0 * 60.0 * OUTPUT_SIZE_MARGIN) as u64 + 4_000;
+        assert_eq!(
+            bitrate_for_target_size(60.0, bytes, OutputFormat::Mp3).unwrap(),
+            "112k"
+        );
+        let bytes =
+            (128_000.0 / 8.0 * 60.0 * OUTPUT_SIZE_MARGIN) as u64 + TARGET_SIZE_HEADER_BYTES + 100;
+        assert_eq!(
+            bitrate_for_target_size(60.0, bytes, OutputFormat::Mp3).unwrap(),
+            "128k"
+        );
+    }
+
+    #[test]
+    fn target_size_refuses_budgets_below_the_lowest_bitrate() {
+        let error = bitrate_for_target_size(60.0, 50_000, OutputFormat::Mp3).unwrap_err();
+        assert!(error.contains("too small"), "{}", error);
+        assert!(error.contains("8k"), "{}", error);
+        let error = bitrate_for_target_size(60.0, 25_000, OutputFormat::Opus).unwrap_err();
+        assert!(error.contains("6k"), "{}", error);
+        // Nothing left once the headers are paid for
+        assert!(
+            bitrate_for_target_size(60.0, TARGET_SIZE_HEADER_BYTES, OutputFormat::Mp3).is_err()
+        );
+        assert!(bitrate_for_target_size(0.0, 1_000_000, OutputFormat::Mp3).is_err());
+        assert!(bitrate_for_target_size(60.0, 1_000_000, OutputFormat::Mp4).is_err());
+    }
+
+    #[test]
+    fn timecodes_count_frames_at_the_edl_rate() {
+        assert_eq!(format_timecode(0, 30), "00:00:00:00");
+        assert_eq!(format_timecode(24 * 3600 + 23, 24), "01:00:00:23");
+        assert_eq!(format_timecode(25 * 61 + 24, 25), "00:01:01:24");
+        assert_eq!(format_timecode(30 * 3661 + 29, 30), "01:01:01:29");
+        // One frame on from the last of a second rolls the seconds over
+        assert_eq!(format_timecode(25 * 61 + 25, 25), "00:01:02:00");
+    }
+
+    #[test]
+    fn edl_events_accumulate_in_whole_frames() {
+        let events = edl_events(&[10.0, 0.5, 61.0], 1.0, 24);
+        let spans: Vec<(u64, u64, u64)> = events
+            .iter()
+            .map(|event| (event.length, event.record_in, event.record_out))
+            .collect();
+        assert_eq!(spans, [(240, 0, 240), (12, 264, 276), (1464, 300, 1764)]);
+        assert_eq!(format_timecode(events[2].record_out, 24), "00:01:13:12");
+
+        // Thirds of a second are exactly ten frames at 30fps, however many there are
+        let events = edl_events(&[1.0 / 3.0; 90], 0.0, 30);
+        assert_eq!(format_timecode(events[89].record_out, 30), "00:00:30:00");
+
+        // Each clip rounds on its own, so the record side never drifts from the source
+        let events = edl_events(&[1.01; 100], 0.0, 25);
+        for pair in events.windows(2) {
+            assert_eq!(pair[1].record_in, pair[0].record_out);
+        }
+        assert!(events.iter().all(|event| event.length == 25));
+        assert_eq!(format_timecode(events[99].record_out, 25), "00:01:40:00");
+    }
+
+    #[test]
+    fn creation_times_parse_as_iso_8601() {
+        let noon = parse_iso8601("2024-05-01T12:03:11Z").unwrap();
+        assert_eq!(noon, 1_714_564_991);
+        assert_eq!(parse_iso8601("2024-05-01T12:03:11.000000Z"), Some(noon));
+        assert_eq!(parse_iso8601("2024-05-01 12:03:11"), Some(noon));
+        assert_eq!(parse_iso8601("2024-05-01T14:03:11+02:00"), Some(noon));
+        assert_eq!(parse_iso8601("2024-05-01T08:33:11-03:30"), Some(noon));
+        assert_eq!(parse_iso8601("2024-05-02T02:03:11+14:00"), Some(noon));
+        assert_eq!(parse_iso8601("2024-05-01T14:03:11+0200"), Some(noon));
+        assert!(parse_iso8601("2024-02-29T00:00:00Z").is_some());
+        // A leap second reads as the first second of the next minute
+        assert_eq!(
+            parse_iso8601("2016-12-31T23:59:60Z"),
+            parse_iso8601("2017-01-01T00:00:00Z")
+        );
+
+        for invalid in [
+            "yesterday",
+            "2024-05-01",
+            "2024-13-01T12:03:11Z",
+            "2024-05-32T12:03:11Z",
+            "2024-05-01T24:03:11Z",
+            "2024-05-01T12:60:11Z",
+            "2024-05-01T12:03Z",
+            "2024-05-01T12:03:11+xx:00",
+            "2023-02-29T00:00:00Z",
+            "2024-02-30T00:00:00Z",
+            "2024-02-31T00:00:00Z",
+            "2024-04-31T00:00:00Z",
+            "2024-05-00T00:00:00Z",
+            "2024-05-01T12:03:61Z",
+            "2024-05-01T12:03:75Z",
+            "2024-05-01T12:03:11:99Z",
+            "2024-05-01-07T12:03:11Z",
+            "2024-05-01T12:03:11.Z",
+            "2024-05-01T12:03:11+99:00",
+            "2024-05-01T12:03:11+14:30",
+            "2024-05-01T12:03:11-15:00",
+            "2024-05-01T12:03:11+02:60",
+        ] {
+            assert_eq!(parse_iso8601(invalid), None, "{}", invalid);
+        }
+        let error = creation_time_args(Some("yesterday"), false, &[]).unwrap_err();
+        assert!(error.contains("yesterday"), "{}", error);
+    }
+
+    #[test]
+    fn creation_times_are_written_in_utc() {
+        let args = creation_time_args(Some("2024-03-01T01:00:00+02:00"), false, &[]).unwrap();
+        // Back across the leap day into February
+        assert_eq!(
+            args,
+            ["-metadata", "creation_time=2024-02-29T23:00:00.000000Z"]
+        );
+        assert_eq!(
+            format_iso8601(parse_iso8601("1999-12-31T23:59:59-01:00").unwrap()),
+            "2000-01-01T00:59:59.000000Z"
+        );
+        assert!(creation_time_args(None, false, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn creation_time_reads_back_from_the_output() {
+        if !ffmpeg_available() {
+            return;
+        }
+        let dir = test_dir("creation-time");
+        let clip = sine_clip(&dir.join("clip.mp3"), 1.0, 440);
+        let output = dir.join("out.mp3");
+        let config = AudioConfig {
+            creation_time: Some("2024-05-01T14:03:11+02:00".to_string()),
+            ..Default::default()
+        };
+
+        let result = tauri::async_runtime::block_on(stitch_audio(
+            vec![clip],
+            path_string(&output),
+            "128k".to_string(),
+            Some(config),
+        ));
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            probe_creation_time(&path_string(&output)),
+            parse_iso8601("2024-05-01T12:03:11Z")
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    fn geotag(latitude: f64, longitude: f64, altitude: Option<f64>) -> GeoTag {
+        GeoTag {
+            latitude,
+            longitude,
+            altitude,
+        }
+    }
+
+    #[test]
+    fn locations_format_as_padded_iso_6709() {
+        assert_eq!(
+            geotag(37.7749, -122.4194, None).iso6709(),
+            "+37.7749-122.4194/"
+        );
+        assert_eq!(geotag(5.5, 3.25, None).iso6709(), "+05.5000+003.2500/");
+        assert_eq!(
+            geotag(-33.8688, 151.2093, Some(12.5)).iso6709(),
+            "-33.8688+151.2093+012.500/"
+        );
+        assert_eq!(
+            geotag(0.0, 0.0, Some(-3.0)).iso6709(),
+            "+00.0000+000.0000-003.000/"
+        );
+    }
+
+    #[test]
+    fn locations_are_range_checked() {
+        assert!(geotag(90.0, 180.0, None).validate().is_ok());
+        assert!(geotag(-90.0, -180.0, Some(8848.0)).validate().is_ok());
+        assert!(geotag(90.5, 0.0, None).validate().is_err());
+        assert!(geotag(-91.0, 0.0, None).validate().is_err());
+        assert!(geotag(0.0, 180.1, None).validate().is_err());
+        assert!(geotag(0.0, -200.0, None).validate().is_err());
+        assert!(geotag(0.0, 0.0, Some(f64::INFINITY)).validate().is_err());
+        assert!(geotag(f64::NAN, 0.0, None).validate().is_err());
+    }
+
+    #[test]
+    fn iso_6709_locations_parse_back() {
+        let tag = parse_iso6709("-33.8688+151.2093+012.500/").unwrap();
+        assert_eq!(
+            (tag.latitude, tag.longitude, tag.altitude),
+            (-33.8688, 151.2093, Some(12.5))
+        );
+        let tag = parse_iso6709("+37.7749-122.4194/").unwrap();
+        assert_eq!(
+            (tag.latitude, tag.longitude, tag.altitude),
+            (37.7749, -122.4194, None)
+        );
+        for invalid in [
+            "",
+            "37.7749-122.4194/",
+            "+37.7749/",
+            "+95.0000+010.0000/",
+            "+1+2+3+4/",
+        ] {
+            assert!(parse_iso6709(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn location_reads_back_from_mp3_and_mp4() {
+        if !ffmpeg_available() {
+            return;
+        }
+        let dir = test_dir("location");
+        let clip = sine_clip(&dir.join("clip.mp3"), 1.0, 440);
+        let location = geotag(37.7749, -122.4194, Some(12.5));
+        let read_back = |path: &Path| {
+            let tag = probe_location(&path_string(path)).expect("no location read back");
+            (tag.latitude, tag.longitude, tag.altitude)
+        };
+
+        let mp3 = dir.join("out.mp3");
+        let config = AudioConfig {
+            location: Some(location.clone()),
+            ..Default::default()
+        };
+        let result = tauri::async_runtime::block_on(stitch_audio(
+            vec![clip.clone()],
+            path_string(&mp3),
+            "128k".to_string(),
+            Some(config),
+        ));
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(read_back(&mp3), (37.7749, -122.4194, Some(12.5)));
+
+        let mp4 = dir.join("out.mp4");
+        let config = VideoConfig {
+            width: Some(320),
+            height: Some(240),
+            location: Some(location),
+            ..Default::default()
+        };
+        let result = tauri::async_runtime::block_on(stitch_video(
+            vec![clip],
+            path_string(&mp4),
+            "128k".to_string(),
+            config,
+        ));
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(read_back(&mp4), (37.7749, -122.4194, Some(12.5)));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn appending_keeps_the_existing_chapters() {
+        if !ffmpeg_available() {
+            return;
+        }
+        let dir = test_dir("append-chapters");
+        let plain = sine_clip(&dir.join("plain.mp3"), 4.0, 440);
+        let metadata = dir.join("chapters.txt");
+        fs::write(
+            &metadata,
+            ";FFMETADATA1\n\
+             [CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=2000\ntitle=Morning\n\
+             [CHAPTER]\nTIMEBASE=1/1000\nSTART=2000\nEND=4000\ntitle=Noon\n",
+        )
+        .unwrap();
+        let source = dir.join("source.mp3");
+        let status = Command::new("ffmpeg")
+            .args(["-v", "error", "-nostdin", "-y", "-i", &plain.path, "-i"])
+            .arg(&metadata)
+            .args(["-map", "0", "-map_chapters", "1", "-c", "copy"])
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let clip = sine_clip(&dir.join("late.mp3"), 1.5, 880);
+        let output = dir.join("out.mp3");
+        let config = AudioConfig {
+            append_to: Some(path_string(&source)),
+            ..Default::default()
+        };
+
+        let result = tauri::async_runtime::block_on(stitch_audio(
+            vec![clip],
+            path_string(&output),
+            "128k".to_string(),
+            Some(config),
+        ));
+        assert!(result.success, "{:?}", result.error);
+        let chapters = probe_chapters(&path_string(&output)).unwrap();
+        let titles: Vec<&str> = chapters.iter().map(|cue| cue.text.as_str()).collect();
+        assert_eq!(titles, ["Morning", "Noon", "late"]);
+        // The new clip's chapter picks up where the existing file ended
+        assert!(
+            chapters[1].start_ms.abs_diff(2000) <= 50,
+            "{}",
+            chapters[1].start_ms
+        );
+        assert!(
+            chapters[2].start_ms.abs_diff(4000) <= 100,
+            "{}",
+            chapters[2].start_ms
+        );
+        assert!(
+            chapters[2].end_ms.abs_diff(5500) <= 150,
+            "{}",
+            chapters[2].end_ms
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn overwrite_prompt_does_not_wait_on_stdin() {
+        if !ffmpeg_available() {
+            return;
+        }
+        let dir = test_dir("nostdin");
+        let existing = dir.join("existing.wav");
+        fs::write(&existing, b"keep me").unwrap();
+
+        // Without -y, FFmpeg would ask before replacing the file and wait for an answer
+        let mut child = ffmpeg_command()
+            .args(["-v", "error", "-f", "lavfi", "-i", "anullsrc", "-t", "0.1"])
+            .arg(&existing)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break status;
+            }
+            if Instant::now() > deadline {
+                let _ = child.kill();
+                panic!("FFmpeg is waiting on stdin for the overwrite prompt");
+            }
+            thread::sleep(Duration::from_millis(20));
+        };
+        assert!(!status.success());
+        assert_eq!(fs::read(&existing).unwrap(), b"keep me");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    /// Peak level in dBFS of the audio within `window` seconds either side of `at`
+    fn peak_db_around(path: &Path, at: f64, window: f64) -> f64 {
+        let filter = format!(
+            "atrim=start={}:end={},astats=measure_perchannel=none:measure_overall=Peak_level",
+            at - window,
+            at + window
+        );
+        let output = Command::new("ffmpeg")
+            .args(["-v", "info", "-nostdin", "-i"])
+            .arg(path)
+            .args(["-af", &filter, "-f", "null", "-"])
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .find_map(|line| line.split_once("Peak level dB:"))
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .expect("astats reported no peak level")
+    }
+
+    #[test]
+    fn boundary_fades_silence_the_join() {
+        if !ffmpeg_available() {
+            return;
+        }
+        let dir = test_dir("boundary-fade");
+        // The first clip ends at +0.8 and the second starts at -0.8: a hard join jumps
+        // straight across, which is the click boundary fades are there to remove
+        let clips = vec![
+            lavfi_clip(
+                &dir.join("high.wav"),
+                "aevalsrc=0.8*cos(2*PI*300*t):s=44100:d=1",
+                1.0,
+            ),
+            lavfi_clip(
+                &dir.join("low.wav"),
+                "aevalsrc=-0.8*cos(2*PI*300*t):s=44100:d=1",
+                1.0,
+            ),
+        ];
+        let render = |name: &str, boundary_fade_ms| {
+            let output = dir.join(name);
+            let config = AudioConfig {
+                boundary_fade_ms,
+                ..Default::default()
+            };
+            let result = tauri::async_runtime::block_on(stitch_audio(
+                clips.clone(),
+                path_string(&output),
+                "192k".to_string(),
+                Some(config),
+            ));
+            assert!(result.success, "{:?}", result.error);
+            peak_db_around(&output, 1.0, 0.001)
+        };
+
+        let hard = render("hard.mp3", None);
+        let faded = render("faded.mp3", Some(10));
+        assert!(hard > -6.0, "hard join peaks at {} dB", hard);
+        // Within a millisecond of the join a 10 ms fade is at a tenth of full level, which
+        // leaves room for the encoder's smearing
+        assert!(faded < -14.0, "faded join peaks at {} dB", faded);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -5,6 +5,16 @@ export interface AudioClip {
     name: string;
     duration: number;
     size: number;
+    metadata?: Record<string, unknown> | null; // Passed through from a recorder session file
+    order?: number | null; // Explicit output position; set on every clip or none
+    pan?: number | null; // -1.0 (left) to 1.0 (right); any pan makes the output stereo
+}
+
+// A clip cut off at cap_clip_length
+export interface ClipTruncation {
+    path: string;
+    original_seconds: number;
+    removed_seconds: number;
 }
 
 // Result from stitching operation
@@ -12,6 +22,36 @@ export interface StitchResult {
     success: boolean;
     output_path: string | null;
     error: string | null;
+    clip_count: number | null;
+    manifest_path: string | null;
+    resampler: string | null;
+    chapters_path: string | null;
+    mtime: number | null;
+    clipping_warning: boolean;
+    limiter_engaged: boolean | null;
+    concat_method: 'demuxer' | 'filter' | null;
+    concat_reason: string | null; // Why that method was used, where the caller had a say
+    audio_encoder: string | null; // AAC encoder a video's audio went through
+    downmix: string | null;
+    audio_output_path: string | null;
+    skipped: string[];
+    dc_offset_removed: number | null; // Mean offset magnitude across the clips
+    cancelled: boolean;
+    duration: number | null;
+    gapless: boolean | null; // MP3 only: encoder delay/padding header present
+    truncated: ClipTruncation[]; // Clips cut short by cap_clip_length or the length limit
+    dropped: string[]; // Paths of clips left out to fit max_total_duration
+    stream_copy: boolean; // Audio copied as-is rather than re-encoded
+    silence_trimmed: number | null; // Seconds of dead air cut from clip edges in all
+    // Render performance, set on success only
+    elapsed_seconds: number | null;
+    realtime_factor: number | null; // Output seconds per wall-clock second
+    throughput_mb_s: number | null; // Output MB (10^6 bytes) per wall-clock second
+    output_bytes: number | null;
+    slideshow_images: number | null; // Background images the slideshow showed
+    output_channels: number | null; // Channel count set by channel_target
+    channels_converted: number | null; // Clips whose channel count was changed
+    warnings: string[];
 }
 
 // Audio file info from FFprobe
@@ -20,6 +60,272 @@ export interface AudioInfo {
     size: number;
     valid: boolean;
     error: string | null;
+    codec: string | null;
+    sample_rate: number | null;
+    channels: number | null;
+    bit_rate: number | null;
+    sample_format: string | null;
+}
+
+// Where a clip lands in the stitched output
+export interface ClipPlacement {
+    index: number;
+    start: number;
+    end: number;
+    start_ms: number;
+    end_ms: number;
+}
+
+// Timeline JSON written by export_timeline_json for web players. Fields may be added
+// without a version bump, so readers should ignore ones they don't know.
+export interface Timeline {
+    version: number;
+    media: string; // File name of the output the offsets refer to
+    duration_ms: number;
+    clips: TimelineEntry[];
+}
+
+// One clip of a Timeline; offsets in whole milliseconds, end exclusive
+export interface TimelineEntry {
+    index: number;
+    id: string;
+    name: string;
+    start_ms: number;
+    end_ms: number;
+    talkgroup?: number;
+    metadata?: Record<string, unknown>;
+}
+
+// Noise gate settings (FFmpeg agate)
+export interface GateConfig {
+    threshold_db: number;
+    ratio: number;
+    attack_ms: number;
+    release_ms: number;
+}
+
+// EBU R128 loudness normalization targets (FFmpeg loudnorm)
+export interface LoudnormConfig {
+    integrated_lufs: number;
+    true_peak_db: number;
+    lra: number;
+}
+
+// Peak limiter settings (FFmpeg alimiter)
+export interface LimiterConfig {
+    limit_db: number;
+    level: boolean;
+    attack_ms: number;
+    release_ms: number;
+}
+
+// Resampler quality: fast (swr) or high (soxr when available)
+export type ResampleQuality = 'fast' | 'high';
+
+// Channel count every clip is brought to; majority picks the most common one
+export type ChannelTarget = 'mono' | 'stereo' | 'majority';
+
+// libopus tuning mode
+export type OpusApplication = 'voip' | 'audio' | 'lowdelay';
+
+// High/low-pass filtering for radio hiss and rumble
+export interface BandFilter {
+    highpass_hz?: number | null;
+    lowpass_hz?: number | null;
+    voice_band?: boolean;
+}
+
+// Narrow cut at one frequency, e.g. a squeal spotted on the spectrogram
+export interface NotchFilter {
+    frequency_hz: number;
+    q: number;
+}
+
+// Gain curve of one side of a crossfade
+export type CrossfadeCurve = 'tri' | 'qsin' | 'esin' | 'log' | 'exp';
+
+// Overlap consecutive clips instead of butting them together
+export interface CrossfadeConfig {
+    duration_seconds?: number;
+    fade_out_curve?: CrossfadeCurve;
+    fade_in_curve?: CrossfadeCurve;
+}
+
+// Short beep played between clips (never after the last one)
+export interface MarkerTone {
+    frequency_hz?: number;
+    duration_seconds?: number;
+    volume_db?: number;
+}
+
+// Passphrase encryption of the output and its sidecars (age format); the passphrase is never
+// saved, and an existing .enc is only replaced through confirm_overwrite
+export interface EncryptConfig {
+    passphrase: string;
+}
+
+// Optional audio processing for stitching
+export interface AudioConfig {
+    remove_dc_offset?: boolean;
+    noise_gate?: GateConfig | null;
+    band_filter?: BandFilter | null;
+    notch_filters?: NotchFilter[];
+    auto_deharsh?: boolean;
+    crossfade?: CrossfadeConfig | null;
+    boundary_fade_ms?: number | null; // Fade each clip's edges to stop clicks at joins; 0 is off
+    marker_tone?: MarkerTone | null;
+    format?: 'mp3' | 'opus';
+    opus_application?: OpusApplication;
+    container?: string | null;
+    loudnorm?: LoudnormConfig | null;
+    gain_db?: number | null;
+    speed?: number | null;
+    pad_to_duration?: number | null; // Trailing silence up to exactly this many seconds
+    truncate_to_pad?: boolean; // Cut a longer output at pad_to_duration instead of failing
+    max_total_duration?: number | null; // Longest output allowed; longer fails before rendering
+    truncate_to_max?: boolean; // Drop and cut the last clips to fit instead of failing
+    limiter?: LimiterConfig | null;
+    sample_rate?: number | null;
+    channel_target?: ChannelTarget | null; // Only clips that differ are converted
+    resample_quality?: ResampleQuality;
+    max_filter_inputs?: number | null;
+    preserve_mtime?: number | null;
+    inherit_mtime?: boolean;
+    creation_time?: string | null; // ISO 8601, e.g. "2024-05-01T12:03:11Z"
+    creation_time_from_clips?: boolean; // Tag with the earliest clip's time when unset
+    location?: GeoTag | null;
+    location_from_clips?: boolean; // Tag with the first clip's location when unset
+    write_manifest?: boolean;
+    check_disk_space?: boolean;
+    warn_bitrate_upscale?: boolean;
+    atomic_write?: boolean;
+    confirm_overwrite?: OverwritePrompt | null; // Ask before replacing an existing output
+    empty_clips?: EmptyClipMode;
+    vbr_handling?: VbrHandling;
+    transcript_srt?: string | null;
+    clip_chapters?: boolean; // A chapter per clip, titled by clip name
+    // Existing output to extend; its chapters are kept and followed by one per new clip
+    append_to?: string | null;
+    threads?: number | null;
+    low_priority?: boolean;
+    skip_corrupt_clips?: boolean;
+    cap_clip_length?: number | null;
+    trim_inter_clip_silence?: boolean; // Skip dead air at clip edges, keeping inner pauses
+    encrypt?: EncryptConfig | null;
+}
+
+// Chromaprint fingerprint of one clip from a batch
+export interface ClipFingerprint {
+    path: string;
+    fingerprint: string | null;
+    error: string | null;
+}
+
+// Target format for batch_transcode; unset rate/channels keep each file's own
+export interface TranscodeSettings {
+    format: 'mp3' | 'opus';
+    bitrate: string;
+    sample_rate?: number | null;
+    channels?: number | null;
+}
+
+// Details a scanner recorder (SDRTrunk, Trunk Recorder) wrote into a filename
+export interface RecordingMeta {
+    pattern: string; // The template that matched
+    timestamp: number | null; // Unix seconds; SDRTrunk wall-clock time taken as-is
+    talkgroup: number | null;
+    radio_id: number | null;
+    frequency_hz: number | null;
+    label: string | null;
+}
+
+// One file's outcome in batch_transcode
+export interface TranscodeResult {
+    input_path: string;
+    output_path: string | null;
+    success: boolean;
+    error: string | null;
+}
+
+// Result of fully decoding a clip
+export interface IntegrityReport {
+    path: string;
+    ok: boolean;
+    errors: string[];
+}
+
+// How an image had to be turned to appear upright
+export type ImageOrientation =
+    | 'upright'
+    | 'flip_horizontal'
+    | 'rotate180'
+    | 'flip_vertical'
+    | 'transpose'
+    | 'rotate90'
+    | 'transverse'
+    | 'rotate270';
+
+// What to do with clips that have no audio
+export type EmptyClipMode = 'skip' | 'abort';
+
+// What to do when the demuxer would join VBR MP3 clips, whose joined duration can be misreported
+export type VbrHandling = 'warn' | 'filter_path' | 'fix_duration';
+
+// How the animated waveform video draws samples
+export type WaveformMode = 'line' | 'point' | 'cline';
+
+// showspectrum color schemes
+export type SpectrumColor =
+    | 'channel' | 'intensity' | 'rainbow' | 'moreland' | 'nebulae' | 'fire' | 'fiery'
+    | 'fruit' | 'cool' | 'magma' | 'green' | 'viridis' | 'plasma' | 'cividis' | 'terrain';
+
+// Look of the waveform-over-spectrogram video (stitch_spectrogram_video)
+export interface SplitScreenStyle {
+    split_ratio?: number; // Share of the height for the waveform on top, default 0.5
+    waveform_color?: string;
+    waveform_mode?: WaveformMode;
+    spectrum_color?: SpectrumColor; // Default intensity
+}
+
+// Encoding speed and size of one x264 preset
+export interface PresetBenchmark {
+    preset: string;
+    seconds: number;
+    fps: number;
+    bytes_per_second: number;
+}
+
+// Silence detection settings for split_on_silence
+export interface SilenceSplitConfig {
+    threshold_db?: number;
+    min_silence_seconds?: number;
+    merge_short_gaps?: number | null;
+    detect_only?: boolean;
+}
+
+export interface TimeRange {
+    start: number;
+    end: number;
+}
+
+export interface SplitSegment {
+    start: number;
+    end: number;
+    path: string | null;
+}
+
+export interface SilenceSplit {
+    silences: TimeRange[];
+    segments: SplitSegment[];
+}
+
+// Free space on an output's volume
+export interface DiskStatus {
+    mount_point: string;
+    available_bytes: number;
+    total_bytes: number;
+    required_bytes: number;
+    sufficient: boolean;
 }
 
 // Supported audio extensions
@@ -43,18 +349,295 @@ export const BITRATE_OPTIONS = [
 ];
 
 // Export format type
-export type ExportFormat = 'mp3' | 'mp4';
+export type ExportFormat = 'mp3' | 'mp4' | 'opus';
 
 // Image fit mode for video export
 export type ImageFitMode = 'fit' | 'fill';
 
+// Frame shape: 1920x1080, 1080x1920, or 1080x1080
+export type Orientation = 'landscape' | 'portrait' | 'square';
+
 // Supported image extensions
 export const SUPPORTED_IMAGE_EXTENSIONS = ['.png', '.jpg', '.jpeg', '.webp', '.gif'];
 
+// Corner of the frame an overlay (clip counter, watermark) is placed in
+export type Corner = 'top_left' | 'top_right' | 'bottom_left' | 'bottom_right';
+
+// Small logo composited in a corner of the video; PNG transparency is kept
+export interface Watermark {
+    image_path: string;
+    position?: Corner;
+    opacity?: number; // 0-1
+    scale?: number; // Logo width as a fraction of the frame width
+}
+
+// Action-safe (90%) and title-safe (80%) outlines for preview_framing only
+export interface SafeGuides {
+    color?: string; // Default white
+    opacity?: number; // 0-1, default 0.6
+}
+
 // Video configuration for export
 export interface VideoConfig {
     image_path: string | null;
     fit_mode: ImageFitMode;
+    orientation?: Orientation; // Sets the frame size; width/height override either side
+    width?: number | null;
+    height?: number | null;
+    write_manifest?: boolean;
+    video_chapters?: boolean;
+    mux_chapters?: boolean;
+    scrolling_log?: boolean;
+    scroll_speed?: number | null;
+    keyframe_interval?: number | null; // Seconds between keyframes
+    also_export_audio?: AudioExport | null;
+    atomic_write?: boolean;
+    confirm_overwrite?: OverwritePrompt | null; // Asks for chapters and the audio export too
+    aac_profile?: AacProfile | null;
+    aac_encoder?: AacEncoder | null; // Unset: native, or libfdk_aac for the HE profiles
+    aac_vbr?: number | null; // libfdk_aac VBR mode 1-5, in place of the bitrate
+    rate_control?: VideoRateControl | null; // Unset: CRF 12, with bitrate for the audio
+    pad_color?: string | null;
+    threads?: number | null;
+    low_priority?: boolean;
+    clip_counter?: boolean;
+    counter_position?: Corner;
+    counter_font_size?: number | null;
+    watermark?: Watermark | null;
+    background_slideshow?: string[] | null; // Shown in turn instead of image_path
+    slideshow_interval?: number | null; // Seconds per image; unset shows one per clip
+    // 1fps with a single keyframe for the smallest file; seeking may jump to the start
+    minimal_motion?: boolean;
+    creation_time?: string | null; // ISO 8601, e.g. "2024-05-01T12:03:11Z"
+    creation_time_from_clips?: boolean; // Tag with the earliest clip's time when unset
+    location?: GeoTag | null;
+    location_from_clips?: boolean; // Tag with the first clip's location when unset
+    encrypt?: EncryptConfig | null;
+}
+
+// AAC profile for a video's audio track; the HE profiles need libfdk_aac
+export type AacProfile = 'low' | 'he' | 'he_v2';
+
+// AAC encoder preference; fdk falls back to native with a warning when not built
+export type AacEncoder = 'native' | 'fdk' | 'auto';
+
+// Standalone audio rendered alongside a video
+export interface AudioExport {
+    format: 'mp3' | 'opus';
+    bitrate: string | null;
+}
+
+// Concat demuxer compatibility across a clip list
+export interface ConcatPlan {
+    demuxer_compatible: boolean;
+    mismatch_index: number | null;
+    mismatch_property: 'codec' | 'sample_rate' | 'channels' | 'profile' | null;
+    error: string | null;
+}
+
+// Everything needed to reproduce a stitch (manifest sidecar / queued job request)
+export interface StitchManifest {
+    format: ExportFormat;
+    clips: AudioClip[];
+    output_path: string;
+    bitrate: string;
+    audio_config: AudioConfig | null;
+    video_config: VideoConfig | null;
+}
+
+// Batch queue job status
+export type JobStatus = 'pending' | 'running' | 'completed' | 'failed' | 'cancelled';
+
+// Payload of the `stitch-output-chunk` event, sent while a render to `pipe:` is written
+export interface OutputChunk {
+    job_id: string | null;
+    sequence: number;
+    data: number[];
+    done: boolean;  // Set on a final, empty chunk once the render has been written
+}
+
+// Payload of the `stitch-progress` event
+export interface StitchProgress {
+    job_id: string | null;
+    output_path: string;
+    percent: number;
+}
+
+// Payload of the `probe-progress` event, sent as each clip of a batch command finishes
+export interface ProbeProgress {
+    command: string;
+    completed: number;
+    total: number;
+    path: string;
+}
+
+// One recommended output setting and why it was picked
+export interface SettingSuggestion<T> {
+    value: T;
+    rationale: string;
+}
+
+// Output settings matched to the clips' source format (suggest_output_settings)
+export interface SuggestedSettings {
+    bitrate: SettingSuggestion<string>;
+    sample_rate: SettingSuggestion<number>;
+    channels: SettingSuggestion<number>;
+    probed: number;
+}
+
+// Caption appearance for burn_subtitles; unset fields keep libass defaults
+export interface SubtitleStyle {
+    font_name?: string | null;
+    font_size?: number | null;
+    outline?: number | null;
+}
+
+// Levels of one channel in dBFS; null for a silent channel
+export interface ChannelLevel {
+    channel: number;
+    rms_db: number | null;
+    peak_db: number | null;
+}
+
+// Per-channel levels of a file (channel_balance)
+export interface ChannelBalance {
+    channels: ChannelLevel[];
+    delta_db: number | null;
+    warnings: string[];
+}
+
+// A job in the persisted batch queue
+export interface QueuedJob {
+    id: string;
+    request: StitchManifest;
+    status: JobStatus;
+    attempts: number;
+    error: string | null;
+    window_id: string | null;
+}
+
+// How the video encoder spends bits. Bitrates are FFmpeg strings like "2500k"; a bitrate
+// target is capped over two seconds, and the audio falls back to the request's bitrate.
+export type VideoRateControl =
+    | { mode: 'crf'; value: number } // 0 (lossless) to 51
+    | { mode: 'bitrate'; video: string; audio?: string | null };
+
+// How multichannel audio is folded down to mono
+export type DownmixMode =
+    | { mode: 'sum' }
+    | { mode: 'left_only' }
+    | { mode: 'right_only' }
+    | { mode: 'pan'; weights: number[] };
+
+// Loudness measured by loudnorm's analysis pass; null where nothing was measurable
+export interface LoudnessMeasurement {
+    integrated_lufs: number | null;
+    true_peak_db: number | null;
+    lra: number | null;
+}
+
+// One clip of rank_clips_by_loudness's list: silent clips first, then quietest to
+// loudest, then clips that couldn't be measured
+export interface ClipLoudness {
+    index: number; // Position in the list given
+    id: string;
+    path: string;
+    name: string;
+    integrated_lufs: number | null;
+    true_peak_db: number | null;
+    silent: boolean; // Nothing above loudnorm's gate, so no level
+    error: string | null;
+}
+
+// A short render of the first clips with the chosen level settings
+export interface PreviewMix {
+    path: string;
+    clip_count: number;
+    duration: number | null;
+    loudness: LoudnessMeasurement;
+    warnings: string[];
+}
+
+// One input of a described pipeline and the filters it goes through before the join
+export interface PipelineInput {
+    path: string;
+    filters: string[]; // Always empty on the demuxer path
+}
+
+// How an audio stitch would run, from describe_pipeline
+export interface PipelineDescription {
+    concat_method: 'demuxer' | 'filter';
+    stream_copy: boolean; // Copied as-is, no decode or encode
+    inputs: PipelineInput[]; // Output order, without skipped or dropped clips
+    join: string; // Concat filter, crossfade or tone; 'demuxer' on the demuxer path
+    output_filters: string[];
+    notes: string[]; // Why this path, and what it costs
+    warnings: string[];
+}
+
+// Ask before replacing an existing output. The render waits for respond_overwrite
+// after its overwrite-confirm event; no answer within the timeout counts as a no.
+export interface OverwritePrompt {
+    timeout_seconds?: number | null; // Defaults to 30
+}
+
+// Payload of the overwrite-confirm event
+export interface OverwriteConfirm {
+    job_id: string; // Pass to respond_overwrite
+    output_path: string;
+    timeout_seconds: number;
+}
+
+// Where recordings were made; written as an ISO 6709 location tag
+export interface GeoTag {
+    latitude: number; // -90 to 90
+    longitude: number; // -180 to 180
+    altitude?: number | null; // Meters
+}
+
+// Settings for archive_session; loudnorm defaults on when audio_config has none
+export interface ArchiveSettings {
+    bitrate: string;
+    audio_config?: AudioConfig | null;
+    patterns?: string[] | null; // Extra recorder filename templates
+}
+
+export type ArchiveStage = 'scanning' | 'probing' | 'ordering' | 'stitching' | 'done';
+
+// Payload of 'archive-progress' events
+export interface ArchiveProgress {
+    stage: ArchiveStage;
+    clip_count: number;
+}
+
+export interface ArchiveSkip {
+    path: string;
+    reason: string;
+}
+
+// Outcome of a successful archive_session
+export interface ArchiveSummary {
+    output_path: string;
+    manifest_path: string | null;
+    included: string[]; // Clip paths, in output order
+    skipped: ArchiveSkip[];
+    total_duration: number;
+    output_size: number;
+    warnings: string[];
+}
+
+// One of the optional FFmpeg libraries the app relies on
+export interface LibrarySupport {
+    name: string; // Configure name, e.g. 'libopus'
+    present: boolean;
+    needed_for: string;
+}
+
+// What the installed FFmpeg was built with
+export interface BuildInfo {
+    version: string;
+    enabled: string[]; // Every --enable-* flag, without the prefix
+    libraries: LibrarySupport[];
 }
 
 // Image info from FFprobe